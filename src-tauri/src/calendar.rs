@@ -1,14 +1,43 @@
+use crate::meeting_url::{self, MeetingLink};
 use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
-use objc2::rc::Retained;
-use objc2_event_kit::{
-    EKAuthorizationStatus, EKCalendar, EKEntityType, EKEvent, EKEventStatus, EKEventStore,
-};
-use objc2_foundation::{NSArray, NSDate, NSString, NSURL};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+mod caldav;
+mod changes;
+#[cfg(target_os = "macos")]
+mod eventkit;
+mod ics;
+mod range;
+
+pub use caldav::{move_passwords_to_keychain, CalDavAccount, CalDavSource};
+#[cfg(target_os = "macos")]
+pub use eventkit::EventKitSource;
+pub use ics::IcsSource;
+use range::{RangeCache, RangeKey};
+
+// Resync once change notifications have been quiet this long...
+const CHANGE_QUIET_MS: u64 = 1500;
+// ...but no later than this after the first one of a burst
+const CHANGE_MAX_WAIT_SECS: u64 = 10;
+// EventKit truncates predicates spanning more than four years
+const MAX_PREDICATE_SPAN_DAYS: i64 = 4 * 365;
+const RANGE_CACHE_TTL_SECS: u64 = 5 * 60;
+const RANGE_CACHE_CAPACITY: usize = 16;
+/// Days after today covered by the background sync unless configured otherwise.
+pub const DEFAULT_WINDOW_DAYS: u32 = 7;
+
+/// Tauri event emitted when a sync changed the cached events; payload `EventsDiff`.
+pub const EVENTS_UPDATED: &str = "events-updated";
+/// Tauri event emitted when the calendar permission status changes; payload `PermissionChanged`.
+pub const PERMISSION_CHANGED: &str = "permission-changed";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CalendarEvent {
     pub id: String,
@@ -25,9 +54,117 @@ pub struct CalendarEvent {
     pub calendar_name: Option<String>,
     pub calendar_account_name: Option<String>,
     pub external_url: Option<String>,
+    /// Detected meeting link; filled in by `enabled_events` from the provider registry.
+    #[serde(default)]
+    pub meeting: Option<MeetingLink>,
+    #[serde(default)]
+    pub attendees: Vec<Participant>,
+    #[serde(default)]
+    pub organizer: Option<Participant>,
+    /// The current user's response, or None when the event isn't an invitation
+    /// (or the source can't tell who "me" is).
+    #[serde(default)]
+    pub self_status: Option<ParticipationStatus>,
+    #[serde(default)]
+    pub is_recurring: bool,
+    /// Shared by every occurrence of a recurring event.
+    #[serde(default)]
+    pub series_id: Option<String>,
+    #[serde(default)]
+    pub alarms: Vec<Alarm>,
+    #[serde(default)]
+    pub availability: Option<Availability>,
+    /// IANA time zone of the event; None for floating times and all-day events.
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl CalendarEvent {
+    /// Mark the attendee/organizer with `email` as the user and fill in
+    /// `self_status`, for sources that don't know who the user is.
+    pub fn identify_self(&mut self, email: &str) {
+        let matches = |p: &Participant| {
+            p.email
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(email))
+        };
+        for attendee in &mut self.attendees {
+            attendee.is_self = matches(attendee);
+        }
+        if let Some(organizer) = &mut self.organizer {
+            organizer.is_self = matches(organizer);
+        }
+        self.self_status = self
+            .attendees
+            .iter()
+            .find(|a| a.is_self)
+            .map(|a| a.status)
+            .or_else(|| {
+                self.organizer
+                    .as_ref()
+                    .filter(|o| o.is_self)
+                    .map(|_| ParticipationStatus::Accepted)
+            });
+    }
+}
+
+/// An invitee's response (EventKit `EKParticipantStatus`, iCalendar `PARTSTAT`).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParticipationStatus {
+    Unknown,
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+    Delegated,
+    Completed,
+    InProcess,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParticipantRole {
+    Unknown,
+    Required,
+    Optional,
+    Chair,
+    NonParticipant,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Participant {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub role: ParticipantRole,
+    pub status: ParticipationStatus,
+    /// True for the account the calendar belongs to.
+    #[serde(default)]
+    pub is_self: bool,
+}
+
+/// How the event shows up in free/busy lookups.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Availability {
+    Busy,
+    Free,
+    Tentative,
+    Unavailable,
+}
+
+/// An alarm set on the event itself (not Galopen's own reminders).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Alarm {
+    /// Minutes before the start; negative fires after it. None for absolute alarms.
+    pub minutes_before: Option<i64>,
+    /// RFC 3339 time of an absolute alarm.
+    pub at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct EventDateTime {
     pub date_time: Option<String>,
@@ -42,82 +179,433 @@ pub struct CalendarInfo {
     pub source_name: String,
 }
 
-// Commands sent to the dedicated EventKit thread
+/// Invitations the user doesn't want auto-opened or reminded about, based on
+/// their own response (`skipDeclined`, `skipTentative`, `skipNeedsAction`
+/// settings). With `hideSkippedInvitations` they are dropped from the list too.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvitationFilter {
+    pub declined: bool,
+    pub tentative: bool,
+    pub needs_action: bool,
+    pub hide: bool,
+}
+
+impl Default for InvitationFilter {
+    fn default() -> Self {
+        Self::from_settings(&crate::settings::Settings::default())
+    }
+}
+
+impl InvitationFilter {
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        Self::from_settings(&crate::settings::get(app))
+    }
+
+    pub fn from_settings(settings: &crate::settings::Settings) -> Self {
+        Self {
+            declined: settings.skip_declined,
+            tentative: settings.skip_tentative,
+            needs_action: settings.skip_needs_action,
+            hide: settings.hide_skipped_invitations,
+        }
+    }
+
+    /// Events that aren't invitations (no `self_status`) are never skipped.
+    pub fn skips(&self, event: &CalendarEvent) -> bool {
+        match event.self_status {
+            Some(ParticipationStatus::Declined) => self.declined,
+            Some(ParticipationStatus::Tentative) => self.tentative,
+            Some(ParticipationStatus::NeedsAction) => self.needs_action,
+            _ => false,
+        }
+    }
+
+    /// Whether `event` should appear in the event list.
+    pub fn shows(&self, event: &CalendarEvent) -> bool {
+        !(self.hide && self.skips(event))
+    }
+}
+
+/// Event ids that a sync added, removed or changed.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl EventsDiff {
+    pub fn between(old: &[CalendarEvent], new: &[CalendarEvent]) -> Self {
+        let old_by_id: HashMap<&str, &CalendarEvent> =
+            old.iter().map(|e| (e.id.as_str(), e)).collect();
+        let new_ids: HashSet<&str> = new.iter().map(|e| e.id.as_str()).collect();
+        let mut diff = Self::default();
+        for event in new {
+            match old_by_id.get(event.id.as_str()) {
+                None => diff.added.push(event.id.clone()),
+                Some(prev) if *prev != event => diff.changed.push(event.id.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|e| !new_ids.contains(e.id.as_str()))
+            .map(|e| e.id.clone())
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PermissionChanged {
+    /// Same values as `check_calendar_permission`.
+    pub status: String,
+}
+
+/// A backend that can provide calendar events and calendar metadata.
+///
+/// Sources are created on, and only used from, the dedicated calendar thread
+/// spawned by `CalendarState`, so they don't need to be `Send`. That lets
+/// EventKit (whose store is not thread-safe) and plain Rust sources share the
+/// same plumbing behind `sync_events`, `get_calendars` and the scheduler.
+pub trait CalendarSource {
+    /// Events overlapping `[start, end]`. Ordering and cancelled-event
+    /// filtering are handled by the caller.
+    fn fetch_events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String>;
+
+    /// Like `fetch_events`, limited to `calendar_ids` when given. Sources that
+    /// can filter natively (EventKit predicates) override this.
+    fn fetch_events_in(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let mut events = self.fetch_events(start, end)?;
+        if calendar_ids.is_some() {
+            events.retain(|e| is_calendar_enabled(e, calendar_ids));
+        }
+        Ok(events)
+    }
+
+    fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String>;
+
+    /// One of "granted", "denied", "restricted" or "not_determined".
+    fn check_permission(&mut self) -> Result<String, String>;
+
+    fn request_permission(&mut self) -> Result<bool, String>;
+
+    /// Send on `changed` whenever the backend's data changes outside the app.
+    /// Sources without change notifications rely on the scheduler's periodic poll.
+    fn watch_changes(&mut self, _changed: mpsc::Sender<()>) {}
+
+    /// Ask the backend to pull remote changes, from the scheduler's periodic
+    /// poll. Whatever it finds is reported through `watch_changes`.
+    fn refresh(&mut self) {}
+}
+
+/// Combines several sources into one, e.g. EventKit plus local ICS files.
+///
+/// A failing or panicking source is logged and stood in for by its last good
+/// result, so one flaky backend neither hides the others' events nor makes
+/// its own look deleted until it recovers. Permission is "granted" if any
+/// source has access.
+pub struct MultiSource {
+    sources: Vec<Box<dyn CalendarSource>>,
+    /// Per source, in the same order
+    last_good: Vec<LastGood>,
+}
+
+/// What a source last returned successfully.
+#[derive(Default)]
+struct LastGood {
+    events: Vec<CalendarEvent>,
+    calendars: Vec<CalendarInfo>,
+}
+
+impl LastGood {
+    /// Replace what was kept for the queried range with `fresh`.
+    fn keep_events(
+        &mut self,
+        fresh: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) {
+        let fresh_ids: HashSet<&str> = fresh.iter().map(|e| e.id.as_str()).collect();
+        let replaced = |e: &CalendarEvent| {
+            fresh_ids.contains(e.id.as_str())
+                || (overlaps(e, start, end) && is_calendar_enabled(e, calendar_ids))
+        };
+        self.events.retain(|e| !replaced(e));
+        self.events.extend_from_slice(fresh);
+    }
+
+    fn events_in(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) -> Vec<CalendarEvent> {
+        self.events
+            .iter()
+            .filter(|e| overlaps(e, start, end) && is_calendar_enabled(e, calendar_ids))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Whether `event` overlaps `[start, end]`. Events whose times can't be read
+/// count as overlapping.
+fn overlaps(event: &CalendarEvent, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    let time = |t: &EventDateTime, day_end: bool| -> Option<DateTime<Utc>> {
+        if let Some(dt) = &t.date_time {
+            return DateTime::parse_from_rfc3339(dt).ok().map(|t| t.with_timezone(&Utc));
+        }
+        let date = NaiveDate::parse_from_str(t.date.as_deref()?, "%Y-%m-%d").ok()?;
+        // All-day events list their last day; it lasts until the next midnight
+        let date = if day_end { date.succ_opt()? } else { date };
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+    };
+    match (time(&event.start, false), time(&event.end, true)) {
+        (Some(s), Some(e)) => s <= end && e >= start,
+        _ => true,
+    }
+}
+
+impl MultiSource {
+    pub fn new(sources: Vec<Box<dyn CalendarSource>>) -> Self {
+        let last_good = sources.iter().map(|_| LastGood::default()).collect();
+        Self { sources, last_good }
+    }
+
+    /// Call `f` on every source. A success is handed to `keep`; a failed
+    /// source contributes `stand_in` from what was kept instead.
+    fn collect<T>(
+        &mut self,
+        mut f: impl FnMut(&mut dyn CalendarSource) -> Result<Vec<T>, String>,
+        mut keep: impl FnMut(&mut LastGood, &[T]),
+        stand_in: impl Fn(&LastGood) -> Vec<T>,
+    ) -> Result<Vec<T>, String> {
+        let mut items = Vec::new();
+        let mut first_err = None;
+        let mut any_ok = false;
+        for (source, last_good) in self.sources.iter_mut().zip(&mut self.last_good) {
+            match guarded(|| f(source.as_mut())) {
+                Ok(v) => {
+                    any_ok = true;
+                    keep(last_good, &v);
+                    items.extend(v);
+                }
+                Err(e) => {
+                    log::warn!("Calendar source failed, reusing its last result: {}", e);
+                    items.extend(stand_in(last_good));
+                    first_err.get_or_insert(e);
+                }
+            }
+        }
+        if any_ok {
+            Ok(items)
+        } else {
+            Err(first_err.unwrap_or_else(|| "No calendar source configured".to_string()))
+        }
+    }
+}
+
+impl CalendarSource for MultiSource {
+    fn fetch_events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        self.fetch_events_in(start, end, None)
+    }
+
+    fn fetch_events_in(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        self.collect(
+            |s| s.fetch_events_in(start, end, calendar_ids),
+            |last, events| last.keep_events(events, start, end, calendar_ids),
+            |last| last.events_in(start, end, calendar_ids),
+        )
+    }
+
+    fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+        self.collect(
+            |s| s.fetch_calendars(),
+            |last, calendars| last.calendars = calendars.to_vec(),
+            |last| last.calendars.clone(),
+        )
+    }
+
+    fn check_permission(&mut self) -> Result<String, String> {
+        let statuses: Vec<String> = self
+            .sources
+            .iter_mut()
+            .filter_map(|s| guarded(|| s.check_permission()).ok())
+            .collect();
+        if statuses.iter().any(|s| s == "granted") {
+            return Ok("granted".to_string());
+        }
+        Ok(statuses
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "restricted".to_string()))
+    }
+
+    fn request_permission(&mut self) -> Result<bool, String> {
+        let mut granted = false;
+        for source in &mut self.sources {
+            granted |= guarded(|| source.request_permission()).unwrap_or(false);
+        }
+        Ok(granted)
+    }
+
+    fn watch_changes(&mut self, changed: mpsc::Sender<()>) {
+        for source in &mut self.sources {
+            source.watch_changes(changed.clone());
+        }
+    }
+
+    fn refresh(&mut self) {
+        for source in &mut self.sources {
+            let _ = guarded(|| {
+                source.refresh();
+                Ok(())
+            });
+        }
+    }
+}
+
+/// Which calendar backends to enable, read from settings at startup.
+#[derive(Default, Clone, Debug)]
+pub struct SourceConfig {
+    /// `.ics` files or directories of them (see `IcsSource`).
+    pub ics_paths: Vec<PathBuf>,
+    pub caldav_accounts: Vec<CalDavAccount>,
+}
+
+// Commands sent to the dedicated calendar thread
 enum CalendarCommand {
-    FetchToday(mpsc::Sender<Result<Vec<CalendarEvent>, String>>),
+    FetchEvents(
+        DateTime<Utc>,
+        DateTime<Utc>,
+        Option<HashSet<String>>,
+        mpsc::Sender<Result<Vec<CalendarEvent>, String>>,
+    ),
     FetchCalendars(mpsc::Sender<Result<Vec<CalendarInfo>, String>>),
     CheckPermission(mpsc::Sender<Result<String, String>>),
     RequestPermission(mpsc::Sender<Result<bool, String>>),
+    Refresh,
 }
 
 pub struct CalendarState {
     pub events: Mutex<Vec<CalendarEvent>>,
+    /// Signalled when a sync changes `events`, so the scheduler can recompute its deadlines.
+    pub updated: tokio::sync::Notify,
     last_sync_date: Mutex<Option<NaiveDate>>,
     command_tx: mpsc::Sender<CalendarCommand>,
+    // Taken by `watch_changes`; disconnected if no source reports changes
+    changes_rx: Mutex<Option<mpsc::Receiver<()>>>,
+    // Last permission status seen, to emit `PERMISSION_CHANGED` only on changes
+    permission: Mutex<Option<String>>,
+    // Days after today that `sync_events` fetches
+    window_days: Mutex<u32>,
+    range_cache: Mutex<RangeCache>,
 }
 
 impl CalendarState {
-    pub fn new() -> Self {
+    /// EventKit (on macOS) plus whatever extra sources `config` enables.
+    pub fn new(config: SourceConfig) -> Self {
+        Self::with_source(move || {
+            let mut sources: Vec<Box<dyn CalendarSource>> = Vec::new();
+            #[cfg(target_os = "macos")]
+            sources.push(Box::new(EventKitSource::new()));
+            if !config.ics_paths.is_empty() {
+                sources.push(Box::new(IcsSource::new(config.ics_paths)));
+            }
+            if !config.caldav_accounts.is_empty() {
+                sources.push(Box::new(CalDavSource::new(config.caldav_accounts)));
+            }
+            MultiSource::new(sources)
+        })
+    }
+
+    /// Change how many days after today `sync_events` covers. Returns true if it changed.
+    pub fn set_window_days(&self, days: u32) -> bool {
+        let mut current = self.window_days.lock().unwrap();
+        let changed = *current != days;
+        *current = days;
+        changed
+    }
+
+    /// Spawn the calendar thread and build the source on it.
+    ///
+    /// The source is constructed inside the thread so non-Send backends
+    /// (EKEventStore) never cross threads. All source calls are wrapped in
+    /// catch_unwind so a panicking backend can't take the thread down.
+    pub fn with_source<S, F>(make_source: F) -> Self
+    where
+        S: CalendarSource + 'static,
+        F: FnOnce() -> S + Send + 'static,
+    {
         let (tx, rx) = mpsc::channel::<CalendarCommand>();
+        let (changes_tx, changes_rx) = mpsc::channel();
 
-        // Spawn a dedicated thread that owns the EKEventStore.
-        // EKEventStore is not Send/Sync, so all EventKit calls happen here.
-        // All EventKit calls are wrapped in both catch_unwind (Rust panics)
-        // and exception::catch (ObjC exceptions) to prevent crashes.
         std::thread::spawn(move || {
-            let store = unsafe { EKEventStore::new() };
+            let mut source = make_source();
+            source.watch_changes(changes_tx);
             for cmd in rx {
                 match cmd {
-                    CalendarCommand::FetchToday(reply) => {
-                        let result =
-                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
-                                objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
-                                    fetch_todays_events_inner(&store)
-                                }))
-                            }));
-                        let _ = reply.send(match result {
-                            Ok(Ok(v)) => v,
-                            Ok(Err(e)) => Err(format!("ObjC exception: {:?}", e)),
-                            Err(_) => Err("EventKit panic".to_string()),
-                        });
+                    CalendarCommand::FetchEvents(start, end, calendar_ids, reply) => {
+                        let max_span = chrono::Duration::days(MAX_PREDICATE_SPAN_DAYS);
+                        let result = range::chunk_range(start, end, max_span)
+                            .into_iter()
+                            .try_fold(Vec::new(), |mut all, (from, to)| {
+                                let ids = calendar_ids.as_ref();
+                                all.extend(guarded(|| source.fetch_events_in(from, to, ids))?);
+                                Ok::<_, String>(all)
+                            })
+                            .map(|mut events| {
+                                range::dedup_by_id(&mut events);
+                                events.retain(|e| e.status.as_deref() != Some("cancelled"));
+                                sort_events(&mut events);
+                                events
+                            });
+                        let _ = reply.send(result);
                     }
                     CalendarCommand::FetchCalendars(reply) => {
-                        let result =
-                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
-                                objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
-                                    fetch_calendars_inner(&store)
-                                }))
-                            }));
-                        let _ = reply.send(match result {
-                            Ok(Ok(v)) => v,
-                            Ok(Err(e)) => Err(format!("ObjC exception: {:?}", e)),
-                            Err(_) => Err("EventKit panic".to_string()),
+                        let result = guarded(|| source.fetch_calendars()).map(|mut cals| {
+                            cals.sort_by(|a, b| {
+                                a.source_name.cmp(&b.source_name).then(a.title.cmp(&b.title))
+                            });
+                            cals
                         });
+                        let _ = reply.send(result);
                     }
                     CalendarCommand::CheckPermission(reply) => {
-                        let result =
-                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
-                                objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
-                                    check_permission_inner()
-                                }))
-                            }));
-                        let _ = reply.send(match result {
-                            Ok(Ok(v)) => v,
-                            Ok(Err(e)) => Err(format!("ObjC exception: {:?}", e)),
-                            Err(_) => Err("EventKit panic".to_string()),
-                        });
+                        let _ = reply.send(guarded(|| source.check_permission()));
                     }
                     CalendarCommand::RequestPermission(reply) => {
-                        let result =
-                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
-                                objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
-                                    request_permission_inner(&store)
-                                }))
-                            }));
-                        let _ = reply.send(match result {
-                            Ok(Ok(v)) => v,
-                            Ok(Err(e)) => Err(format!("ObjC exception: {:?}", e)),
-                            Err(_) => Err("EventKit panic".to_string()),
+                        let _ = reply.send(guarded(|| source.request_permission()));
+                    }
+                    CalendarCommand::Refresh => {
+                        let _ = guarded(|| {
+                            source.refresh();
+                            Ok(())
                         });
                     }
                 }
@@ -126,190 +614,55 @@ impl CalendarState {
 
         Self {
             events: Mutex::new(Vec::new()),
+            updated: tokio::sync::Notify::new(),
             last_sync_date: Mutex::new(None),
             command_tx: tx,
+            changes_rx: Mutex::new(Some(changes_rx)),
+            permission: Mutex::new(None),
+            window_days: Mutex::new(DEFAULT_WINDOW_DAYS),
+            range_cache: Mutex::new(RangeCache::new(
+                Duration::from_secs(RANGE_CACHE_TTL_SECS),
+                RANGE_CACHE_CAPACITY,
+            )),
         }
     }
 }
 
-fn nsdate_to_chrono(nsdate: &NSDate) -> DateTime<Utc> {
-    let timestamp = nsdate.timeIntervalSince1970();
-    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default()
-}
-
-fn ekevent_to_calendar_event(event: &EKEvent) -> Option<CalendarEvent> {
-    // Use msg_send! with Option types for properties that may return nil
-    // to avoid panics from objc2's non-null Retained assertions.
-    let title: Option<Retained<NSString>> = unsafe { objc2::msg_send![event, title] };
-    let title = title.map(|s| s.to_string()).unwrap_or_default();
-    let start_date: Option<Retained<NSDate>> = unsafe { objc2::msg_send![event, startDate] };
-    let end_date: Option<Retained<NSDate>> = unsafe { objc2::msg_send![event, endDate] };
-    let start_date = start_date?;
-    let end_date = end_date?;
-    let is_all_day = unsafe { event.isAllDay() };
-    let event_id_base = unsafe { event.eventIdentifier() }
-        .map(|s| s.to_string())
-        .unwrap_or_default();
-
-    let location = unsafe { event.location() }.map(|s| s.to_string());
-    let notes = unsafe { event.notes() }.map(|s| s.to_string());
-    let url = unsafe { event.URL() }
-        .and_then(|u| u.absoluteString().map(|s| s.to_string()));
-
-    let start_chrono = nsdate_to_chrono(&start_date);
-    let end_chrono = nsdate_to_chrono(&end_date);
-
-    // Recurring events share the same eventIdentifier, so append start time to make unique
-    let event_id = format!("{}_{}", event_id_base, start_chrono.timestamp());
-
-    let status_raw = unsafe { event.status() };
-    let status = if status_raw == EKEventStatus::Confirmed {
-        Some("confirmed".to_string())
-    } else if status_raw == EKEventStatus::Tentative {
-        Some("tentative".to_string())
-    } else if status_raw == EKEventStatus::Canceled {
-        Some("cancelled".to_string())
-    } else {
-        None
-    };
-
-    let cal: Option<Retained<EKCalendar>> = unsafe { objc2::msg_send![event, calendar] };
-    let calendar_id = cal
-        .as_ref()
-        .map(|c| unsafe { c.calendarIdentifier() }.to_string());
-    let calendar_name = cal.as_ref().and_then(|c| {
-        let t: Option<Retained<NSString>> = unsafe { objc2::msg_send![c, title] };
-        t.map(|s| s.to_string())
-    });
-    let calendar_account_name = cal
-        .as_ref()
-        .and_then(|c| unsafe { c.source() })
-        .map(|s| unsafe { s.title() }.to_string());
-
-    // calendarItemExternalURI - use objc2 exception handling to avoid crash
-    let external_url: Option<String> = unsafe {
-        objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
-            let uri: Option<Retained<NSURL>> = objc2::msg_send![event, calendarItemExternalURI];
-            uri.and_then(|u| u.absoluteString().map(|s| s.to_string()))
-        }))
-        .unwrap_or(None)
-    };
-
-    Some(CalendarEvent {
-        id: event_id,
-        summary: title,
-        start: EventDateTime {
-            date_time: if !is_all_day {
-                Some(start_chrono.to_rfc3339())
-            } else {
-                None
-            },
-            // For all-day events, format the date in the local timezone
-            // (NSDate stores UTC; midnight-local is 15:00 UTC the day before in JST,
-            // so formatting the UTC value would yield the wrong day).
-            date: if is_all_day {
-                Some(start_chrono.with_timezone(&Local).format("%Y-%m-%d").to_string())
-            } else {
-                None
-            },
-        },
-        end: EventDateTime {
-            date_time: if !is_all_day {
-                Some(end_chrono.to_rfc3339())
-            } else {
-                None
-            },
-            date: if is_all_day {
-                Some(end_chrono.with_timezone(&Local).format("%Y-%m-%d").to_string())
-            } else {
-                None
-            },
-        },
-        description: notes,
-        location,
-        url,
-        is_all_day,
-        status,
-        calendar_id,
-        calendar_name,
-        calendar_account_name,
-        external_url,
-    })
-}
-
-#[allow(deprecated)]
-fn check_permission_inner() -> Result<String, String> {
-    let status =
-        unsafe { EKEventStore::authorizationStatusForEntityType(EKEntityType::Event) };
-    let s = if status == EKAuthorizationStatus::FullAccess
-        || status == EKAuthorizationStatus::Authorized
-    {
-        "granted"
-    } else if status == EKAuthorizationStatus::Denied {
-        "denied"
-    } else if status == EKAuthorizationStatus::Restricted {
-        "restricted"
-    } else {
-        "not_determined"
-    };
-    Ok(s.to_string())
-}
-
-fn request_permission_inner(store: &EKEventStore) -> Result<bool, String> {
-    let (tx, rx) = mpsc::channel();
-    let tx_arc = std::sync::Arc::new(std::sync::Mutex::new(Some(tx)));
-    let tx_clone = tx_arc.clone();
-
-    let completion = block2::RcBlock::new(
-        move |granted: objc2::runtime::Bool, _error: *mut objc2_foundation::NSError| {
-            if let Some(sender) = tx_clone.lock().unwrap().take() {
-                let _ = sender.send(granted.as_bool());
-            }
-        },
-    );
-
-    unsafe {
-        let _: () =
-            objc2::msg_send![store, requestFullAccessToEventsWithCompletion: &*completion];
+fn guarded<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(v) => v,
+        Err(_) => Err("Calendar source panic".to_string()),
     }
-
-    rx.recv()
-        .map_err(|e| format!("Permission request failed: {}", e))
 }
 
-fn fetch_calendars_inner(store: &EKEventStore) -> Result<Vec<CalendarInfo>, String> {
-    let calendars =
-        unsafe { store.calendarsForEntityType(EKEntityType::Event) };
-
-    let mut result: Vec<CalendarInfo> = calendars
-        .iter()
-        .map(|cal| {
-            let id = unsafe { cal.calendarIdentifier() }.to_string();
-            let title = unsafe { cal.title() }.to_string();
-            let source_name = unsafe { cal.source() }
-                .map(|s| unsafe { s.title() }.to_string())
-                .unwrap_or_default();
-            CalendarInfo {
-                id,
-                title,
-                source_name,
-            }
-        })
-        .collect();
-
-    result.sort_by(|a, b| a.source_name.cmp(&b.source_name).then(a.title.cmp(&b.title)));
-    Ok(result)
+fn sort_events(events: &mut [CalendarEvent]) {
+    events.sort_by(|a, b| {
+        let a_time = a
+            .start
+            .date_time
+            .as_deref()
+            .or(a.start.date.as_deref())
+            .unwrap_or("");
+        let b_time = b
+            .start
+            .date_time
+            .as_deref()
+            .or(b.start.date.as_deref())
+            .unwrap_or("");
+        a_time.cmp(b_time)
+    });
 }
 
-fn fetch_todays_events_inner(
-    store: &EKEventStore,
-) -> Result<Vec<CalendarEvent>, String> {
-    // Fetch a wide range so the UI can display today / +1 / +2 days, plus extra
-    // days when "weekdays only" mode skips weekends/holidays. The scheduler
-    // only fires for events approaching their start time, so the wider range
-    // does not affect auto-open behavior.
+/// The range the scheduler and UI work from: start of today through the end
+/// of today + `window_days` (local time, 7 by default).
+///
+/// The UI displays today / +1 / +2 days, plus extra days when "weekdays only"
+/// mode skips weekends/holidays. The scheduler only fires for events
+/// approaching their start time, so the wider range does not affect
+/// auto-open behavior. Other ranges go through `get_events_in_range`.
+fn sync_range(window_days: u32) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
     let today = Local::now().date_naive();
-    let end_day = today + chrono::Duration::days(7);
+    let end_day = today + chrono::Duration::days(window_days.into());
     let start_of_day = today
         .and_hms_opt(0, 0, 0)
         .ok_or("Failed to create start of day")?;
@@ -328,91 +681,150 @@ fn fetch_todays_events_inner(
         .ok_or("Failed to convert end to UTC")?
         .with_timezone(&Utc);
 
-    let start_nsdate = NSDate::dateWithTimeIntervalSince1970(start_utc.timestamp() as f64);
-    let end_nsdate = NSDate::dateWithTimeIntervalSince1970(end_utc.timestamp() as f64);
-
-    // Ask macOS to pull latest data from remote sources (Google, iCloud, etc.)
-    unsafe { store.refreshSourcesIfNecessary() };
-    // Reset cached data so external changes are picked up
-    unsafe { store.reset() };
+    Ok((start_utc, end_utc))
+}
 
-    let predicate = unsafe {
-        store.predicateForEventsWithStartDate_endDate_calendars(
-            &start_nsdate,
-            &end_nsdate,
-            None::<&NSArray<EKCalendar>>,
-        )
+/// Refetch the default range into `events`. Returns what changed; callers with
+/// an app handle pass it to `emit_events_updated`.
+pub fn sync_events(calendar_state: &CalendarState) -> Result<EventsDiff, String> {
+    let window_days = *calendar_state.window_days.lock().unwrap();
+    let (start, end) = sync_range(window_days)?;
+    let events = fetch_range(calendar_state, start, end, None)?;
+    let diff = {
+        let mut current = calendar_state.events.lock().unwrap();
+        let diff = EventsDiff::between(&current, &events);
+        *current = events;
+        diff
     };
+    if !diff.is_empty() {
+        calendar_state.range_cache.lock().unwrap().clear();
+        calendar_state.updated.notify_one();
+    }
+    *calendar_state.last_sync_date.lock().unwrap() = Some(Local::now().date_naive());
+    Ok(diff)
+}
 
-    let ek_events = unsafe { store.eventsMatchingPredicate(&predicate) };
-
-    let mut events: Vec<CalendarEvent> = ek_events
-        .iter()
-        .filter_map(|e| ekevent_to_calendar_event(&e))
-        .filter(|e| e.status.as_deref() != Some("cancelled"))
-        .collect();
-
-    events.sort_by(|a, b| {
-        let a_time = a
-            .start
-            .date_time
-            .as_deref()
-            .or(a.start.date.as_deref())
-            .unwrap_or("");
-        let b_time = b
-            .start
-            .date_time
-            .as_deref()
-            .or(b.start.date.as_deref())
-            .unwrap_or("");
-        a_time.cmp(b_time)
-    });
-
-    Ok(events)
+/// Have the sources pull remote changes in the background; see
+/// `CalendarSource::refresh`.
+pub fn refresh_sources(calendar_state: &CalendarState) {
+    if let Err(e) = calendar_state.command_tx.send(CalendarCommand::Refresh) {
+        log::warn!("Failed to refresh calendar sources: {}", e);
+    }
 }
 
-pub fn sync_events(calendar_state: &CalendarState) -> Result<(), String> {
+fn fetch_range(
+    calendar_state: &CalendarState,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    calendar_ids: Option<HashSet<String>>,
+) -> Result<Vec<CalendarEvent>, String> {
     let (tx, rx) = mpsc::channel();
     calendar_state
         .command_tx
-        .send(CalendarCommand::FetchToday(tx))
+        .send(CalendarCommand::FetchEvents(start, end, calendar_ids, tx))
         .map_err(|e| e.to_string())?;
-    let events = rx.recv().map_err(|e| e.to_string())??;
-    *calendar_state.events.lock().unwrap() = events;
-    *calendar_state.last_sync_date.lock().unwrap() = Some(Local::now().date_naive());
-    Ok(())
+    rx.recv().map_err(|e| e.to_string())?
 }
 
-pub fn has_permission(calendar_state: &CalendarState) -> bool {
+/// Tell every window that the cached events changed.
+pub fn emit_events_updated(app: &tauri::AppHandle, diff: &EventsDiff) {
+    if diff.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(EVENTS_UPDATED, diff) {
+        log::warn!("Failed to emit {}: {}", EVENTS_UPDATED, e);
+    }
+}
+
+/// Resync whenever a source reports external changes (debounced), so a meeting
+/// added or moved shortly before it starts is picked up without waiting for
+/// the next poll. Syncs that change the events wake the scheduler through
+/// `CalendarState::updated` and tell the frontend via `EVENTS_UPDATED`.
+pub fn watch_changes(app: tauri::AppHandle) {
+    let Some(rx) = app.state::<CalendarState>().changes_rx.lock().unwrap().take() else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let debouncer = changes::Debouncer::new(
+            Duration::from_millis(CHANGE_QUIET_MS),
+            Duration::from_secs(CHANGE_MAX_WAIT_SECS),
+        );
+        changes::run_debounced(rx, debouncer, || {
+            let state = app.state::<CalendarState>();
+            // The change may be outside the synced window, so range queries refetch too
+            state.range_cache.lock().unwrap().clear();
+            if !has_permission(&state) {
+                return;
+            }
+            match sync_events(&state) {
+                Ok(diff) => {
+                    if !diff.is_empty() {
+                        log::info!("Calendar changed, events resynced");
+                    }
+                    emit_events_updated(&app, &diff);
+                }
+                Err(e) => log::error!("Calendar sync after change failed: {}", e),
+            }
+        });
+    });
+}
+
+fn permission_status(calendar_state: &CalendarState) -> Result<String, String> {
     let (tx, rx) = mpsc::channel();
-    if calendar_state
+    calendar_state
         .command_tx
         .send(CalendarCommand::CheckPermission(tx))
-        .is_err()
-    {
-        return false;
+        .map_err(|e| e.to_string())?;
+    rx.recv().map_err(|e| e.to_string())?
+}
+
+/// Remember `status` and emit `PERMISSION_CHANGED` if it differs from the last one seen.
+fn record_permission(app: &tauri::AppHandle, calendar_state: &CalendarState, status: &str) {
+    let mut last = calendar_state.permission.lock().unwrap();
+    if last.as_deref() == Some(status) {
+        return;
+    }
+    *last = Some(status.to_string());
+    drop(last);
+    let payload = PermissionChanged {
+        status: status.to_string(),
+    };
+    if let Err(e) = app.emit(PERMISSION_CHANGED, payload) {
+        log::warn!("Failed to emit {}: {}", PERMISSION_CHANGED, e);
     }
-    rx.recv()
+}
+
+/// Like `has_permission`, but also emits `PERMISSION_CHANGED` when the status changed.
+pub fn check_permission(app: &tauri::AppHandle, calendar_state: &CalendarState) -> bool {
+    match permission_status(calendar_state) {
+        Ok(status) => {
+            record_permission(app, calendar_state, &status);
+            status == "granted"
+        }
+        Err(_) => false,
+    }
+}
+
+pub fn has_permission(calendar_state: &CalendarState) -> bool {
+    permission_status(calendar_state)
         .ok()
-        .and_then(|r| r.ok())
         .map(|s| s == "granted")
         .unwrap_or(false)
 }
 
 #[tauri::command]
 pub async fn check_calendar_permission(
+    app: tauri::AppHandle,
     calendar_state: tauri::State<'_, CalendarState>,
 ) -> Result<String, String> {
-    let (tx, rx) = mpsc::channel();
-    calendar_state
-        .command_tx
-        .send(CalendarCommand::CheckPermission(tx))
-        .map_err(|e| e.to_string())?;
-    rx.recv().map_err(|e| e.to_string())?
+    let status = permission_status(&calendar_state)?;
+    record_permission(&app, &calendar_state, &status);
+    Ok(status)
 }
 
 #[tauri::command]
 pub async fn request_calendar_permission(
+    app: tauri::AppHandle,
     calendar_state: tauri::State<'_, CalendarState>,
 ) -> Result<bool, String> {
     let (tx, rx) = mpsc::channel();
@@ -420,7 +832,9 @@ pub async fn request_calendar_permission(
         .command_tx
         .send(CalendarCommand::RequestPermission(tx))
         .map_err(|e| e.to_string())?;
-    rx.recv().map_err(|e| e.to_string())?
+    let granted = rx.recv().map_err(|e| e.to_string())??;
+    check_permission(&app, &calendar_state);
+    Ok(granted)
 }
 
 #[tauri::command]
@@ -435,17 +849,483 @@ pub async fn get_calendars(
     rx.recv().map_err(|e| e.to_string())?
 }
 
+/// Calendar ids enabled in the calendar filter, or None when the user hasn't
+/// narrowed it down (everything enabled).
+pub fn enabled_calendars(app: &tauri::AppHandle) -> Option<HashSet<String>> {
+    crate::settings::get(app)
+        .enabled_calendars
+        .map(|ids| ids.into_iter().collect())
+}
+
+/// Events without a calendar id are always kept.
+pub fn is_calendar_enabled(event: &CalendarEvent, enabled: Option<&HashSet<String>>) -> bool {
+    match (enabled, event.calendar_id.as_ref()) {
+        (Some(ids), Some(id)) => ids.contains(id),
+        _ => true,
+    }
+}
+
+/// Cached events from calendars the user has enabled, minus hidden invitations.
+pub fn enabled_events(app: &tauri::AppHandle, calendar_state: &CalendarState) -> Vec<CalendarEvent> {
+    let enabled = enabled_calendars(app);
+    let invitations = InvitationFilter::load(app);
+    let providers = meeting_url::registry(app);
+    calendar_state
+        .events
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|e| is_calendar_enabled(e, enabled.as_ref()) && invitations.shows(e))
+        .map(|e| CalendarEvent {
+            meeting: providers.find(e),
+            ..e.clone()
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub async fn get_todays_events(
+    app: tauri::AppHandle,
     calendar_state: tauri::State<'_, CalendarState>,
 ) -> Result<Vec<CalendarEvent>, String> {
-    Ok(calendar_state.events.lock().unwrap().clone())
+    Ok(enabled_events(&app, &calendar_state))
 }
 
 #[tauri::command]
 pub async fn force_sync(
+    app: tauri::AppHandle,
+    calendar_state: tauri::State<'_, CalendarState>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let diff = sync_events(&calendar_state)?;
+    emit_events_updated(&app, &diff);
+    Ok(enabled_events(&app, &calendar_state))
+}
+
+/// Events overlapping `[start, end]` from `calendar_ids` (default: the calendar
+/// filter), for views beyond the synced window such as a week view or export.
+#[tauri::command]
+pub async fn get_events_in_range(
+    app: tauri::AppHandle,
     calendar_state: tauri::State<'_, CalendarState>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    calendar_ids: Option<Vec<String>>,
 ) -> Result<Vec<CalendarEvent>, String> {
-    sync_events(&calendar_state)?;
-    Ok(calendar_state.events.lock().unwrap().clone())
+    if end <= start {
+        return Err("Range end must be after its start".to_string());
+    }
+    let calendar_ids = match calendar_ids {
+        Some(ids) => Some(ids.into_iter().collect()),
+        None => enabled_calendars(&app),
+    };
+    let key = RangeKey::new(start, end, calendar_ids.as_ref());
+    let now = std::time::Instant::now();
+    let cached = calendar_state.range_cache.lock().unwrap().get(&key, now);
+    let events = match cached {
+        Some(events) => events,
+        None => {
+            let events = fetch_range(&calendar_state, start, end, calendar_ids)?;
+            calendar_state
+                .range_cache
+                .lock()
+                .unwrap()
+                .insert(key, events.clone(), now);
+            events
+        }
+    };
+    let invitations = InvitationFilter::load(&app);
+    let providers = meeting_url::registry(&app);
+    Ok(events
+        .into_iter()
+        .filter(|e| invitations.shows(e))
+        .map(|e| CalendarEvent {
+            meeting: providers.find(&e),
+            ..e
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_enabled_calendars(app: tauri::AppHandle) -> Result<Option<Vec<String>>, String> {
+    Ok(enabled_calendars(&app).map(|ids| ids.into_iter().collect()))
+}
+
+#[tauri::command]
+pub async fn set_enabled_calendars(app: tauri::AppHandle, ids: Vec<String>) -> Result<(), String> {
+    let mut patch = serde_json::Map::new();
+    patch.insert("enabledCalendars".to_string(), serde_json::json!(ids));
+    crate::settings::update(&app, patch)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeSource {
+        events: Vec<CalendarEvent>,
+        permission: &'static str,
+    }
+
+    impl CalendarSource for FakeSource {
+        fn fetch_events(
+            &mut self,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<Vec<CalendarEvent>, String> {
+            Ok(self.events.clone())
+        }
+
+        fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+            Ok(Vec::new())
+        }
+
+        fn check_permission(&mut self) -> Result<String, String> {
+            Ok(self.permission.to_string())
+        }
+
+        fn request_permission(&mut self) -> Result<bool, String> {
+            Ok(self.permission == "granted")
+        }
+    }
+
+    struct FailingSource;
+
+    impl CalendarSource for FailingSource {
+        fn fetch_events(
+            &mut self,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<Vec<CalendarEvent>, String> {
+            Err("offline".to_string())
+        }
+
+        fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+            Err("offline".to_string())
+        }
+
+        fn check_permission(&mut self) -> Result<String, String> {
+            Err("offline".to_string())
+        }
+
+        fn request_permission(&mut self) -> Result<bool, String> {
+            Err("offline".to_string())
+        }
+    }
+
+    /// A `FakeSource` that fails while `failing` is set.
+    struct FlakySource {
+        source: FakeSource,
+        failing: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl CalendarSource for FlakySource {
+        fn fetch_events(
+            &mut self,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+        ) -> Result<Vec<CalendarEvent>, String> {
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err("offline".to_string());
+            }
+            self.source.fetch_events(start, end)
+        }
+
+        fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+            self.source.fetch_calendars()
+        }
+
+        fn check_permission(&mut self) -> Result<String, String> {
+            self.source.check_permission()
+        }
+
+        fn request_permission(&mut self) -> Result<bool, String> {
+            self.source.request_permission()
+        }
+    }
+
+    struct PanickingSource;
+
+    impl CalendarSource for PanickingSource {
+        fn fetch_events(
+            &mut self,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<Vec<CalendarEvent>, String> {
+            panic!("boom")
+        }
+
+        fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+            panic!("boom")
+        }
+
+        fn check_permission(&mut self) -> Result<String, String> {
+            panic!("boom")
+        }
+
+        fn request_permission(&mut self) -> Result<bool, String> {
+            panic!("boom")
+        }
+    }
+
+    fn make_event(id: &str, start: &str, status: Option<&str>) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            summary: id.to_string(),
+            start: EventDateTime { date_time: Some(start.to_string()), date: None },
+            end: EventDateTime { date_time: Some(start.to_string()), date: None },
+            status: status.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sync_events_sorts_and_drops_cancelled() {
+        let state = CalendarState::with_source(|| FakeSource {
+            events: vec![
+                make_event("late", "2024-05-01T10:00:00+00:00", Some("confirmed")),
+                make_event("gone", "2024-05-01T08:00:00+00:00", Some("cancelled")),
+                make_event("early", "2024-05-01T09:00:00+00:00", None),
+            ],
+            permission: "granted",
+        });
+        sync_events(&state).unwrap();
+        let ids: Vec<String> = state.events.lock().unwrap().iter().map(|e| e.id.clone()).collect();
+        assert_eq!(ids, vec!["early", "late"]);
+    }
+
+    #[test]
+    fn sync_events_notifies_only_when_events_change() {
+        let state = CalendarState::with_source(|| FakeSource {
+            events: vec![make_event("a", "2024-05-01T10:00:00+00:00", None)],
+            permission: "granted",
+        });
+        let notified = |state: &CalendarState| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    tokio::time::timeout(
+                        std::time::Duration::from_millis(10),
+                        state.updated.notified(),
+                    )
+                    .await
+                    .is_ok()
+                })
+        };
+        assert_eq!(sync_events(&state).unwrap().added, vec!["a"]);
+        assert!(notified(&state));
+        assert!(sync_events(&state).unwrap().is_empty());
+        assert!(!notified(&state));
+    }
+
+    #[test]
+    fn fetch_range_chunks_long_ranges_and_filters_calendars() {
+        let mut work = make_event("work", "2024-05-01T10:00:00+00:00", None);
+        work.calendar_id = Some("cal_work".to_string());
+        let mut home = make_event("home", "2024-05-01T11:00:00+00:00", None);
+        home.calendar_id = Some("cal_home".to_string());
+        let state = CalendarState::with_source(move || FakeSource {
+            events: vec![work, home],
+            permission: "granted",
+        });
+        let start = DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap().to_utc();
+        let end = start + chrono::Duration::days(10 * 365);
+
+        // Every chunk returns both events; they must only appear once
+        let all = fetch_range(&state, start, end, None).unwrap();
+        let ids: Vec<&str> = all.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["work", "home"]);
+
+        let only_work = HashSet::from(["cal_work".to_string()]);
+        let filtered = fetch_range(&state, start, end, Some(only_work)).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, "work");
+    }
+
+    #[test]
+    fn invitation_filter_skips_by_own_response() {
+        let with_status = |status| {
+            let mut e = make_event("a", "2024-05-01T10:00:00+00:00", None);
+            e.self_status = status;
+            e
+        };
+        let declined = with_status(Some(ParticipationStatus::Declined));
+        let tentative = with_status(Some(ParticipationStatus::Tentative));
+        let pending = with_status(Some(ParticipationStatus::NeedsAction));
+        let own = with_status(None);
+
+        let filter = InvitationFilter::default();
+        assert!(filter.skips(&declined));
+        assert!(!filter.skips(&tentative));
+        assert!(!filter.skips(&pending));
+        assert!(!filter.skips(&own));
+        assert!(filter.shows(&declined));
+
+        let strict = InvitationFilter {
+            tentative: true,
+            needs_action: true,
+            hide: true,
+            ..filter
+        };
+        assert!(strict.skips(&tentative) && strict.skips(&pending));
+        assert!(!strict.shows(&declined));
+        assert!(strict.shows(&own));
+    }
+
+    #[test]
+    fn identify_self_uses_attendee_then_organizer() {
+        let person = |email: &str, status| Participant {
+            name: None,
+            email: Some(email.to_string()),
+            role: ParticipantRole::Required,
+            status,
+            is_self: false,
+        };
+        let mut event = make_event("a", "2024-05-01T10:00:00+00:00", None);
+        event.organizer = Some(person("boss@example.com", ParticipationStatus::Unknown));
+        event.attendees = vec![
+            person("me@example.com", ParticipationStatus::Declined),
+            person("other@example.com", ParticipationStatus::Accepted),
+        ];
+        event.identify_self("Me@Example.com");
+        assert_eq!(event.self_status, Some(ParticipationStatus::Declined));
+        assert!(event.attendees[0].is_self);
+        assert!(!event.attendees[1].is_self);
+
+        event.identify_self("boss@example.com");
+        assert_eq!(event.self_status, Some(ParticipationStatus::Accepted));
+        assert!(event.organizer.as_ref().unwrap().is_self);
+
+        event.identify_self("nobody@example.com");
+        assert_eq!(event.self_status, None);
+    }
+
+    #[test]
+    fn events_diff_reports_added_removed_and_changed_ids() {
+        let old = vec![
+            make_event("kept", "2024-05-01T09:00:00+00:00", None),
+            make_event("moved", "2024-05-01T10:00:00+00:00", None),
+            make_event("gone", "2024-05-01T11:00:00+00:00", None),
+        ];
+        let new = vec![
+            make_event("kept", "2024-05-01T09:00:00+00:00", None),
+            make_event("moved", "2024-05-01T10:30:00+00:00", None),
+            make_event("new", "2024-05-01T12:00:00+00:00", None),
+        ];
+        let diff = EventsDiff::between(&old, &new);
+        assert_eq!(diff.added, vec!["new"]);
+        assert_eq!(diff.removed, vec!["gone"]);
+        assert_eq!(diff.changed, vec!["moved"]);
+        assert!(EventsDiff::between(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn has_permission_reflects_source() {
+        let granted = CalendarState::with_source(|| FakeSource {
+            events: Vec::new(),
+            permission: "granted",
+        });
+        let denied = CalendarState::with_source(|| FakeSource {
+            events: Vec::new(),
+            permission: "denied",
+        });
+        assert!(has_permission(&granted));
+        assert!(!has_permission(&denied));
+    }
+
+    #[test]
+    fn multi_source_merges_and_skips_failing_sources() {
+        let state = CalendarState::with_source(|| {
+            MultiSource::new(vec![
+                Box::new(FakeSource {
+                    events: vec![make_event("a", "2024-05-01T10:00:00+00:00", None)],
+                    permission: "denied",
+                }),
+                Box::new(FailingSource),
+                Box::new(FakeSource {
+                    events: vec![make_event("b", "2024-05-01T09:00:00+00:00", None)],
+                    permission: "granted",
+                }),
+            ])
+        });
+        sync_events(&state).unwrap();
+        let ids: Vec<String> = state.events.lock().unwrap().iter().map(|e| e.id.clone()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert!(has_permission(&state));
+    }
+
+    #[test]
+    fn failing_source_keeps_its_last_events() {
+        let soon = (Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let failing = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = failing.clone();
+        let state = CalendarState::with_source(move || {
+            MultiSource::new(vec![
+                Box::new(FlakySource {
+                    source: FakeSource {
+                        events: vec![make_event("flaky", &soon, None)],
+                        permission: "granted",
+                    },
+                    failing: flag,
+                }),
+                Box::new(FakeSource {
+                    events: vec![make_event("steady", &soon, None)],
+                    permission: "granted",
+                }),
+            ])
+        });
+        let ids = |state: &CalendarState| -> Vec<String> {
+            let mut ids: Vec<String> =
+                state.events.lock().unwrap().iter().map(|e| e.id.clone()).collect();
+            ids.sort();
+            ids
+        };
+        sync_events(&state).unwrap();
+        assert_eq!(ids(&state), vec!["flaky", "steady"]);
+
+        failing.store(true, std::sync::atomic::Ordering::SeqCst);
+        let diff = sync_events(&state).unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(ids(&state), vec!["flaky", "steady"]);
+    }
+
+    #[test]
+    fn calendar_filter_keeps_enabled_and_uncategorised_events() {
+        let mut work = make_event("work", "2024-05-01T09:00:00+00:00", None);
+        work.calendar_id = Some("work".to_string());
+        let mut team = make_event("team", "2024-05-01T09:00:00+00:00", None);
+        team.calendar_id = Some("team".to_string());
+        let loose = make_event("loose", "2024-05-01T09:00:00+00:00", None);
+
+        let enabled: HashSet<String> = ["work".to_string()].into_iter().collect();
+        assert!(is_calendar_enabled(&work, Some(&enabled)));
+        assert!(!is_calendar_enabled(&team, Some(&enabled)));
+        assert!(is_calendar_enabled(&loose, Some(&enabled)));
+        assert!(is_calendar_enabled(&team, None));
+    }
+
+    #[test]
+    fn panicking_source_reports_error_and_keeps_thread_alive() {
+        let state = CalendarState::with_source(|| PanickingSource);
+        assert!(sync_events(&state).is_err());
+        assert!(sync_events(&state).is_err());
+        assert!(!has_permission(&state));
+
+        // Next to other sources, only the panicking one is lost
+        let state = CalendarState::with_source(|| {
+            MultiSource::new(vec![
+                Box::new(PanickingSource),
+                Box::new(FakeSource {
+                    events: vec![make_event("a", "2024-05-01T10:00:00+00:00", None)],
+                    permission: "granted",
+                }),
+            ])
+        });
+        sync_events(&state).unwrap();
+        let ids: Vec<String> = state.events.lock().unwrap().iter().map(|e| e.id.clone()).collect();
+        assert_eq!(ids, vec!["a"]);
+        assert!(has_permission(&state));
+    }
 }
@@ -0,0 +1,939 @@
+use super::ics::events_from_ics;
+use super::{CalendarEvent, CalendarInfo, CalendarSource};
+use chrono::{DateTime, Utc};
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use reqwest::blocking::Client;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+// After a failed request an account is left alone this long, doubling with
+// each further failure up to the max; its cached events are served meanwhile
+const RETRY_BASE_SECS: u64 = 60;
+const RETRY_MAX_SECS: u64 = 30 * 60;
+const KEYCHAIN_SERVICE: &str = "com.galopen.desktop.caldav";
+// Servers commonly cap calendar-multiget sizes; stay well below that.
+const MULTIGET_CHUNK: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavAccount {
+    /// Server root, principal or calendar-home URL.
+    pub url: String,
+    pub username: String,
+    /// Only accepted as input, from the Settings UI or from settings written
+    /// by older versions. `move_passwords_to_keychain` stores it in the
+    /// Keychain under `keychain_account()`; it is never written to
+    /// settings.json or sent to the webview.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// Shown as the calendars' account name; defaults to the username.
+    pub name: Option<String>,
+}
+
+impl CalDavAccount {
+    /// The Keychain item holding this account's password.
+    pub fn keychain_account(&self) -> String {
+        format!("{}@{}", self.username, self.url)
+    }
+}
+
+/// Move passwords given in `accounts` to the Keychain. A password that can't
+/// be stored stays in memory, so it works until the app quits.
+pub fn move_passwords_to_keychain(accounts: &mut [CalDavAccount]) {
+    for account in accounts {
+        let Some(password) = &account.password else {
+            continue;
+        };
+        match keychain::set(&account.keychain_account(), password) {
+            Ok(()) => account.password = None,
+            Err(e) => log::warn!("Failed to store CalDAV password for {}: {}", account.url, e),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod keychain {
+    use super::KEYCHAIN_SERVICE;
+    use security_framework::passwords::{get_generic_password, set_generic_password};
+
+    pub fn set(account: &str, password: &str) -> Result<(), String> {
+        set_generic_password(KEYCHAIN_SERVICE, account, password.as_bytes())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn get(account: &str) -> Result<String, String> {
+        let bytes = get_generic_password(KEYCHAIN_SERVICE, account).map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod keychain {
+    pub fn set(_account: &str, _password: &str) -> Result<(), String> {
+        Err("no Keychain on this platform".to_string())
+    }
+
+    pub fn get(_account: &str) -> Result<String, String> {
+        Err("no Keychain on this platform".to_string())
+    }
+}
+
+/// Calendar source that talks to CalDAV servers (Fastmail, Nextcloud, Radicale, ...).
+///
+/// Calendars are discovered with PROPFIND (current-user-principal →
+/// calendar-home-set → collections). Events are fetched with a time-ranged
+/// calendar-query that only returns ETags; bodies are pulled with
+/// calendar-multiget for new or changed objects. Once a range has been
+/// fetched, later syncs use the collection's sync-token (RFC 6578) and fall
+/// back to the ETag diff if the server rejects it.
+pub struct CalDavSource {
+    client: Client,
+    accounts: Vec<AccountState>,
+}
+
+struct AccountState {
+    config: CalDavAccount,
+    password: String,
+    calendars: Option<Vec<RemoteCalendar>>,
+    backoff: Backoff,
+}
+
+impl AccountState {
+    fn calendar_infos(&self) -> Vec<CalendarInfo> {
+        self.calendars.iter().flatten().map(|c| c.info.clone()).collect()
+    }
+}
+
+/// Keeps an unreachable server from stalling every fetch on the calendar
+/// thread with request timeouts.
+#[derive(Default)]
+struct Backoff {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Backoff {
+    fn is_waiting(&self, now: Instant) -> bool {
+        self.retry_at.is_some_and(|at| now < at)
+    }
+
+    fn failed(&mut self, now: Instant) {
+        self.failures += 1;
+        let secs = RETRY_BASE_SECS << (self.failures - 1).min(5);
+        self.retry_at = Some(now + Duration::from_secs(secs.min(RETRY_MAX_SECS)));
+    }
+
+    fn succeeded(&mut self) {
+        *self = Self::default();
+    }
+}
+
+struct RemoteCalendar {
+    url: Url,
+    info: CalendarInfo,
+    sync_token: Option<String>,
+    synced_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// href → object
+    objects: HashMap<String, CachedObject>,
+}
+
+struct CachedObject {
+    etag: String,
+    data: String,
+}
+
+impl CalDavSource {
+    pub fn new(accounts: Vec<CalDavAccount>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to build CalDAV client, using defaults: {}", e);
+                Client::new()
+            });
+        Self {
+            client,
+            accounts: accounts
+                .into_iter()
+                .map(|config| {
+                    let password = match &config.password {
+                        Some(password) => password.clone(),
+                        None => keychain::get(&config.keychain_account()).unwrap_or_else(|e| {
+                            log::warn!("No CalDAV password for {}: {}", config.url, e);
+                            String::new()
+                        }),
+                    };
+                    AccountState {
+                        config,
+                        password,
+                        calendars: None,
+                        backoff: Backoff::default(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CalendarSource for CalDavSource {
+    fn fetch_events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let mut events = Vec::new();
+        let now = Instant::now();
+        for account in &mut self.accounts {
+            let dav = Dav {
+                client: &self.client,
+                account: &account.config,
+                password: &account.password,
+            };
+            let mut online = !account.backoff.is_waiting(now);
+            if !online {
+                log::debug!("Skipping CalDAV sync for {} until retry time", account.config.url);
+            }
+            if account.calendars.is_none() {
+                if !online {
+                    continue;
+                }
+                match dav.discover() {
+                    Ok(cals) => account.calendars = Some(cals),
+                    Err(e) => {
+                        log::warn!("CalDAV discovery failed for {}: {}", account.config.url, e);
+                        account.backoff.failed(now);
+                        continue;
+                    }
+                }
+            }
+            let mut failed = false;
+            for cal in account.calendars.iter_mut().flatten() {
+                // On failure keep serving the last good copy, and don't try
+                // the account's other calendars on the same server
+                if online {
+                    if let Err(e) = dav.sync_calendar(cal, start, end) {
+                        log::warn!("CalDAV sync failed for {}: {}", cal.url, e);
+                        online = false;
+                        failed = true;
+                    }
+                }
+                let text: String = cal
+                    .objects
+                    .values()
+                    .map(|o| o.data.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let mut cal_events = events_from_ics(&text, &cal.info, start, end);
+                // Usernames are often the account's email address, which tells
+                // us which attendee is the user
+                if account.config.username.contains('@') {
+                    for event in &mut cal_events {
+                        event.identify_self(&account.config.username);
+                    }
+                }
+                events.extend(cal_events);
+            }
+            if failed {
+                account.backoff.failed(now);
+            } else if online {
+                account.backoff.succeeded();
+            }
+        }
+        Ok(events)
+    }
+
+    fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+        let mut result = Vec::new();
+        let now = Instant::now();
+        for account in &mut self.accounts {
+            let dav = Dav {
+                client: &self.client,
+                account: &account.config,
+                password: &account.password,
+            };
+            // An account that can't be reached keeps the calendars found last time
+            if account.backoff.is_waiting(now) {
+                result.extend(account.calendar_infos());
+                continue;
+            }
+            let mut fresh = match dav.discover() {
+                Ok(fresh) => fresh,
+                Err(e) => {
+                    log::warn!("CalDAV discovery failed for {}: {}", account.config.url, e);
+                    account.backoff.failed(now);
+                    result.extend(account.calendar_infos());
+                    continue;
+                }
+            };
+            // Keep cached objects/sync state for calendars that still exist
+            if let Some(old) = account.calendars.take() {
+                let mut old: HashMap<Url, RemoteCalendar> =
+                    old.into_iter().map(|c| (c.url.clone(), c)).collect();
+                for cal in &mut fresh {
+                    if let Some(prev) = old.remove(&cal.url) {
+                        cal.sync_token = prev.sync_token;
+                        cal.synced_range = prev.synced_range;
+                        cal.objects = prev.objects;
+                    }
+                }
+            }
+            result.extend(fresh.iter().map(|c| c.info.clone()));
+            account.calendars = Some(fresh);
+            account.backoff.succeeded();
+        }
+        Ok(result)
+    }
+
+    // Credentials problems surface as fetch errors; there is no OS prompt.
+    fn check_permission(&mut self) -> Result<String, String> {
+        Ok("granted".to_string())
+    }
+
+    fn request_permission(&mut self) -> Result<bool, String> {
+        Ok(true)
+    }
+}
+
+struct Dav<'a> {
+    client: &'a Client,
+    account: &'a CalDavAccount,
+    password: &'a str,
+}
+
+impl Dav<'_> {
+    fn request(
+        &self,
+        method: &str,
+        url: &Url,
+        depth: Option<&str>,
+        body: String,
+    ) -> Result<XmlNode, String> {
+        let m = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+        let mut req = self
+            .client
+            .request(m, url.clone())
+            .basic_auth(&self.account.username, Some(self.password))
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body);
+        if let Some(depth) = depth {
+            req = req.header("Depth", depth);
+        }
+        let resp = req
+            .send()
+            .map_err(|e| format!("CalDAV {} {} failed: {}", method, url, e))?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(format!("CalDAV {} {} returned {}", method, url, status));
+        }
+        let text = resp.text().map_err(|e| e.to_string())?;
+        parse_xml(&text)
+    }
+
+    fn propfind(&self, url: &Url, depth: &str, props: &str) -> Result<Vec<DavResponse>, String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop>{}</d:prop></d:propfind>"#,
+            props
+        );
+        Ok(parse_multistatus(&self.request("PROPFIND", url, Some(depth), body)?))
+    }
+
+    /// Follow a single `<href>` property, falling back to `url` itself.
+    fn follow_href(&self, url: &Url, prop: &str, props: &str) -> Url {
+        self.propfind(url, "0", props)
+            .ok()
+            .and_then(|responses| {
+                responses
+                    .iter()
+                    .find_map(|r| r.prop(prop).and_then(|p| p.find("href")).map(|h| h.text.clone()))
+            })
+            .and_then(|href| url.join(href.trim()).ok())
+            .unwrap_or_else(|| url.clone())
+    }
+
+    fn discover(&self) -> Result<Vec<RemoteCalendar>, String> {
+        let base = Url::parse(&self.account.url)
+            .map_err(|e| format!("Invalid CalDAV URL {}: {}", self.account.url, e))?;
+        let principal = self.follow_href(&base, "current-user-principal", "<d:current-user-principal/>");
+        let home = self.follow_href(&principal, "calendar-home-set", "<c:calendar-home-set/>");
+
+        let responses = self.propfind(
+            &home,
+            "1",
+            "<d:resourcetype/><d:displayname/><d:sync-token/><c:supported-calendar-component-set/>",
+        )?;
+        let source_name = self
+            .account
+            .name
+            .clone()
+            .unwrap_or_else(|| self.account.username.clone());
+
+        let mut calendars = Vec::new();
+        for r in responses {
+            let is_calendar = r
+                .prop("resourcetype")
+                .is_some_and(|t| t.child("calendar").is_some());
+            // No component set advertised means "all components"
+            let has_events = r.prop("supported-calendar-component-set").is_none_or(|set| {
+                set.children
+                    .iter()
+                    .any(|c| c.attr("name").is_some_and(|n| n.eq_ignore_ascii_case("VEVENT")))
+            });
+            if !is_calendar || !has_events {
+                continue;
+            }
+            let Ok(url) = home.join(&r.href) else {
+                continue;
+            };
+            let title = r
+                .prop("displayname")
+                .map(|d| d.text.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .or_else(|| {
+                    url.path_segments()
+                        .and_then(|mut s| s.rfind(|seg| !seg.is_empty()).map(String::from))
+                })
+                .unwrap_or_default();
+            calendars.push(RemoteCalendar {
+                info: CalendarInfo {
+                    id: format!("caldav:{}", url),
+                    title,
+                    source_name: source_name.clone(),
+                },
+                url,
+                sync_token: None,
+                synced_range: None,
+                objects: HashMap::new(),
+            });
+        }
+        Ok(calendars)
+    }
+
+    fn sync_calendar(
+        &self,
+        cal: &mut RemoteCalendar,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let covered = cal.synced_range.is_some_and(|(s, e)| s <= start && e >= end);
+        if covered {
+            if let Some(token) = cal.sync_token.clone() {
+                match self.sync_collection(cal, &token) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => log::info!("sync-collection failed for {}, re-querying: {}", cal.url, e),
+                }
+            }
+        }
+        self.query_range(cal, start, end)
+    }
+
+    fn current_sync_token(&self, url: &Url) -> Option<String> {
+        self.propfind(url, "0", "<d:sync-token/>")
+            .ok()?
+            .iter()
+            .find_map(|r| r.prop("sync-token").map(|t| t.text.trim().to_string()))
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Full ETag listing for the range; only new or changed objects are downloaded.
+    fn query_range(
+        &self,
+        cal: &mut RemoteCalendar,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(), String> {
+        // Read the token first so changes made during the query show up next time
+        let token = self.current_sync_token(&cal.url);
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:getetag/></d:prop><c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT"><c:time-range start="{}" end="{}"/></c:comp-filter></c:comp-filter></c:filter></c:calendar-query>"#,
+            start.format("%Y%m%dT%H%M%SZ"),
+            end.format("%Y%m%dT%H%M%SZ"),
+        );
+        let responses = parse_multistatus(&self.request("REPORT", &cal.url, Some("1"), body)?);
+
+        let etags: HashMap<String, String> = responses
+            .iter()
+            .filter(|r| !r.removed)
+            .filter_map(|r| Some((r.href.clone(), r.prop("getetag")?.text.clone())))
+            .collect();
+        let stale: Vec<String> = etags
+            .iter()
+            .filter(|(href, etag)| cal.objects.get(*href).map(|o| &o.etag) != Some(etag))
+            .map(|(href, _)| href.clone())
+            .collect();
+
+        self.multiget(cal, &stale)?;
+        // Objects in this range that the server no longer lists were deleted;
+        // the others belong to ranges synced before and stay cached
+        cal.objects.retain(|href, o| {
+            etags.contains_key(href) || events_from_ics(&o.data, &cal.info, start, end).is_empty()
+        });
+        if cal.synced_range.is_none_or(|(s, e)| start <= s && end >= e) {
+            // Everything cached is now as fresh as the new token
+            cal.sync_token = token;
+            cal.synced_range = Some((start, end));
+        } else if let Some((s, e)) = cal.synced_range.filter(|(s, e)| start <= *e && end >= *s) {
+            // Objects outside this range are only as fresh as the old token,
+            // so keep it; the next sync-collection catches them up
+            cal.synced_range = Some((s.min(start), e.max(end)));
+        }
+        // A range elsewhere (e.g. from get_events_in_range) is merged in
+        // without replacing the scheduler's window
+        Ok(())
+    }
+
+    fn sync_collection(&self, cal: &mut RemoteCalendar, token: &str) -> Result<(), String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><d:sync-collection xmlns:d="DAV:"><d:sync-token>{}</d:sync-token><d:sync-level>1</d:sync-level><d:prop><d:getetag/></d:prop></d:sync-collection>"#,
+            escape(token)
+        );
+        let root = self.request("REPORT", &cal.url, None, body)?;
+        let new_token = root
+            .children
+            .iter()
+            .find(|c| c.name == "sync-token")
+            .map(|t| t.text.trim().to_string());
+
+        let mut changed = Vec::new();
+        for r in parse_multistatus(&root) {
+            if r.removed {
+                cal.objects.remove(&r.href);
+                continue;
+            }
+            let Some(etag) = r.prop("getetag").map(|e| e.text.clone()) else {
+                continue;
+            };
+            if cal.objects.get(&r.href).map(|o| &o.etag) != Some(&etag) {
+                changed.push(r.href);
+            }
+        }
+        self.multiget(cal, &changed)?;
+        if new_token.is_some() {
+            cal.sync_token = new_token;
+        }
+        Ok(())
+    }
+
+    fn multiget(&self, cal: &mut RemoteCalendar, hrefs: &[String]) -> Result<(), String> {
+        for chunk in hrefs.chunks(MULTIGET_CHUNK) {
+            let href_xml: String = chunk
+                .iter()
+                .map(|h| format!("<d:href>{}</d:href>", escape(h.as_str())))
+                .collect();
+            let body = format!(
+                r#"<?xml version="1.0" encoding="utf-8"?><c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:getetag/><c:calendar-data/></d:prop>{}</c:calendar-multiget>"#,
+                href_xml
+            );
+            let root = self.request("REPORT", &cal.url, Some("1"), body)?;
+            for r in parse_multistatus(&root) {
+                if r.removed {
+                    cal.objects.remove(&r.href);
+                    continue;
+                }
+                let (Some(etag), Some(data)) = (r.prop("getetag"), r.prop("calendar-data")) else {
+                    continue;
+                };
+                cal.objects.insert(
+                    r.href.clone(),
+                    CachedObject {
+                        etag: etag.text.clone(),
+                        data: data.text.clone(),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+// --- Minimal XML tree ---
+//
+// DAV responses mix prefixes freely (d:, D:, default namespaces), so
+// elements are matched on their local name only.
+
+#[derive(Debug, Default, Clone)]
+struct XmlNode {
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlNode>,
+}
+
+impl XmlNode {
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn find(&self, name: &str) -> Option<&XmlNode> {
+        self.children
+            .iter()
+            .find_map(|c| if c.name == name { Some(c) } else { c.find(name) })
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn node_from_start(e: &BytesStart) -> XmlNode {
+    XmlNode {
+        name: String::from_utf8_lossy(e.local_name().as_ref()).to_string(),
+        attrs: e
+            .attributes()
+            .flatten()
+            .map(|a| {
+                (
+                    String::from_utf8_lossy(a.key.local_name().as_ref()).to_string(),
+                    String::from_utf8_lossy(&a.value).to_string(),
+                )
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn parse_xml(text: &str) -> Result<XmlNode, String> {
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid XML: {}", e))?;
+        match event {
+            Event::Start(e) => stack.push(node_from_start(&e)),
+            Event::Empty(e) => {
+                let node = node_from_start(&e);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let done = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(done);
+                }
+            }
+            Event::Text(t) => {
+                if let (Some(node), Ok(s)) = (stack.last_mut(), t.decode()) {
+                    node.text.push_str(&s);
+                }
+            }
+            Event::CData(t) => {
+                if let (Some(node), Ok(s)) = (stack.last_mut(), t.decode()) {
+                    node.text.push_str(&s);
+                }
+            }
+            Event::GeneralRef(r) => {
+                let Some(node) = stack.last_mut() else {
+                    continue;
+                };
+                if let Ok(Some(c)) = r.resolve_char_ref() {
+                    node.text.push(c);
+                } else if let Some(s) = r.decode().ok().and_then(|n| resolve_predefined_entity(&n)) {
+                    node.text.push_str(s);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    // Document root is the single child of the synthetic node
+    stack
+        .into_iter()
+        .next()
+        .and_then(|doc| doc.children.into_iter().next())
+        .ok_or_else(|| "Empty XML document".to_string())
+}
+
+struct DavResponse {
+    href: String,
+    /// 404 for the whole response (sync-collection deletions, missing multiget hrefs)
+    removed: bool,
+    /// Properties from 2xx propstats
+    props: Vec<XmlNode>,
+}
+
+impl DavResponse {
+    fn prop(&self, name: &str) -> Option<&XmlNode> {
+        self.props.iter().find(|p| p.name == name)
+    }
+}
+
+fn is_ok_status(status: &str) -> bool {
+    status.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2'))
+}
+
+fn parse_multistatus(root: &XmlNode) -> Vec<DavResponse> {
+    root.children
+        .iter()
+        .filter(|c| c.name == "response")
+        .filter_map(|r| {
+            let href = r.child("href")?.text.trim().to_string();
+            let removed = r
+                .child("status")
+                .is_some_and(|s| s.text.contains(" 404"));
+            let props = r
+                .children
+                .iter()
+                .filter(|c| c.name == "propstat")
+                .filter(|ps| ps.child("status").is_none_or(|s| is_ok_status(&s.text)))
+                .filter_map(|ps| ps.child("prop"))
+                .flat_map(|p| p.children.iter().cloned())
+                .collect();
+            Some(DavResponse {
+                href,
+                removed,
+                props,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    type Handler = dyn Fn(&str, &str, &str) -> (u16, String) + Send + Sync;
+
+    /// Stand-in CalDAV server: one request per connection, answered by `handler`.
+    fn serve(handler: Arc<Handler>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_clone = log.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((k, v)) = line.split_once(':') {
+                        if k.eq_ignore_ascii_case("content-length") {
+                            content_length = v.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                log_clone.lock().unwrap().push(format!("{} {} {}", method, path, body));
+                let (status, resp) = handler(method, path, &body);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Type: application/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    resp.len(),
+                    resp
+                );
+            }
+        });
+        (format!("http://{}/", addr), log)
+    }
+
+    fn multistatus(inner: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">{}</D:multistatus>"#,
+            inner
+        )
+    }
+
+    fn ics(summary: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\nSUMMARY:{}\r\nDTSTART:20240501T090000Z\r\nDTEND:20240501T093000Z\r\nLOCATION:https://meet.google.com/aaa-bbbb-ccc\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+            summary
+        )
+    }
+
+    fn fake_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let version = Arc::new(AtomicUsize::new(1));
+        serve(Arc::new(move |method, path, body| {
+            let v = version.load(Ordering::SeqCst);
+            let resp = match (method, path) {
+                ("PROPFIND", "/") => multistatus(
+                    "<D:response><D:href>/</D:href><D:propstat><D:prop><D:current-user-principal><D:href>/principals/alice/</D:href></D:current-user-principal></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                ),
+                ("PROPFIND", "/principals/alice/") => multistatus(
+                    "<D:response><D:href>/principals/alice/</D:href><D:propstat><D:prop><C:calendar-home-set><D:href>/calendars/alice/</D:href></C:calendar-home-set></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                ),
+                ("PROPFIND", "/calendars/alice/") => multistatus(
+                    r#"<D:response><D:href>/calendars/alice/</D:href><D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>
+                    <D:response><D:href>/calendars/alice/work/</D:href><D:propstat><D:prop><D:resourcetype><D:collection/><C:calendar/></D:resourcetype><D:displayname>Work &amp; Stuff</D:displayname><C:supported-calendar-component-set><C:comp name="VEVENT"/></C:supported-calendar-component-set></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>
+                    <D:response><D:href>/calendars/alice/tasks/</D:href><D:propstat><D:prop><D:resourcetype><D:collection/><C:calendar/></D:resourcetype><C:supported-calendar-component-set><C:comp name="VTODO"/></C:supported-calendar-component-set></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>"#,
+                ),
+                ("PROPFIND", "/calendars/alice/work/") => multistatus(&format!(
+                    "<D:response><D:href>/calendars/alice/work/</D:href><D:propstat><D:prop><D:sync-token>tok{}</D:sync-token></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                    v
+                )),
+                ("REPORT", "/calendars/alice/work/") if body.contains("calendar-query") => {
+                    multistatus(&format!(
+                        "<D:response><D:href>/calendars/alice/work/a.ics</D:href><D:propstat><D:prop><D:getetag>\"{}\"</D:getetag></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                        v
+                    ))
+                }
+                ("REPORT", "/calendars/alice/work/") if body.contains("sync-collection") => {
+                    // Someone renamed the event since tok1
+                    let v = version.fetch_add(1, Ordering::SeqCst) + 1;
+                    multistatus(&format!(
+                        "<D:response><D:href>/calendars/alice/work/a.ics</D:href><D:propstat><D:prop><D:getetag>\"{}\"</D:getetag></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response><D:response><D:href>/calendars/alice/work/gone.ics</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response><D:sync-token>tok{}</D:sync-token>",
+                        v, v
+                    ))
+                }
+                ("REPORT", "/calendars/alice/work/") if body.contains("calendar-multiget") => {
+                    multistatus(&format!(
+                        "<D:response><D:href>/calendars/alice/work/a.ics</D:href><D:propstat><D:prop><D:getetag>\"{}\"</D:getetag><C:calendar-data><![CDATA[{}]]></C:calendar-data></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                        v,
+                        ics(&format!("Planning v{}", v))
+                    ))
+                }
+                _ => return (404, String::new()),
+            };
+            (207, resp)
+        }))
+    }
+
+    fn account(url: &str) -> CalDavAccount {
+        CalDavAccount {
+            url: url.to_string(),
+            username: "alice".to_string(),
+            password: Some("secret".to_string()),
+            name: None,
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn discovers_event_calendars_via_principal_and_home() {
+        let (url, _) = fake_server();
+        let mut source = CalDavSource::new(vec![account(&url)]);
+        let cals = source.fetch_calendars().unwrap();
+        assert_eq!(cals.len(), 1);
+        assert_eq!(cals[0].title, "Work & Stuff");
+        assert_eq!(cals[0].source_name, "alice");
+        assert_eq!(cals[0].id, format!("caldav:{}calendars/alice/work/", url));
+    }
+
+    #[test]
+    fn fetches_range_then_syncs_incrementally() {
+        let (url, log) = fake_server();
+        let mut source = CalDavSource::new(vec![account(&url)]);
+        let (start, end) = (utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+
+        let events = source.fetch_events(start, end).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "Planning v1");
+        assert_eq!(events[0].start.date_time.as_deref(), Some("2024-05-01T09:00:00+00:00"));
+        assert_eq!(events[0].calendar_name.as_deref(), Some("Work & Stuff"));
+
+        // Same range again: sync-collection reports a changed ETag, so only that object is refetched
+        let events = source.fetch_events(start, end).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "Planning v2");
+
+        let log = log.lock().unwrap();
+        let queries = log.iter().filter(|l| l.contains("calendar-query")).count();
+        let syncs: Vec<&String> = log.iter().filter(|l| l.contains("sync-collection")).collect();
+        assert_eq!(queries, 1);
+        assert_eq!(syncs.len(), 1);
+        assert!(syncs[0].contains("<d:sync-token>tok1</d:sync-token>"));
+    }
+
+    #[test]
+    fn unchanged_etags_are_not_downloaded_again() {
+        let (url, log) = fake_server();
+        let mut source = CalDavSource::new(vec![account(&url)]);
+        source
+            .fetch_events(utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"))
+            .unwrap();
+        // A wider range isn't covered by the sync token, so it re-queries ETags
+        source
+            .fetch_events(utc("2024-04-30T00:00:00Z"), utc("2024-05-03T00:00:00Z"))
+            .unwrap();
+        let log = log.lock().unwrap();
+        assert_eq!(log.iter().filter(|l| l.contains("calendar-query")).count(), 2);
+        assert_eq!(log.iter().filter(|l| l.contains("calendar-multiget")).count(), 1);
+    }
+
+    #[test]
+    fn other_ranges_do_not_evict_the_synced_window() {
+        let (url, log) = fake_server();
+        let mut source = CalDavSource::new(vec![account(&url)]);
+        let (start, end) = (utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        source.fetch_events(start, end).unwrap();
+        // e.g. the user looking at a later month
+        source
+            .fetch_events(utc("2024-08-01T00:00:00Z"), utc("2024-09-01T00:00:00Z"))
+            .unwrap();
+        let events = source.fetch_events(start, end).unwrap();
+        assert_eq!(events.len(), 1);
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.iter().filter(|l| l.contains("calendar-query")).count(), 2);
+        assert_eq!(log.iter().filter(|l| l.contains("sync-collection")).count(), 1);
+    }
+
+    #[test]
+    fn unreachable_account_does_not_hide_the_others_calendars() {
+        let (url, _) = fake_server();
+        let (down, _) = serve(Arc::new(|_, _, _| (503, String::new())));
+        let mut source = CalDavSource::new(vec![account(&down), account(&url)]);
+        let cals = source.fetch_calendars().unwrap();
+        assert_eq!(cals.len(), 1);
+        assert_eq!(cals[0].title, "Work & Stuff");
+    }
+
+    #[test]
+    fn failing_account_is_left_alone_until_retry_time() {
+        let (url, log) = serve(Arc::new(|_, _, _| (503, String::new())));
+        let mut source = CalDavSource::new(vec![account(&url)]);
+        let (start, end) = (utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        assert!(source.fetch_events(start, end).unwrap().is_empty());
+        let requests = log.lock().unwrap().len();
+        assert!(source.fetch_events(start, end).unwrap().is_empty());
+        assert_eq!(log.lock().unwrap().len(), requests);
+
+        let mut backoff = Backoff::default();
+        let now = Instant::now();
+        for _ in 0..10 {
+            backoff.failed(now);
+        }
+        assert!(backoff.is_waiting(now + Duration::from_secs(RETRY_MAX_SECS - 1)));
+        assert!(!backoff.is_waiting(now + Duration::from_secs(RETRY_MAX_SECS)));
+        backoff.succeeded();
+        assert!(!backoff.is_waiting(now));
+    }
+
+    #[test]
+    fn parses_multistatus_with_mixed_prefixes() {
+        let root = parse_xml(
+            r#"<multistatus xmlns="DAV:"><response><href>/a.ics</href><propstat><prop><getetag>"1"</getetag></prop><status>HTTP/1.1 200 OK</status></propstat><propstat><prop><displayname/></prop><status>HTTP/1.1 404 Not Found</status></propstat></response></multistatus>"#,
+        )
+        .unwrap();
+        let responses = parse_multistatus(&root);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].href, "/a.ics");
+        assert_eq!(responses[0].prop("getetag").unwrap().text, "\"1\"");
+        assert!(responses[0].prop("displayname").is_none());
+        assert!(!responses[0].removed);
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Coalesces bursts of change notifications into one resync.
+///
+/// EventKit posts `EKEventStoreChangedNotification` several times while an
+/// account syncs, so a resync fires once notifications have been quiet for
+/// `quiet`, or at the latest `max_wait` after the first one of a burst.
+#[derive(Debug)]
+pub struct Debouncer {
+    quiet: Duration,
+    max_wait: Duration,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(quiet: Duration, max_wait: Duration) -> Self {
+        Self {
+            quiet,
+            max_wait,
+            first: None,
+            last: None,
+        }
+    }
+
+    pub fn record(&mut self, now: Instant) {
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+    }
+
+    /// When the pending burst is due, or None if nothing is pending.
+    pub fn deadline(&self) -> Option<Instant> {
+        Some((self.last? + self.quiet).min(self.first? + self.max_wait))
+    }
+
+    /// Returns true (and resets) if a pending burst is due at `now`.
+    pub fn fire(&mut self, now: Instant) -> bool {
+        if self.deadline().is_some_and(|d| d <= now) {
+            self.first = None;
+            self.last = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Run `on_change` once per debounced burst received on `rx`.
+///
+/// Blocks until every sender is dropped; a burst still pending at that point
+/// is flushed first.
+pub fn run_debounced(
+    rx: mpsc::Receiver<()>,
+    mut debouncer: Debouncer,
+    mut on_change: impl FnMut(),
+) {
+    loop {
+        let received = match debouncer.deadline() {
+            None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+        };
+        match received {
+            Ok(()) => debouncer.record(Instant::now()),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if debouncer.fire(Instant::now()) {
+                    on_change();
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                if debouncer.deadline().is_some() {
+                    on_change();
+                }
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_fires_after_quiet_period_or_max_wait() {
+        let t0 = Instant::now();
+        let secs = Duration::from_secs;
+        let mut d = Debouncer::new(secs(2), secs(10));
+        assert_eq!(d.deadline(), None);
+        assert!(!d.fire(t0));
+
+        d.record(t0);
+        d.record(t0 + secs(1));
+        assert_eq!(d.deadline(), Some(t0 + secs(3)));
+        assert!(!d.fire(t0 + secs(2)));
+        assert!(d.fire(t0 + secs(3)));
+        assert_eq!(d.deadline(), None);
+
+        // A steady stream still resyncs after max_wait
+        for i in 0..10 {
+            d.record(t0 + secs(20 + i));
+        }
+        assert_eq!(d.deadline(), Some(t0 + secs(30)));
+    }
+
+    #[test]
+    fn run_debounced_coalesces_and_flushes_on_disconnect() {
+        let (tx, rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let mut count = 0;
+            let debouncer = Debouncer::new(Duration::from_millis(50), Duration::from_secs(5));
+            run_debounced(rx, debouncer, || count += 1);
+            count
+        });
+        for _ in 0..5 {
+            tx.send(()).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(200));
+        // Second burst is still pending when the sender goes away
+        tx.send(()).unwrap();
+        drop(tx);
+        assert_eq!(handle.join().unwrap(), 2);
+    }
+}
@@ -0,0 +1,467 @@
+use super::{
+    Alarm, Availability, CalendarEvent, CalendarInfo, CalendarSource, EventDateTime, Participant,
+    ParticipantRole, ParticipationStatus,
+};
+use chrono::{DateTime, Local, Utc};
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2_event_kit::{
+    EKAuthorizationStatus, EKCalendar, EKEntityType, EKEvent, EKEventStatus, EKEventStore,
+};
+use objc2_foundation::{NSArray, NSDate, NSString, NSURL};
+use std::collections::HashSet;
+use std::sync::mpsc;
+
+/// Calendar source backed by macOS Calendar via EventKit.
+///
+/// EKEventStore is not Send/Sync, so this must be created on (and only used
+/// from) the calendar thread spawned by `CalendarState`.
+pub struct EventKitSource {
+    store: Retained<EKEventStore>,
+    // Token returned by NSNotificationCenter; removed again on drop
+    change_observer: Option<Retained<AnyObject>>,
+}
+
+impl EventKitSource {
+    pub fn new() -> Self {
+        Self {
+            store: unsafe { EKEventStore::new() },
+            change_observer: None,
+        }
+    }
+}
+
+impl Drop for EventKitSource {
+    fn drop(&mut self) {
+        if let Some(observer) = self.change_observer.take() {
+            unsafe {
+                let center: Retained<AnyObject> =
+                    objc2::msg_send![objc2::class!(NSNotificationCenter), defaultCenter];
+                let _: () = objc2::msg_send![&*center, removeObserver: &*observer];
+            }
+        }
+    }
+}
+
+impl CalendarSource for EventKitSource {
+    fn fetch_events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let store = &self.store;
+        catch_objc(|| fetch_events_inner(store, start, end, None))
+    }
+
+    fn fetch_events_in(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let store = &self.store;
+        catch_objc(|| fetch_events_inner(store, start, end, calendar_ids))
+    }
+
+    fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+        let store = &self.store;
+        catch_objc(|| fetch_calendars_inner(store))
+    }
+
+    fn check_permission(&mut self) -> Result<String, String> {
+        catch_objc(check_permission_inner)
+    }
+
+    fn request_permission(&mut self) -> Result<bool, String> {
+        let store = &self.store;
+        catch_objc(|| request_permission_inner(store))
+    }
+
+    fn watch_changes(&mut self, changed: mpsc::Sender<()>) {
+        let store = &self.store;
+        match catch_objc(|| observe_store_changes(store, changed)) {
+            Ok(observer) => self.change_observer = Some(observer),
+            Err(e) => log::warn!("Failed to observe EventKit changes: {}", e),
+        }
+    }
+
+    // Only from the poll: a refresh posts EKEventStoreChangedNotification
+    // when it finds something, which already triggers a resync
+    fn refresh(&mut self) {
+        let store = &self.store;
+        let result = catch_objc(|| {
+            // Ask macOS to pull latest data from remote sources (Google, iCloud, etc.)
+            unsafe { store.refreshSourcesIfNecessary() };
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::warn!("Failed to refresh EventKit sources: {}", e);
+        }
+    }
+}
+
+// All EventKit calls are wrapped in exception::catch so ObjC exceptions are
+// turned into errors instead of aborting the process. Rust panics are caught
+// by the calendar thread.
+fn catch_objc<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match unsafe { objc2::exception::catch(std::panic::AssertUnwindSafe(f)) } {
+        Ok(v) => v,
+        Err(e) => Err(format!("ObjC exception: {:?}", e)),
+    }
+}
+
+fn nsdate_to_chrono(nsdate: &NSDate) -> DateTime<Utc> {
+    let timestamp = nsdate.timeIntervalSince1970();
+    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default()
+}
+
+// EKParticipantStatus
+fn participation_status(raw: isize) -> ParticipationStatus {
+    match raw {
+        1 => ParticipationStatus::NeedsAction,
+        2 => ParticipationStatus::Accepted,
+        3 => ParticipationStatus::Declined,
+        4 => ParticipationStatus::Tentative,
+        5 => ParticipationStatus::Delegated,
+        6 => ParticipationStatus::Completed,
+        7 => ParticipationStatus::InProcess,
+        _ => ParticipationStatus::Unknown,
+    }
+}
+
+// EKParticipantRole
+fn participant_role(raw: isize) -> ParticipantRole {
+    match raw {
+        1 => ParticipantRole::Required,
+        2 => ParticipantRole::Optional,
+        3 => ParticipantRole::Chair,
+        4 => ParticipantRole::NonParticipant,
+        _ => ParticipantRole::Unknown,
+    }
+}
+
+// EKEventAvailability; -1 (NotSupported) maps to None
+fn availability(raw: isize) -> Option<Availability> {
+    match raw {
+        0 => Some(Availability::Busy),
+        1 => Some(Availability::Free),
+        2 => Some(Availability::Tentative),
+        3 => Some(Availability::Unavailable),
+        _ => None,
+    }
+}
+
+// EKParticipant is accessed through msg_send! so no extra objc2-event-kit
+// features are needed; `URL` is usually a mailto: link.
+fn ekparticipant_to_participant(p: &AnyObject) -> Participant {
+    let name: Option<Retained<NSString>> = unsafe { objc2::msg_send![p, name] };
+    let url: Option<Retained<NSURL>> = unsafe { objc2::msg_send![p, URL] };
+    let email = url
+        .and_then(|u| u.absoluteString().map(|s| s.to_string()))
+        .map(|s| s.strip_prefix("mailto:").map(String::from).unwrap_or(s));
+    let role: isize = unsafe { objc2::msg_send![p, participantRole] };
+    let status: isize = unsafe { objc2::msg_send![p, participantStatus] };
+    let is_self: bool = unsafe { objc2::msg_send![p, isCurrentUser] };
+    Participant {
+        name: name.map(|s| s.to_string()),
+        email,
+        role: participant_role(role),
+        status: participation_status(status),
+        is_self,
+    }
+}
+
+fn ekalarm_to_alarm(alarm: &AnyObject) -> Alarm {
+    let absolute: Option<Retained<NSDate>> = unsafe { objc2::msg_send![alarm, absoluteDate] };
+    match absolute {
+        Some(date) => Alarm {
+            minutes_before: None,
+            at: Some(nsdate_to_chrono(&date).to_rfc3339()),
+        },
+        None => {
+            // relativeOffset is in seconds, negative before the start
+            let offset: f64 = unsafe { objc2::msg_send![alarm, relativeOffset] };
+            Alarm {
+                minutes_before: Some((-offset / 60.0).round() as i64),
+                at: None,
+            }
+        }
+    }
+}
+
+fn ekevent_to_calendar_event(event: &EKEvent) -> Option<CalendarEvent> {
+    // Use msg_send! with Option types for properties that may return nil
+    // to avoid panics from objc2's non-null Retained assertions.
+    let title: Option<Retained<NSString>> = unsafe { objc2::msg_send![event, title] };
+    let title = title.map(|s| s.to_string()).unwrap_or_default();
+    let start_date: Option<Retained<NSDate>> = unsafe { objc2::msg_send![event, startDate] };
+    let end_date: Option<Retained<NSDate>> = unsafe { objc2::msg_send![event, endDate] };
+    let start_date = start_date?;
+    let end_date = end_date?;
+    let is_all_day = unsafe { event.isAllDay() };
+    let event_id_base = unsafe { event.eventIdentifier() }
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+
+    let location = unsafe { event.location() }.map(|s| s.to_string());
+    let notes = unsafe { event.notes() }.map(|s| s.to_string());
+    let url = unsafe { event.URL() }
+        .and_then(|u| u.absoluteString().map(|s| s.to_string()));
+
+    let start_chrono = nsdate_to_chrono(&start_date);
+    let end_chrono = nsdate_to_chrono(&end_date);
+
+    // Recurring events share the same eventIdentifier, so append start time to make unique
+    let event_id = format!("{}_{}", event_id_base, start_chrono.timestamp());
+    let series_id = (!event_id_base.is_empty()).then(|| event_id_base.clone());
+
+    let status_raw = unsafe { event.status() };
+    let status = if status_raw == EKEventStatus::Confirmed {
+        Some("confirmed".to_string())
+    } else if status_raw == EKEventStatus::Tentative {
+        Some("tentative".to_string())
+    } else if status_raw == EKEventStatus::Canceled {
+        Some("cancelled".to_string())
+    } else {
+        None
+    };
+
+    let cal: Option<Retained<EKCalendar>> = unsafe { objc2::msg_send![event, calendar] };
+    let calendar_id = cal
+        .as_ref()
+        .map(|c| unsafe { c.calendarIdentifier() }.to_string());
+    let calendar_name = cal.as_ref().and_then(|c| {
+        let t: Option<Retained<NSString>> = unsafe { objc2::msg_send![c, title] };
+        t.map(|s| s.to_string())
+    });
+    let calendar_account_name = cal
+        .as_ref()
+        .and_then(|c| unsafe { c.source() })
+        .map(|s| unsafe { s.title() }.to_string());
+
+    let attendees: Vec<Participant> = {
+        let list: Option<Retained<NSArray<AnyObject>>> =
+            unsafe { objc2::msg_send![event, attendees] };
+        list.map(|l| l.iter().map(|p| ekparticipant_to_participant(&p)).collect())
+            .unwrap_or_default()
+    };
+    let organizer = {
+        let o: Option<Retained<AnyObject>> = unsafe { objc2::msg_send![event, organizer] };
+        o.map(|o| ekparticipant_to_participant(&o))
+    };
+    // Organizers aren't always listed among the attendees
+    let self_status = attendees
+        .iter()
+        .find(|a| a.is_self)
+        .map(|a| a.status)
+        .or_else(|| {
+            organizer
+                .as_ref()
+                .filter(|o| o.is_self)
+                .map(|_| ParticipationStatus::Accepted)
+        });
+    let is_recurring: bool = unsafe { objc2::msg_send![event, hasRecurrenceRules] };
+    let alarms: Vec<Alarm> = {
+        let list: Option<Retained<NSArray<AnyObject>>> =
+            unsafe { objc2::msg_send![event, alarms] };
+        list.map(|l| l.iter().map(|a| ekalarm_to_alarm(&a)).collect())
+            .unwrap_or_default()
+    };
+    let availability = availability(unsafe { objc2::msg_send![event, availability] });
+    let time_zone = {
+        let tz: Option<Retained<AnyObject>> = unsafe { objc2::msg_send![event, timeZone] };
+        tz.and_then(|tz| {
+            let name: Option<Retained<NSString>> = unsafe { objc2::msg_send![&*tz, name] };
+            name.map(|n| n.to_string())
+        })
+    };
+
+    // calendarItemExternalURI - use objc2 exception handling to avoid crash
+    let external_url: Option<String> = unsafe {
+        objc2::exception::catch(std::panic::AssertUnwindSafe(|| {
+            let uri: Option<Retained<NSURL>> = objc2::msg_send![event, calendarItemExternalURI];
+            uri.and_then(|u| u.absoluteString().map(|s| s.to_string()))
+        }))
+        .unwrap_or(None)
+    };
+
+    Some(CalendarEvent {
+        id: event_id,
+        summary: title,
+        start: EventDateTime {
+            date_time: if !is_all_day {
+                Some(start_chrono.to_rfc3339())
+            } else {
+                None
+            },
+            // For all-day events, format the date in the local timezone
+            // (NSDate stores UTC; midnight-local is 15:00 UTC the day before in JST,
+            // so formatting the UTC value would yield the wrong day).
+            date: if is_all_day {
+                Some(start_chrono.with_timezone(&Local).format("%Y-%m-%d").to_string())
+            } else {
+                None
+            },
+        },
+        end: EventDateTime {
+            date_time: if !is_all_day {
+                Some(end_chrono.to_rfc3339())
+            } else {
+                None
+            },
+            date: if is_all_day {
+                Some(end_chrono.with_timezone(&Local).format("%Y-%m-%d").to_string())
+            } else {
+                None
+            },
+        },
+        description: notes,
+        location,
+        url,
+        is_all_day,
+        status,
+        calendar_id,
+        calendar_name,
+        calendar_account_name,
+        external_url,
+        meeting: None,
+        attendees,
+        organizer,
+        self_status,
+        is_recurring,
+        series_id,
+        alarms,
+        availability,
+        time_zone: if is_all_day { None } else { time_zone },
+    })
+}
+
+#[allow(deprecated)]
+fn check_permission_inner() -> Result<String, String> {
+    let status =
+        unsafe { EKEventStore::authorizationStatusForEntityType(EKEntityType::Event) };
+    let s = if status == EKAuthorizationStatus::FullAccess
+        || status == EKAuthorizationStatus::Authorized
+    {
+        "granted"
+    } else if status == EKAuthorizationStatus::Denied {
+        "denied"
+    } else if status == EKAuthorizationStatus::Restricted {
+        "restricted"
+    } else {
+        "not_determined"
+    };
+    Ok(s.to_string())
+}
+
+fn request_permission_inner(store: &EKEventStore) -> Result<bool, String> {
+    let (tx, rx) = mpsc::channel();
+    let tx_arc = std::sync::Arc::new(std::sync::Mutex::new(Some(tx)));
+    let tx_clone = tx_arc.clone();
+
+    let completion = block2::RcBlock::new(
+        move |granted: objc2::runtime::Bool, _error: *mut objc2_foundation::NSError| {
+            if let Some(sender) = tx_clone.lock().unwrap().take() {
+                let _ = sender.send(granted.as_bool());
+            }
+        },
+    );
+
+    unsafe {
+        let _: () =
+            objc2::msg_send![store, requestFullAccessToEventsWithCompletion: &*completion];
+    }
+
+    rx.recv()
+        .map_err(|e| format!("Permission request failed: {}", e))
+}
+
+// Subscribe to EKEventStoreChangedNotification for `store`. With a nil queue
+// the block runs on whichever thread posts the notification, so it only
+// forwards a signal; the resync itself goes through the calendar thread.
+fn observe_store_changes(
+    store: &EKEventStore,
+    changed: mpsc::Sender<()>,
+) -> Result<Retained<AnyObject>, String> {
+    let name = NSString::from_str("EKEventStoreChangedNotification");
+    let block = block2::RcBlock::new(move |_notification: *mut AnyObject| {
+        let _ = changed.send(());
+    });
+    let observer: Option<Retained<AnyObject>> = unsafe {
+        let center: Retained<AnyObject> =
+            objc2::msg_send![objc2::class!(NSNotificationCenter), defaultCenter];
+        objc2::msg_send![
+            &*center,
+            addObserverForName: &*name,
+            object: store,
+            queue: std::ptr::null_mut::<AnyObject>(),
+            usingBlock: &*block
+        ]
+    };
+    observer.ok_or_else(|| "NSNotificationCenter returned no observer".to_string())
+}
+
+fn fetch_calendars_inner(store: &EKEventStore) -> Result<Vec<CalendarInfo>, String> {
+    let calendars =
+        unsafe { store.calendarsForEntityType(EKEntityType::Event) };
+
+    let result: Vec<CalendarInfo> = calendars
+        .iter()
+        .map(|cal| {
+            let id = unsafe { cal.calendarIdentifier() }.to_string();
+            let title = unsafe { cal.title() }.to_string();
+            let source_name = unsafe { cal.source() }
+                .map(|s| unsafe { s.title() }.to_string())
+                .unwrap_or_default();
+            CalendarInfo {
+                id,
+                title,
+                source_name,
+            }
+        })
+        .collect();
+
+    Ok(result)
+}
+
+// `start`..`end` must not span more than four years; the calendar thread chunks longer ranges.
+fn fetch_events_inner(
+    store: &EKEventStore,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    calendar_ids: Option<&HashSet<String>>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let start_nsdate = NSDate::dateWithTimeIntervalSince1970(start.timestamp() as f64);
+    let end_nsdate = NSDate::dateWithTimeIntervalSince1970(end.timestamp() as f64);
+
+    // Filter in the predicate so EventKit doesn't expand recurrences of unwanted calendars
+    let calendars = match calendar_ids {
+        Some(ids) => {
+            let selected: Vec<Retained<EKCalendar>> =
+                unsafe { store.calendarsForEntityType(EKEntityType::Event) }
+                    .iter()
+                    .filter(|cal| ids.contains(&unsafe { cal.calendarIdentifier() }.to_string()))
+                    .collect();
+            if selected.is_empty() {
+                // Nothing to query; passing no calendars would mean all of them
+                return Ok(Vec::new());
+            }
+            Some(NSArray::from_retained_slice(&selected))
+        }
+        None => None,
+    };
+    let predicate = unsafe {
+        store.predicateForEventsWithStartDate_endDate_calendars(
+            &start_nsdate,
+            &end_nsdate,
+            calendars.as_deref(),
+        )
+    };
+
+    let ek_events = unsafe { store.eventsMatchingPredicate(&predicate) };
+
+    Ok(ek_events
+        .iter()
+        .filter_map(|e| ekevent_to_calendar_event(&e))
+        .collect())
+}
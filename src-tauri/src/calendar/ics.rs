@@ -0,0 +1,1308 @@
+use super::{
+    Alarm, Availability, CalendarEvent, CalendarInfo, CalendarSource, EventDateTime, Participant,
+    ParticipantRole, ParticipationStatus,
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Upper bound on recurrence periods walked per series, so a malformed rule
+// (or a daily event from decades ago) can't stall the calendar thread.
+const MAX_RECURRENCE_PERIODS: usize = 50_000;
+
+/// Calendar source that reads `.ics` files from disk.
+///
+/// Each configured path is either a single `.ics` file (one calendar) or a
+/// directory. A directory that directly contains `.ics` files is one calendar
+/// (the vdirsyncer "one file per event" layout), and each immediate
+/// subdirectory containing `.ics` files is another, so pointing at a
+/// vdirsyncer storage root picks up every collection.
+pub struct IcsSource {
+    paths: Vec<PathBuf>,
+}
+
+struct IcsCalendar {
+    info: CalendarInfo,
+    files: Vec<PathBuf>,
+}
+
+impl IcsSource {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self { paths }
+    }
+
+    fn discover(&self) -> Vec<IcsCalendar> {
+        let mut calendars = Vec::new();
+        for path in &self.paths {
+            if path.is_dir() {
+                if let Some(cal) = dir_calendar(path) {
+                    calendars.push(cal);
+                }
+                let Ok(entries) = std::fs::read_dir(path) else {
+                    log::warn!("Failed to read ICS directory: {}", path.display());
+                    continue;
+                };
+                let mut subdirs: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.is_dir())
+                    .collect();
+                subdirs.sort();
+                calendars.extend(subdirs.iter().filter_map(|p| dir_calendar(p)));
+            } else if path.is_file() {
+                calendars.push(file_calendar(path));
+            } else {
+                log::warn!("ICS path does not exist: {}", path.display());
+            }
+        }
+        calendars
+    }
+}
+
+impl CalendarSource for IcsSource {
+    fn fetch_events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let mut events = Vec::new();
+        for cal in self.discover() {
+            // Concatenating keeps RECURRENCE-ID overrides stored in separate
+            // files (vdirsyncer) next to their master event.
+            let mut text = String::new();
+            for file in &cal.files {
+                match std::fs::read_to_string(file) {
+                    Ok(t) => {
+                        text.push_str(&t);
+                        text.push('\n');
+                    }
+                    Err(e) => log::warn!("Failed to read {}: {}", file.display(), e),
+                }
+            }
+            events.extend(events_from_ics(&text, &cal.info, start, end));
+        }
+        Ok(events)
+    }
+
+    fn fetch_calendars(&mut self) -> Result<Vec<CalendarInfo>, String> {
+        Ok(self.discover().into_iter().map(|c| c.info).collect())
+    }
+
+    // Local files need no OS permission.
+    fn check_permission(&mut self) -> Result<String, String> {
+        Ok("granted".to_string())
+    }
+
+    fn request_permission(&mut self) -> Result<bool, String> {
+        Ok(true)
+    }
+}
+
+fn is_ics_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+}
+
+fn source_name_for(path: &Path) -> String {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Local".to_string())
+}
+
+fn dir_calendar(dir: &Path) -> Option<IcsCalendar> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_ics_file(p))
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    files.sort();
+
+    // vdirsyncer stores the collection name in a `displayname` file
+    let title = std::fs::read_to_string(dir.join("displayname"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .or_else(|| dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    Some(IcsCalendar {
+        info: CalendarInfo {
+            id: format!("ics:{}", dir.display()),
+            title,
+            source_name: source_name_for(dir),
+        },
+        files,
+    })
+}
+
+fn file_calendar(file: &Path) -> IcsCalendar {
+    let title = std::fs::read_to_string(file)
+        .ok()
+        .and_then(|text| {
+            parse_components(&text)
+                .iter()
+                .find(|c| c.name == "VCALENDAR")
+                .and_then(|c| c.prop("X-WR-CALNAME"))
+                .map(|p| unescape_text(&p.value))
+        })
+        .or_else(|| file.file_stem().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    IcsCalendar {
+        info: CalendarInfo {
+            id: format!("ics:{}", file.display()),
+            title,
+            source_name: source_name_for(file),
+        },
+        files: vec![file.to_path_buf()],
+    }
+}
+
+// --- Content lines and components (RFC 5545 §3.1) ---
+
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Component {
+    name: String,
+    props: Vec<Property>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn prop(&self, name: &str) -> Option<&Property> {
+        self.props.iter().find(|p| p.name == name)
+    }
+
+    fn props_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> {
+        self.props.iter().filter(move |p| p.name == name)
+    }
+}
+
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split('\n') {
+        let line = raw.strip_suffix('\r').unwrap_or(raw);
+        if line.starts_with([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn split_outside_quotes(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == sep && !in_quotes {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = split_outside_quotes(head, ';').into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((k.trim().to_ascii_uppercase(), v.trim().trim_matches('"').to_string()))
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+/// Parse text into its top-level components (normally one VCALENDAR per file).
+fn parse_components(text: &str) -> Vec<Component> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+    for line in unfold_lines(text) {
+        let Some(prop) = parse_property(&line) else {
+            continue;
+        };
+        match prop.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: prop.value.trim().to_ascii_uppercase(),
+                ..Default::default()
+            }),
+            "END" => {
+                if let Some(done) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(done),
+                        None => roots.push(done),
+                    }
+                }
+            }
+            _ => {
+                if let Some(current) = stack.last_mut() {
+                    current.props.push(prop);
+                }
+            }
+        }
+    }
+    roots
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// --- Date/time values ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    /// No zone given: interpreted in the system's local time zone.
+    Floating,
+    Named(Tz),
+}
+
+impl Zone {
+    fn to_utc(self, wall: NaiveDateTime) -> DateTime<Utc> {
+        // For wall times that fall in a DST gap, shift forward an hour like
+        // Calendar.app does.
+        fn resolve<T: TimeZone>(tz: &T, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+            tz.from_local_datetime(&wall)
+                .earliest()
+                .or_else(|| tz.from_local_datetime(&(wall + Duration::hours(1))).earliest())
+                .map(|dt| dt.with_timezone(&Utc))
+        }
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(&wall)),
+            Zone::Floating => resolve(&Local, wall),
+            Zone::Named(tz) => resolve(&tz, wall),
+        }
+        .unwrap_or_else(|| Utc.from_utc_datetime(&wall))
+    }
+}
+
+fn resolve_tzid(tzid: &str) -> Zone {
+    if let Ok(tz) = tzid.parse::<Tz>() {
+        return Zone::Named(tz);
+    }
+    // Some producers prefix IANA names, e.g. "/mozilla.org/20050126_1/Europe/Berlin"
+    let segments: Vec<&str> = tzid.split('/').filter(|s| !s.is_empty()).collect();
+    for n in [3, 2] {
+        if segments.len() >= n {
+            if let Ok(tz) = segments[segments.len() - n..].join("/").parse::<Tz>() {
+                return Zone::Named(tz);
+            }
+        }
+    }
+    log::debug!("Unknown TZID '{}', treating as local time", tzid);
+    Zone::Floating
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IcsTime {
+    wall: NaiveDateTime,
+    zone: Zone,
+    is_date: bool,
+}
+
+impl IcsTime {
+    fn to_utc(self) -> DateTime<Utc> {
+        self.zone.to_utc(self.wall)
+    }
+
+    fn with_wall(self, wall: NaiveDateTime) -> Self {
+        Self { wall, ..self }
+    }
+}
+
+fn parse_time_value(value: &str, is_date: bool, zone: Zone) -> Option<IcsTime> {
+    let value = value.trim();
+    if is_date || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime {
+            wall: date.and_time(NaiveTime::MIN),
+            zone: Zone::Floating,
+            is_date: true,
+        });
+    }
+    let (value, zone) = match value.strip_suffix(['Z', 'z']) {
+        Some(v) => (v, Zone::Utc),
+        None => (value, zone),
+    };
+    let wall = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some(IcsTime {
+        wall,
+        zone,
+        is_date: false,
+    })
+}
+
+/// Parse a DATE / DATE-TIME property, which may hold a comma-separated list
+/// (EXDATE, RDATE).
+fn parse_time_prop(prop: &Property, default_zone: Zone) -> Vec<IcsTime> {
+    let is_date = prop.param("VALUE") == Some("DATE");
+    let zone = prop.param("TZID").map(resolve_tzid).unwrap_or(default_zone);
+    prop.value
+        .split(',')
+        .filter_map(|v| parse_time_value(v, is_date, zone))
+        .collect()
+}
+
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let s = s.strip_prefix('P')?;
+    let mut seconds = 0i64;
+    let mut num = String::new();
+    let mut in_time = false;
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            num.push(c);
+            continue;
+        }
+        let n: i64 = if c == 'T' { 0 } else { num.parse().ok()? };
+        num.clear();
+        seconds += match (c, in_time) {
+            ('T', _) => {
+                in_time = true;
+                0
+            }
+            ('W', false) => n * 7 * 86_400,
+            ('D', false) => n * 86_400,
+            ('H', true) => n * 3_600,
+            ('M', true) => n * 60,
+            ('S', true) => n,
+            _ => return None,
+        };
+    }
+    let d = Duration::seconds(seconds);
+    Some(if negative { -d } else { d })
+}
+
+// --- Recurrence rules (RFC 5545 §3.3.10) ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone)]
+struct RRule {
+    freq: Freq,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<IcsTime>,
+    /// (ordinal, weekday); ordinal 0 means "every".
+    by_day: Vec<(i32, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    by_set_pos: Vec<i32>,
+    wkst: Weekday,
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    Some(match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_rrule(value: &str, default_zone: Zone) -> Option<RRule> {
+    let mut rule = RRule {
+        freq: Freq::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+        by_set_pos: Vec::new(),
+        wkst: Weekday::Mon,
+    };
+    let mut freq = None;
+    for part in value.split(';') {
+        let Some((key, val)) = part.split_once('=') else {
+            continue;
+        };
+        let list = || val.split(',').map(str::trim);
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match val.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Freq::Daily,
+                    "WEEKLY" => Freq::Weekly,
+                    "MONTHLY" => Freq::Monthly,
+                    "YEARLY" => Freq::Yearly,
+                    other => {
+                        log::debug!("Unsupported RRULE FREQ '{}'", other);
+                        return None;
+                    }
+                })
+            }
+            "INTERVAL" => rule.interval = val.trim().parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => rule.count = val.trim().parse().ok(),
+            "UNTIL" => rule.until = parse_time_value(val, false, default_zone),
+            "BYDAY" => {
+                rule.by_day = list()
+                    .filter_map(|d| {
+                        let d = d.to_ascii_uppercase();
+                        let (ord, wd) = d.split_at(d.len().checked_sub(2)?);
+                        let ord = if ord.is_empty() { 0 } else { ord.parse().ok()? };
+                        Some((ord, parse_weekday(wd)?))
+                    })
+                    .collect()
+            }
+            "BYMONTHDAY" => rule.by_month_day = list().filter_map(|d| d.parse().ok()).collect(),
+            "BYMONTH" => rule.by_month = list().filter_map(|d| d.parse().ok()).collect(),
+            "BYSETPOS" => rule.by_set_pos = list().filter_map(|d| d.parse().ok()).collect(),
+            "WKST" => rule.wkst = parse_weekday(&val.trim().to_ascii_uppercase())?,
+            _ => {}
+        }
+    }
+    rule.freq = freq?;
+    Some(rule)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (ny, nm) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(ny, nm, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// Whether `date` is the `ord`-th (negative: from the end) `weekday` among `scope`.
+fn matches_ordinal(date: NaiveDate, ord: i32, scope: &[NaiveDate]) -> bool {
+    if ord == 0 {
+        return true;
+    }
+    let same: Vec<&NaiveDate> = scope.iter().filter(|d| d.weekday() == date.weekday()).collect();
+    let idx = if ord > 0 {
+        ord as usize - 1
+    } else {
+        match same.len().checked_sub(ord.unsigned_abs() as usize) {
+            Some(i) => i,
+            None => return false,
+        }
+    };
+    same.get(idx).is_some_and(|d| **d == date)
+}
+
+impl RRule {
+    fn matches_by_day(&self, date: NaiveDate, scope: &[NaiveDate]) -> bool {
+        self.by_day
+            .iter()
+            .any(|(ord, wd)| date.weekday() == *wd && matches_ordinal(date, *ord, scope))
+    }
+
+    fn month_candidates(&self, year: i32, month: u32, dtstart: NaiveDate) -> Vec<NaiveDate> {
+        let len = days_in_month(year, month);
+        let all: Vec<NaiveDate> = (1..=len)
+            .filter_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+            .collect();
+        let mut days: Vec<NaiveDate> = if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .filter_map(|md| {
+                    let d = if *md < 0 { len as i32 + md + 1 } else { *md };
+                    u32::try_from(d).ok().and_then(|d| NaiveDate::from_ymd_opt(year, month, d))
+                })
+                .collect()
+        } else if self.by_day.is_empty() {
+            NaiveDate::from_ymd_opt(year, month, dtstart.day()).into_iter().collect()
+        } else {
+            all.clone()
+        };
+        if !self.by_day.is_empty() {
+            days.retain(|d| self.matches_by_day(*d, &all));
+        }
+        days.sort();
+        days.dedup();
+        days
+    }
+
+    /// Candidate dates for the `index`-th period after the one containing
+    /// dtstart, or None once that period is past the dates chrono can represent.
+    fn period_candidates(&self, dtstart: NaiveDate, index: i64) -> Option<Vec<NaiveDate>> {
+        // INTERVAL is any u32, so this can run off the calendar quickly
+        let step = index.checked_mul(self.interval as i64)?;
+        let mut dates = match self.freq {
+            Freq::Daily => {
+                let day = dtstart.checked_add_signed(Duration::try_days(step)?)?;
+                let ok = (self.by_month_day.is_empty()
+                    || self.month_candidates(day.year(), day.month(), dtstart).contains(&day))
+                    && (self.by_day.is_empty()
+                        || self.by_day.iter().any(|(_, wd)| *wd == day.weekday()));
+                if ok {
+                    vec![day]
+                } else {
+                    Vec::new()
+                }
+            }
+            Freq::Weekly => {
+                let offset = |wd: Weekday| {
+                    (wd.num_days_from_monday() + 7 - self.wkst.num_days_from_monday()) % 7
+                };
+                let week_start = (dtstart - Duration::days(offset(dtstart.weekday()) as i64))
+                    .checked_add_signed(Duration::try_weeks(step)?)?;
+                let day = |wd: Weekday| {
+                    week_start.checked_add_signed(Duration::days(offset(wd) as i64))
+                };
+                if self.by_day.is_empty() {
+                    vec![day(dtstart.weekday())?]
+                } else {
+                    self.by_day.iter().map(|(_, wd)| day(*wd)).collect::<Option<_>>()?
+                }
+            }
+            Freq::Monthly => {
+                let months =
+                    (dtstart.year() as i64 * 12 + dtstart.month0() as i64).checked_add(step)?;
+                let year = i32::try_from(months / 12).ok()?;
+                NaiveDate::from_ymd_opt(year, 1, 1)?;
+                self.month_candidates(year, (months % 12) as u32 + 1, dtstart)
+            }
+            Freq::Yearly => {
+                let year = dtstart.year().checked_add(i32::try_from(step).ok()?)?;
+                NaiveDate::from_ymd_opt(year, 1, 1)?;
+                if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    // BYDAY ordinals count within the whole year
+                    let all: Vec<NaiveDate> = NaiveDate::from_ymd_opt(year, 1, 1)
+                        .map(|first| {
+                            first
+                                .iter_days()
+                                .take_while(|d| d.year() == year)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    all.iter()
+                        .copied()
+                        .filter(|d| self.matches_by_day(*d, &all))
+                        .collect()
+                } else {
+                    let months: Vec<u32> = if !self.by_month.is_empty() {
+                        self.by_month.clone()
+                    } else if !self.by_month_day.is_empty() {
+                        (1..=12).collect()
+                    } else {
+                        vec![dtstart.month()]
+                    };
+                    months
+                        .into_iter()
+                        .flat_map(|m| self.month_candidates(year, m, dtstart))
+                        .collect()
+                }
+            }
+        };
+
+        if !self.by_month.is_empty() {
+            dates.retain(|d| self.by_month.contains(&d.month()));
+        }
+        dates.sort();
+        dates.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = dates.len() as i32;
+            let mut picked: Vec<NaiveDate> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|pos| {
+                    let idx = if *pos > 0 { pos - 1 } else { len + pos };
+                    usize::try_from(idx).ok().and_then(|i| dates.get(i).copied())
+                })
+                .collect();
+            picked.sort();
+            picked.dedup();
+            dates = picked;
+        }
+        Some(dates)
+    }
+
+    /// Occurrence start times (in the event's wall clock) from dtstart up to `range_end`.
+    fn expand(&self, dtstart: IcsTime, range_end: DateTime<Utc>) -> Vec<IcsTime> {
+        let start_date = dtstart.wall.date();
+        let time = dtstart.wall.time();
+        let until_utc = self.until.map(|u| {
+            if u.is_date {
+                // A DATE-valued UNTIL includes the whole day
+                dtstart.zone.to_utc(u.wall.date().and_time(time))
+            } else {
+                u.to_utc()
+            }
+        });
+
+        // DTSTART always counts as the first occurrence
+        let mut out = vec![dtstart];
+        'periods: for index in 0..MAX_RECURRENCE_PERIODS as i64 {
+            let Some(candidates) = self.period_candidates(start_date, index) else {
+                break;
+            };
+            for date in candidates {
+                if date <= start_date {
+                    continue;
+                }
+                let occ = dtstart.with_wall(date.and_time(time));
+                let occ_utc = occ.to_utc();
+                if until_utc.is_some_and(|u| occ_utc > u) || occ_utc > range_end {
+                    break 'periods;
+                }
+                out.push(occ);
+                if self.count.is_some_and(|c| out.len() >= c) {
+                    break 'periods;
+                }
+            }
+        }
+        if let Some(c) = self.count {
+            out.truncate(c);
+        }
+        out
+    }
+}
+
+// --- Events ---
+
+#[derive(Debug, Clone)]
+struct RawEvent {
+    uid: String,
+    summary: String,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    status: Option<String>,
+    start: IcsTime,
+    end: IcsTime,
+    rrule: Option<RRule>,
+    rdates: Vec<IcsTime>,
+    exdates: Vec<IcsTime>,
+    recurrence_id: Option<IcsTime>,
+    attendees: Vec<Participant>,
+    organizer: Option<Participant>,
+    alarms: Vec<Alarm>,
+    availability: Availability,
+}
+
+fn parse_partstat(value: Option<&str>) -> ParticipationStatus {
+    // RFC 5545 §3.2.12: NEEDS-ACTION is the default
+    match value.unwrap_or("NEEDS-ACTION").to_ascii_uppercase().as_str() {
+        "NEEDS-ACTION" => ParticipationStatus::NeedsAction,
+        "ACCEPTED" => ParticipationStatus::Accepted,
+        "DECLINED" => ParticipationStatus::Declined,
+        "TENTATIVE" => ParticipationStatus::Tentative,
+        "DELEGATED" => ParticipationStatus::Delegated,
+        "COMPLETED" => ParticipationStatus::Completed,
+        "IN-PROCESS" => ParticipationStatus::InProcess,
+        _ => ParticipationStatus::Unknown,
+    }
+}
+
+fn parse_role(value: Option<&str>) -> ParticipantRole {
+    // RFC 5545 §3.2.16: REQ-PARTICIPANT is the default
+    match value.unwrap_or("REQ-PARTICIPANT").to_ascii_uppercase().as_str() {
+        "REQ-PARTICIPANT" => ParticipantRole::Required,
+        "OPT-PARTICIPANT" => ParticipantRole::Optional,
+        "CHAIR" => ParticipantRole::Chair,
+        "NON-PARTICIPANT" => ParticipantRole::NonParticipant,
+        _ => ParticipantRole::Unknown,
+    }
+}
+
+/// ATTENDEE / ORGANIZER. ICS files don't say which attendee is the user, so
+/// `is_self` is always false here.
+fn parse_participant(prop: &Property) -> Participant {
+    let value = prop.value.trim();
+    let email = match value.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &value[7..],
+        _ => value,
+    };
+    Participant {
+        name: prop.param("CN").map(unescape_text),
+        email: (!email.is_empty()).then(|| email.to_string()),
+        role: parse_role(prop.param("ROLE")),
+        status: parse_partstat(prop.param("PARTSTAT")),
+        is_self: false,
+    }
+}
+
+/// VALARM TRIGGER, either relative to the start (or end, with RELATED=END) or absolute.
+fn parse_alarm(valarm: &Component, event_length: Duration) -> Option<Alarm> {
+    let trigger = valarm.prop("TRIGGER")?;
+    if trigger.param("VALUE") == Some("DATE-TIME") {
+        let at = parse_time_value(&trigger.value, false, Zone::Utc)?;
+        return Some(Alarm {
+            minutes_before: None,
+            at: Some(at.to_utc().to_rfc3339()),
+        });
+    }
+    let mut offset = parse_duration(&trigger.value)?;
+    if trigger.param("RELATED") == Some("END") {
+        offset += event_length;
+    }
+    Some(Alarm {
+        minutes_before: Some(-offset.num_minutes()),
+        at: None,
+    })
+}
+
+fn parse_vevent(comp: &Component, default_zone: Zone) -> Option<RawEvent> {
+    let text = |name: &str| comp.prop(name).map(|p| unescape_text(&p.value));
+    let times = |name: &str| -> Vec<IcsTime> {
+        comp.props_named(name)
+            .flat_map(|p| parse_time_prop(p, default_zone))
+            .collect()
+    };
+
+    let start = *times("DTSTART").first()?;
+    let end = times("DTEND")
+        .first()
+        .copied()
+        .or_else(|| {
+            let d = comp.prop("DURATION").and_then(|p| parse_duration(&p.value))?;
+            Some(start.with_wall(start.wall + d))
+        })
+        // RFC 5545: no DTEND/DURATION means one day for DATE, zero length otherwise
+        .unwrap_or_else(|| {
+            if start.is_date {
+                start.with_wall(start.wall + Duration::days(1))
+            } else {
+                start
+            }
+        });
+
+    let status = comp
+        .prop("STATUS")
+        .and_then(|p| match p.value.trim().to_ascii_uppercase().as_str() {
+            "CONFIRMED" => Some("confirmed".to_string()),
+            "TENTATIVE" => Some("tentative".to_string()),
+            "CANCELLED" => Some("cancelled".to_string()),
+            _ => None,
+        });
+
+    let event_length = end.to_utc() - start.to_utc();
+    let availability = match comp.prop("TRANSP").map(|p| p.value.trim().to_ascii_uppercase()) {
+        Some(t) if t == "TRANSPARENT" => Availability::Free,
+        _ => Availability::Busy,
+    };
+
+    Some(RawEvent {
+        uid: text("UID").unwrap_or_else(|| content_uid(comp)),
+        summary: text("SUMMARY").unwrap_or_default(),
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        url: comp.prop("URL").map(|p| p.value.trim().to_string()),
+        status,
+        start,
+        end,
+        rrule: comp
+            .prop("RRULE")
+            .and_then(|p| parse_rrule(&p.value, default_zone)),
+        rdates: times("RDATE"),
+        exdates: times("EXDATE"),
+        recurrence_id: times("RECURRENCE-ID").first().copied(),
+        attendees: comp.props_named("ATTENDEE").map(parse_participant).collect(),
+        organizer: comp.prop("ORGANIZER").map(parse_participant),
+        alarms: comp
+            .children
+            .iter()
+            .filter(|c| c.name == "VALARM")
+            .filter_map(|c| parse_alarm(c, event_length))
+            .collect(),
+        availability,
+    })
+}
+
+/// Stand-in UID for a VEVENT without one, so its occurrences still get ids
+/// that survive a refetch. Hashes the event's content minus the timestamps
+/// generators rewrite on every export; FNV-1a since std's hasher may change
+/// between releases.
+fn content_uid(comp: &Component) -> String {
+    const VOLATILE: [&str; 4] = ["DTSTAMP", "LAST-MODIFIED", "CREATED", "SEQUENCE"];
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for prop in comp.props.iter().filter(|p| !VOLATILE.contains(&p.name.as_str())) {
+        let params = prop.params.iter().flat_map(|(k, v)| [k.as_str(), v.as_str()]);
+        for part in [prop.name.as_str()].into_iter().chain(params).chain([prop.value.as_str()]) {
+            for byte in part.bytes().chain([0]) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+    format!("nouid-{:016x}", hash)
+}
+
+fn to_calendar_event(
+    event: &RawEvent,
+    start: IcsTime,
+    end: IcsTime,
+    cal: &CalendarInfo,
+) -> CalendarEvent {
+    let is_all_day = start.is_date;
+    let (start_dt, end_dt) = if is_all_day {
+        // ICS all-day DTEND is exclusive; EventKit reports the last day itself.
+        let first = start.wall.date();
+        let last = (end.wall.date() - Duration::days(1)).max(first);
+        (
+            EventDateTime {
+                date_time: None,
+                date: Some(first.format("%Y-%m-%d").to_string()),
+            },
+            EventDateTime {
+                date_time: None,
+                date: Some(last.format("%Y-%m-%d").to_string()),
+            },
+        )
+    } else {
+        (
+            EventDateTime {
+                date_time: Some(start.to_utc().to_rfc3339()),
+                date: None,
+            },
+            EventDateTime {
+                date_time: Some(end.to_utc().to_rfc3339()),
+                date: None,
+            },
+        )
+    };
+
+    CalendarEvent {
+        // Same shape as EventKit ids, scoped by calendar since a UID is only
+        // unique within one: recurring instances share the part before `_`
+        id: format!("{}#{}_{}", cal.id, event.uid, start.to_utc().timestamp()),
+        summary: event.summary.clone(),
+        start: start_dt,
+        end: end_dt,
+        description: event.description.clone(),
+        location: event.location.clone(),
+        url: event.url.clone(),
+        is_all_day,
+        status: event.status.clone(),
+        calendar_id: Some(cal.id.clone()),
+        calendar_name: Some(cal.title.clone()),
+        calendar_account_name: Some(cal.source_name.clone()),
+        external_url: None,
+        meeting: None,
+        attendees: event.attendees.clone(),
+        organizer: event.organizer.clone(),
+        self_status: None,
+        is_recurring: event.rrule.is_some()
+            || !event.rdates.is_empty()
+            || event.recurrence_id.is_some(),
+        series_id: Some(event.uid.clone()),
+        alarms: event.alarms.clone(),
+        availability: Some(event.availability),
+        time_zone: match start.zone {
+            _ if is_all_day => None,
+            Zone::Utc => Some("UTC".to_string()),
+            Zone::Named(tz) => Some(tz.name().to_string()),
+            Zone::Floating => None,
+        },
+    }
+}
+
+/// Expand every VEVENT in `text` into concrete occurrences overlapping `[range_start, range_end]`.
+pub(super) fn events_from_ics(
+    text: &str,
+    cal: &CalendarInfo,
+    range_start: DateTime<Utc>,
+    range_end: DateTime<Utc>,
+) -> Vec<CalendarEvent> {
+    let mut masters: Vec<RawEvent> = Vec::new();
+    let mut overrides: HashMap<String, Vec<RawEvent>> = HashMap::new();
+
+    for root in parse_components(text) {
+        let default_zone = root
+            .prop("X-WR-TIMEZONE")
+            .map(|p| resolve_tzid(p.value.trim()))
+            .unwrap_or(Zone::Floating);
+        for comp in root.children.iter().filter(|c| c.name == "VEVENT") {
+            let Some(event) = parse_vevent(comp, default_zone) else {
+                continue;
+            };
+            if event.recurrence_id.is_some() {
+                overrides.entry(event.uid.clone()).or_default().push(event);
+            } else {
+                masters.push(event);
+            }
+        }
+    }
+
+    let overlaps = |start: IcsTime, end: IcsTime| {
+        let (s, e) = (start.to_utc(), end.to_utc());
+        s <= range_end && (e > range_start || s >= range_start)
+    };
+
+    let mut result = Vec::new();
+    for master in &masters {
+        let duration = master.end.wall - master.start.wall;
+        let mut starts = match &master.rrule {
+            Some(rule) => rule.expand(master.start, range_end),
+            None => vec![master.start],
+        };
+        starts.extend(master.rdates.iter().copied());
+
+        let excluded: HashSet<DateTime<Utc>> = master.exdates.iter().map(|t| t.to_utc()).collect();
+        let mut seen = HashSet::new();
+        let series_overrides = overrides.remove(&master.uid).unwrap_or_default();
+        let mut used = vec![false; series_overrides.len()];
+
+        for start in starts {
+            let key = start.to_utc();
+            if excluded.contains(&key) || !seen.insert(key) {
+                continue;
+            }
+            let replacement = series_overrides
+                .iter()
+                .position(|o| o.recurrence_id.map(|r| r.to_utc()) == Some(key));
+            let (event, s, e) = match replacement {
+                Some(i) => {
+                    used[i] = true;
+                    let o = &series_overrides[i];
+                    (o, o.start, o.end)
+                }
+                None => (master, start, start.with_wall(start.wall + duration)),
+            };
+            if overlaps(s, e) {
+                result.push(to_calendar_event(event, s, e, cal));
+            }
+        }
+
+        // Overrides whose original slot wasn't generated (e.g. beyond COUNT) still stand
+        for (o, _) in series_overrides.iter().zip(used).filter(|(_, u)| !u) {
+            if overlaps(o.start, o.end) {
+                result.push(to_calendar_event(o, o.start, o.end, cal));
+            }
+        }
+    }
+
+    // Overrides without a master in this calendar
+    for o in overrides.values().flatten() {
+        if overlaps(o.start, o.end) {
+            result.push(to_calendar_event(o, o.start, o.end, cal));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cal() -> CalendarInfo {
+        CalendarInfo {
+            id: "ics:/tmp/work.ics".to_string(),
+            title: "Work".to_string(),
+            source_name: "Local".to_string(),
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn wrap(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    fn starts(events: &[CalendarEvent]) -> Vec<String> {
+        let mut v: Vec<String> = events
+            .iter()
+            .map(|e| e.start.date_time.clone().or(e.start.date.clone()).unwrap())
+            .collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn parses_single_utc_event() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:abc\r\nSUMMARY:Standup\\, daily\r\n\
+             DTSTART:20240501T090000Z\r\nDTEND:20240501T091500Z\r\n\
+             LOCATION:https://meet.google.com/aaa-bbbb-ccc\r\n\
+             DESCRIPTION:Line one\\nLine two that is fol\r\n ded\r\n\
+             STATUS:TENTATIVE\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        assert_eq!(events.len(), 1);
+        let e = &events[0];
+        assert_eq!(e.id, format!("ics:/tmp/work.ics#abc_{}", utc("2024-05-01T09:00:00Z").timestamp()));
+        assert_eq!(e.summary, "Standup, daily");
+        assert_eq!(e.start.date_time.as_deref(), Some("2024-05-01T09:00:00+00:00"));
+        assert_eq!(e.end.date_time.as_deref(), Some("2024-05-01T09:15:00+00:00"));
+        assert_eq!(e.description.as_deref(), Some("Line one\nLine two that is folded"));
+        assert_eq!(e.status.as_deref(), Some("tentative"));
+        assert_eq!(e.calendar_id.as_deref(), Some("ics:/tmp/work.ics"));
+        assert_eq!(e.calendar_name.as_deref(), Some("Work"));
+        assert!(!e.is_all_day);
+    }
+
+    #[test]
+    fn maps_attendees_organizer_alarms_and_availability() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:sync\r\nSUMMARY:Sync\r\n\
+             DTSTART;TZID=Europe/Berlin:20240501T090000\r\nDTEND;TZID=Europe/Berlin:20240501T093000\r\n\
+             RRULE:FREQ=WEEKLY;COUNT=2\r\nTRANSP:TRANSPARENT\r\n\
+             ORGANIZER;CN=\"Ann Lee\":mailto:ann@example.com\r\n\
+             ATTENDEE;CN=Bob;ROLE=OPT-PARTICIPANT;PARTSTAT=DECLINED:MAILTO:bob@example.com\r\n\
+             ATTENDEE:mailto:carol@example.com\r\n\
+             BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT10M\r\nEND:VALARM\r\n\
+             BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER;RELATED=END:PT0S\r\nEND:VALARM\r\n\
+             BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER;VALUE=DATE-TIME:20240501T060000Z\r\nEND:VALARM\r\n\
+             END:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        assert_eq!(events.len(), 1);
+        let e = &events[0];
+        assert!(e.is_recurring);
+        assert_eq!(e.series_id.as_deref(), Some("sync"));
+        assert_eq!(e.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(e.availability, Some(Availability::Free));
+        assert_eq!(e.self_status, None);
+
+        let organizer = e.organizer.as_ref().unwrap();
+        assert_eq!(organizer.name.as_deref(), Some("Ann Lee"));
+        assert_eq!(organizer.email.as_deref(), Some("ann@example.com"));
+
+        assert_eq!(e.attendees.len(), 2);
+        assert_eq!(e.attendees[0].email.as_deref(), Some("bob@example.com"));
+        assert_eq!(e.attendees[0].role, ParticipantRole::Optional);
+        assert_eq!(e.attendees[0].status, ParticipationStatus::Declined);
+        assert_eq!(e.attendees[1].role, ParticipantRole::Required);
+        assert_eq!(e.attendees[1].status, ParticipationStatus::NeedsAction);
+
+        let minutes: Vec<Option<i64>> = e.alarms.iter().map(|a| a.minutes_before).collect();
+        assert_eq!(minutes, vec![Some(10), Some(-30), None]);
+        assert_eq!(e.alarms[2].at.as_deref(), Some("2024-05-01T06:00:00+00:00"));
+    }
+
+    #[test]
+    fn converts_tzid_to_utc() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:tz\r\nDTSTART;TZID=Asia/Tokyo:20240501T100000\r\n\
+             DURATION:PT30M\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-04-30T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        assert_eq!(events[0].start.date_time.as_deref(), Some("2024-05-01T01:00:00+00:00"));
+        assert_eq!(events[0].end.date_time.as_deref(), Some("2024-05-01T01:30:00+00:00"));
+    }
+
+    #[test]
+    fn resolves_prefixed_tzid() {
+        assert_eq!(
+            resolve_tzid("/mozilla.org/20050126_1/Europe/Berlin"),
+            Zone::Named(chrono_tz::Europe::Berlin)
+        );
+        assert_eq!(resolve_tzid("Tokyo Standard Time"), Zone::Floating);
+    }
+
+    #[test]
+    fn all_day_event_reports_inclusive_dates() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:holiday\r\nDTSTART;VALUE=DATE:20240503\r\n\
+             DTEND;VALUE=DATE:20240504\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-05-08T00:00:00Z"));
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_all_day);
+        assert_eq!(events[0].start.date.as_deref(), Some("2024-05-03"));
+        assert_eq!(events[0].end.date.as_deref(), Some("2024-05-03"));
+        assert!(events[0].start.date_time.is_none());
+    }
+
+    #[test]
+    fn weekly_rrule_with_byday_and_count() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:w\r\nDTSTART:20240506T090000Z\r\nDTEND:20240506T093000Z\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-06-30T00:00:00Z"));
+        assert_eq!(
+            starts(&events),
+            vec![
+                "2024-05-06T09:00:00+00:00",
+                "2024-05-08T09:00:00+00:00",
+                "2024-05-13T09:00:00+00:00",
+                "2024-05-15T09:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn exdate_and_recurrence_id_override() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:d\r\nSUMMARY:Daily\r\nDTSTART:20240501T090000Z\r\n\
+             DTEND:20240501T091500Z\r\nRRULE:FREQ=DAILY;UNTIL=20240504T090000Z\r\n\
+             EXDATE:20240502T090000Z\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:d\r\nSUMMARY:Moved\r\nRECURRENCE-ID:20240503T090000Z\r\n\
+             DTSTART:20240503T140000Z\r\nDTEND:20240503T141500Z\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-05-10T00:00:00Z"));
+        assert_eq!(
+            starts(&events),
+            vec![
+                "2024-05-01T09:00:00+00:00",
+                "2024-05-03T14:00:00+00:00",
+                "2024-05-04T09:00:00+00:00",
+            ]
+        );
+        let moved = events.iter().find(|e| e.summary == "Moved").unwrap();
+        assert_eq!(moved.id, format!("ics:/tmp/work.ics#d_{}", utc("2024-05-03T14:00:00Z").timestamp()));
+    }
+
+    #[test]
+    fn ids_stay_unique_without_uids_and_across_calendars() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nSUMMARY:One\r\nDTSTAMP:20240420T000000Z\r\n\
+             DTSTART:20240501T090000Z\r\nDTEND:20240501T091500Z\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Two\r\n\
+             DTSTART:20240501T090000Z\r\nDTEND:20240501T091500Z\r\nEND:VEVENT\r\n",
+        );
+        let (from, to) = (utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        let events = events_from_ics(&text, &cal(), from, to);
+        assert_eq!(events.len(), 2);
+        assert_ne!(events[0].id, events[1].id);
+
+        // A re-export only bumps DTSTAMP; the ids must not change
+        let again = events_from_ics(&text.replace("20240420T000000Z", "20240425T000000Z"), &cal(), from, to);
+        let ids = |events: &[CalendarEvent]| events.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&events), ids(&again));
+
+        let other = CalendarInfo { id: "ics:/tmp/home.ics".to_string(), ..cal() };
+        let uid = wrap("BEGIN:VEVENT\r\nUID:same\r\nDTSTART:20240501T090000Z\r\nEND:VEVENT\r\n");
+        assert_ne!(
+            events_from_ics(&uid, &cal(), from, to)[0].id,
+            events_from_ics(&uid, &other, from, to)[0].id
+        );
+    }
+
+    #[test]
+    fn monthly_last_friday_keeps_wall_time_across_dst() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:m\r\nDTSTART;TZID=America/New_York:20240126T100000\r\n\
+             DTEND;TZID=America/New_York:20240126T110000\r\n\
+             RRULE:FREQ=MONTHLY;BYDAY=-1FR\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-01-01T00:00:00Z"), utc("2024-04-01T00:00:00Z"));
+        assert_eq!(
+            starts(&events),
+            vec![
+                "2024-01-26T15:00:00+00:00",
+                "2024-02-23T15:00:00+00:00",
+                "2024-03-29T14:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_interval_stops_expanding_instead_of_overflowing() {
+        for freq in ["DAILY", "WEEKLY", "MONTHLY", "YEARLY"] {
+            let text = wrap(&format!(
+                "BEGIN:VEVENT\r\nUID:i\r\nDTSTART:20240501T090000Z\r\nDTEND:20240501T093000Z\r\n\
+                 RRULE:FREQ={};INTERVAL=4294967295\r\nEND:VEVENT\r\n",
+                freq
+            ));
+            let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-06-01T00:00:00Z"));
+            assert_eq!(starts(&events), vec!["2024-05-01T09:00:00+00:00"], "{}", freq);
+        }
+    }
+
+    #[test]
+    fn recurring_events_outside_range_are_dropped() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:y\r\nDTSTART;VALUE=DATE:20200229\r\n\
+             RRULE:FREQ=YEARLY\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-02-01T00:00:00Z"), utc("2024-03-31T00:00:00Z"));
+        assert_eq!(starts(&events), vec!["2024-02-29"]);
+    }
+
+    #[test]
+    fn cancelled_status_is_preserved_for_filtering() {
+        let text = wrap(
+            "BEGIN:VEVENT\r\nUID:c\r\nDTSTART:20240501T090000Z\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n",
+        );
+        let events = events_from_ics(&text, &cal(), utc("2024-05-01T00:00:00Z"), utc("2024-05-02T00:00:00Z"));
+        assert_eq!(events[0].status.as_deref(), Some("cancelled"));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("-P1DT2H"), Some(-Duration::hours(26)));
+        assert_eq!(parse_duration("1H"), None);
+    }
+}
@@ -0,0 +1,168 @@
+use super::CalendarEvent;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Split `[start, end]` into consecutive spans of at most `max`.
+///
+/// EventKit silently truncates predicates spanning more than four years, so
+/// long ranges are queried piecewise.
+pub fn chunk_range(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    max: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut chunks = Vec::new();
+    let mut from = start;
+    while from < end {
+        let to = (from + max).min(end);
+        chunks.push((from, to));
+        from = to;
+    }
+    chunks
+}
+
+/// Drop repeated event ids, keeping the first. Events spanning a chunk
+/// boundary are returned by both neighbouring queries.
+pub fn dedup_by_id(events: &mut Vec<CalendarEvent>) {
+    let mut seen = HashSet::new();
+    events.retain(|e| seen.insert(e.id.clone()));
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeKey {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Sorted, so the same selection in a different order hits the cache.
+    pub calendar_ids: Option<Vec<String>>,
+}
+
+impl RangeKey {
+    pub fn new(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        calendar_ids: Option<&HashSet<String>>,
+    ) -> Self {
+        let calendar_ids = calendar_ids.map(|ids| {
+            let mut ids: Vec<String> = ids.iter().cloned().collect();
+            ids.sort();
+            ids
+        });
+        Self {
+            start,
+            end,
+            calendar_ids,
+        }
+    }
+}
+
+/// Results of recent `get_events_in_range` queries.
+///
+/// Entries expire after `ttl` and the whole cache is cleared when a sync sees
+/// the calendar change, so views don't show stale data for long.
+pub struct RangeCache {
+    entries: Vec<(RangeKey, Instant, Vec<CalendarEvent>)>,
+    ttl: std::time::Duration,
+    capacity: usize,
+}
+
+impl RangeCache {
+    pub fn new(ttl: std::time::Duration, capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    pub fn get(&mut self, key: &RangeKey, now: Instant) -> Option<Vec<CalendarEvent>> {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|(_, stored, _)| now.saturating_duration_since(*stored) < ttl);
+        self.entries
+            .iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, _, events)| events.clone())
+    }
+
+    pub fn insert(&mut self, key: RangeKey, events: Vec<CalendarEvent>, now: Instant) {
+        self.entries.retain(|(k, _, _)| *k != key);
+        if self.entries.len() >= self.capacity {
+            // Oldest first
+            self.entries.remove(0);
+        }
+        self.entries.push((key, now, events));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::EventDateTime;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn event(id: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            summary: id.to_string(),
+            start: EventDateTime { date_time: None, date: Some("2024-05-01".into()) },
+            end: EventDateTime { date_time: None, date: Some("2024-05-02".into()) },
+            is_all_day: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn chunks_cover_range_without_gaps() {
+        let start = at("2020-01-01T00:00:00Z");
+        let end = at("2030-06-01T00:00:00Z");
+        let max = Duration::days(4 * 365);
+        let chunks = chunk_range(start, end, max);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].0, start);
+        assert_eq!(chunks[2].1, end);
+        assert!(chunks.windows(2).all(|w| w[0].1 == w[1].0));
+        assert!(chunks.iter().all(|(s, e)| *e - *s <= max));
+
+        let short = chunk_range(start, start + Duration::days(8), max);
+        assert_eq!(short, vec![(start, start + Duration::days(8))]);
+        assert!(chunk_range(end, start, max).is_empty());
+    }
+
+    #[test]
+    fn dedup_keeps_first_occurrence() {
+        let mut events = vec![event("a"), event("b"), event("a")];
+        dedup_by_id(&mut events);
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn cache_matches_ids_in_any_order_and_expires() {
+        let t0 = Instant::now();
+        let ttl = std::time::Duration::from_secs(60);
+        let mut cache = RangeCache::new(ttl, 2);
+        let start = at("2024-05-01T00:00:00Z");
+        let end = at("2024-05-08T00:00:00Z");
+        let ids: HashSet<String> = ["x".to_string(), "y".to_string()].into();
+        cache.insert(RangeKey::new(start, end, Some(&ids)), vec![event("a")], t0);
+
+        let same: HashSet<String> = ["y".to_string(), "x".to_string()].into();
+        assert!(cache.get(&RangeKey::new(start, end, Some(&same)), t0).is_some());
+        assert!(cache.get(&RangeKey::new(start, end, None), t0).is_none());
+        assert!(cache.get(&RangeKey::new(start, end, Some(&ids)), t0 + ttl).is_none());
+
+        // Capacity evicts the oldest entry
+        cache.insert(RangeKey::new(start, end, None), vec![], t0);
+        cache.insert(RangeKey::new(end, end + Duration::days(1), None), vec![], t0);
+        cache.insert(RangeKey::new(start, end, Some(&ids)), vec![], t0);
+        assert!(cache.get(&RangeKey::new(start, end, None), t0).is_none());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::calendar::{
+    enabled_events, Availability, CalendarEvent, CalendarState, InvitationFilter,
+    ParticipationStatus,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tauri event emitted when overlapping meetings need the user to pick one;
+/// payload `Conflict`.
+pub const CONFLICT_DETECTED: &str = "conflict-detected";
+
+/// Which of several overlapping meetings to auto-open (`conflictPolicy` setting).
+///
+/// When the policy can't single out one meeting, nothing is opened and the
+/// user is asked instead.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Meetings the user organises (or their own events without invitees)
+    PreferOrganizer,
+    /// Accepted over tentative over unanswered
+    #[default]
+    PreferAccepted,
+    /// Meetings from `conflictCalendarId`
+    PreferCalendar,
+    Ask,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConflictSettings {
+    pub policy: ConflictPolicy,
+    pub calendar_id: Option<String>,
+}
+
+impl ConflictSettings {
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        let settings = crate::settings::get(app);
+        Self {
+            policy: settings.conflict_policy,
+            calendar_id: settings.conflict_calendar_id,
+        }
+    }
+
+    /// The meeting to join among `events`, or None if the user should choose.
+    pub fn choose<'a>(&self, events: &[&'a CalendarEvent]) -> Option<&'a CalendarEvent> {
+        let rank = |e: &CalendarEvent| -> u8 {
+            match self.policy {
+                ConflictPolicy::PreferOrganizer => {
+                    let own = e
+                        .organizer
+                        .as_ref()
+                        .map_or(e.attendees.is_empty(), |o| o.is_self);
+                    own as u8
+                }
+                ConflictPolicy::PreferAccepted => match e.self_status {
+                    None | Some(ParticipationStatus::Accepted) => 3,
+                    Some(ParticipationStatus::Tentative) => 2,
+                    Some(ParticipationStatus::NeedsAction | ParticipationStatus::Unknown) => 1,
+                    Some(_) => 0,
+                },
+                ConflictPolicy::PreferCalendar => {
+                    (self.calendar_id.is_some() && e.calendar_id == self.calendar_id) as u8
+                }
+                ConflictPolicy::Ask => 0,
+            }
+        };
+        let best = events.iter().map(|e| rank(e)).max().filter(|r| *r > 0)?;
+        let mut winners = events.iter().filter(|e| rank(e) == best);
+        match (winners.next(), winners.next()) {
+            (Some(winner), None) => Some(*winner),
+            _ => None,
+        }
+    }
+}
+
+/// A group of meetings whose times overlap.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    /// In start order.
+    pub event_ids: Vec<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The meeting the conflict policy would join; None if the user is asked.
+    pub preferred: Option<String>,
+}
+
+impl Conflict {
+    pub fn contains(&self, event_id: &str) -> bool {
+        self.event_ids.iter().any(|id| id == event_id)
+    }
+}
+
+fn time_range(event: &CalendarEvent) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if event.is_all_day {
+        return None;
+    }
+    let parse = |s: &Option<String>| {
+        DateTime::parse_from_rfc3339(s.as_deref()?)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    let start = parse(&event.start.date_time)?;
+    // Zero-length events still clash with meetings starting at the same time
+    let end = parse(&event.end.date_time)
+        .unwrap_or(start)
+        .max(start + Duration::minutes(1));
+    Some((start, end))
+}
+
+/// Group `events` into clusters of overlapping meetings. All-day events and
+/// events marked free are ignored.
+pub fn find_conflicts(events: &[&CalendarEvent], settings: &ConflictSettings) -> Vec<Conflict> {
+    let mut timed: Vec<(&CalendarEvent, DateTime<Utc>, DateTime<Utc>)> = events
+        .iter()
+        .filter(|e| e.availability != Some(Availability::Free))
+        .filter_map(|e| time_range(e).map(|(s, end)| (*e, s, end)))
+        .collect();
+    timed.sort_by_key(|(_, start, _)| *start);
+
+    let mut conflicts = Vec::new();
+    let mut i = 0;
+    while i < timed.len() {
+        let (_, start, mut end) = timed[i];
+        let mut j = i + 1;
+        while j < timed.len() && timed[j].1 < end {
+            end = end.max(timed[j].2);
+            j += 1;
+        }
+        if j - i > 1 {
+            let group: Vec<&CalendarEvent> = timed[i..j].iter().map(|(e, _, _)| *e).collect();
+            conflicts.push(Conflict {
+                event_ids: group.iter().map(|e| e.id.clone()).collect(),
+                start,
+                end,
+                preferred: settings.choose(&group).map(|e| e.id.clone()),
+            });
+        }
+        i = j;
+    }
+    conflicts
+}
+
+/// `event` and the meetings among `events` that overlap it, as one conflict
+/// with the policy's pick among just those; None if nothing overlaps it.
+///
+/// Unlike the clusters from `find_conflicts`, a meeting that only overlaps
+/// one of `event`'s neighbours is left out, so `event` is never passed over
+/// for (or asked about together with) a meeting it doesn't clash with.
+pub fn conflict_with(
+    event: &CalendarEvent,
+    events: &[&CalendarEvent],
+    settings: &ConflictSettings,
+) -> Option<Conflict> {
+    let (start, end) = time_range(event)?;
+    let group: Vec<&CalendarEvent> = std::iter::once(event)
+        .chain(events.iter().copied().filter(|e| {
+            e.id != event.id && time_range(e).is_some_and(|(s, e_end)| s < end && start < e_end)
+        }))
+        .collect();
+    find_conflicts(&group, settings)
+        .into_iter()
+        .find(|c| c.contains(&event.id))
+}
+
+/// Overlapping meetings in the synced window, for flagging in the schedule.
+#[tauri::command]
+pub fn get_conflicts(
+    app: tauri::AppHandle,
+    calendar_state: tauri::State<'_, CalendarState>,
+) -> Vec<Conflict> {
+    let invitations = InvitationFilter::load(&app);
+    let events: Vec<CalendarEvent> = enabled_events(&app, &calendar_state)
+        .into_iter()
+        .filter(|e| !invitations.skips(e))
+        .collect();
+    let refs: Vec<&CalendarEvent> = events.iter().collect();
+    find_conflicts(&refs, &ConflictSettings::load(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{EventDateTime, Participant, ParticipantRole};
+
+    fn event(id: &str, start: &str, end: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            summary: id.to_string(),
+            start: EventDateTime { date_time: Some(start.to_string()), date: None },
+            end: EventDateTime { date_time: Some(end.to_string()), date: None },
+            ..Default::default()
+        }
+    }
+
+    fn invited(mut e: CalendarEvent, status: ParticipationStatus) -> CalendarEvent {
+        e.organizer = Some(Participant {
+            name: None,
+            email: Some("boss@example.com".to_string()),
+            role: ParticipantRole::Chair,
+            status: ParticipationStatus::Accepted,
+            is_self: false,
+        });
+        e.self_status = Some(status);
+        e
+    }
+
+    #[test]
+    fn groups_overlapping_meetings() {
+        let events = [
+            event("a", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z"),
+            event("b", "2024-05-01T10:30:00Z", "2024-05-01T11:30:00Z"),
+            event("c", "2024-05-01T11:15:00Z", "2024-05-01T12:00:00Z"),
+            // Back-to-back is not a conflict
+            event("d", "2024-05-01T12:00:00Z", "2024-05-01T12:30:00Z"),
+            event("e", "2024-05-01T14:00:00Z", "2024-05-01T14:00:00Z"),
+            event("f", "2024-05-01T14:00:00Z", "2024-05-01T14:30:00Z"),
+        ];
+        let refs: Vec<&CalendarEvent> = events.iter().collect();
+        let conflicts = find_conflicts(&refs, &ConflictSettings::default());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].event_ids, vec!["a", "b", "c"]);
+        assert_eq!(conflicts[0].end.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        assert_eq!(conflicts[1].event_ids, vec!["e", "f"]);
+    }
+
+    #[test]
+    fn chained_overlaps_only_weigh_meetings_that_clash() {
+        let a = event("a", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z");
+        let b = event("b", "2024-05-01T10:30:00Z", "2024-05-01T11:30:00Z");
+        let mut c = event("c", "2024-05-01T11:15:00Z", "2024-05-01T12:00:00Z");
+        c.calendar_id = Some("work".to_string());
+        let refs = vec![&a, &b, &c];
+        let settings = ConflictSettings {
+            policy: ConflictPolicy::PreferCalendar,
+            calendar_id: Some("work".to_string()),
+        };
+        // The cluster prefers c, but a never overlaps c
+        assert_eq!(find_conflicts(&refs, &settings)[0].preferred.as_deref(), Some("c"));
+
+        let for_a = conflict_with(&a, &refs, &settings).unwrap();
+        assert_eq!(for_a.event_ids, vec!["a", "b"]);
+        assert_eq!(for_a.preferred, None);
+        let for_b = conflict_with(&b, &refs, &settings).unwrap();
+        assert_eq!(for_b.event_ids, vec!["a", "b", "c"]);
+        assert_eq!(for_b.preferred.as_deref(), Some("c"));
+        let for_c = conflict_with(&c, &refs, &settings).unwrap();
+        assert_eq!(for_c.event_ids, vec!["b", "c"]);
+        assert_eq!(for_c.preferred.as_deref(), Some("c"));
+
+        let later = event("later", "2024-05-01T15:00:00Z", "2024-05-01T16:00:00Z");
+        assert_eq!(conflict_with(&later, &refs, &settings), None);
+    }
+
+    #[test]
+    fn free_and_all_day_events_never_conflict() {
+        let mut free = event("free", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z");
+        free.availability = Some(Availability::Free);
+        let mut all_day = event("all_day", "2024-05-01T00:00:00Z", "2024-05-02T00:00:00Z");
+        all_day.is_all_day = true;
+        let busy = event("busy", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z");
+        let refs = vec![&free, &all_day, &busy];
+        assert!(find_conflicts(&refs, &ConflictSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn policies_pick_a_single_meeting_or_ask() {
+        let own = event("own", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z");
+        let tentative = invited(
+            event("tentative", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z"),
+            ParticipationStatus::Tentative,
+        );
+        let mut accepted = invited(
+            event("accepted", "2024-05-01T10:00:00Z", "2024-05-01T11:00:00Z"),
+            ParticipationStatus::Accepted,
+        );
+        accepted.calendar_id = Some("work".to_string());
+
+        let settings = |policy, calendar_id: Option<&str>| ConflictSettings {
+            policy,
+            calendar_id: calendar_id.map(String::from),
+        };
+        let pick = |s: &ConflictSettings, events: &[&CalendarEvent]| {
+            s.choose(events).map(|e| e.id.clone())
+        };
+
+        let accepted_policy = settings(ConflictPolicy::PreferAccepted, None);
+        assert_eq!(pick(&accepted_policy, &[&tentative, &accepted]).as_deref(), Some("accepted"));
+        // Own event and accepted invitation rank the same: ask
+        assert_eq!(pick(&accepted_policy, &[&own, &accepted]), None);
+
+        let organizer = settings(ConflictPolicy::PreferOrganizer, None);
+        assert_eq!(pick(&organizer, &[&own, &accepted]).as_deref(), Some("own"));
+        assert_eq!(pick(&organizer, &[&tentative, &accepted]), None);
+
+        let calendar = settings(ConflictPolicy::PreferCalendar, Some("work"));
+        assert_eq!(pick(&calendar, &[&own, &accepted]).as_deref(), Some("accepted"));
+        let no_calendar = settings(ConflictPolicy::PreferCalendar, None);
+        assert_eq!(pick(&no_calendar, &[&own, &accepted]), None);
+
+        let ask = settings(ConflictPolicy::Ask, None);
+        assert_eq!(pick(&ask, &[&own, &tentative]), None);
+    }
+}
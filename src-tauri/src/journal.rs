@@ -0,0 +1,111 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "journal.json";
+const STORE_KEY: &str = "journal";
+// Entries are kept this long after the meeting ends
+const PRUNE_GRACE_MINUTES: i64 = 10;
+
+/// Meetings the scheduler already opened or notified, persisted so a restart
+/// (or the updater relaunching the app) doesn't open the same meeting twice.
+///
+/// Keyed on `CalendarEvent.id`; the value is the occurrence's end time, used for pruning.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Journal {
+    #[serde(default)]
+    pub opened: HashMap<String, DateTime<Utc>>,
+    #[serde(default)]
+    pub notified: HashMap<String, DateTime<Utc>>,
+}
+
+impl Journal {
+    pub fn was_opened(&self, event_id: &str) -> bool {
+        self.opened.contains_key(event_id)
+    }
+
+    pub fn was_notified(&self, event_id: &str) -> bool {
+        self.notified.contains_key(event_id)
+    }
+
+    /// Returns false if the meeting was already recorded.
+    pub fn mark_opened(&mut self, event_id: &str, end: DateTime<Utc>) -> bool {
+        self.opened.insert(event_id.to_string(), end).is_none()
+    }
+
+    /// Returns false if the meeting was already recorded.
+    pub fn mark_notified(&mut self, event_id: &str, end: DateTime<Utc>) -> bool {
+        self.notified.insert(event_id.to_string(), end).is_none()
+    }
+
+    /// Drop meetings that ended a while ago. Returns true if anything was removed.
+    pub fn prune(&mut self, now: DateTime<Utc>) -> bool {
+        let cutoff = now - Duration::minutes(PRUNE_GRACE_MINUTES);
+        let before = self.opened.len() + self.notified.len();
+        self.opened.retain(|_, end| *end > cutoff);
+        self.notified.retain(|_, end| *end > cutoff);
+        before != self.opened.len() + self.notified.len()
+    }
+}
+
+pub fn load(app: &tauri::AppHandle) -> Journal {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(STORE_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(app: &tauri::AppHandle, journal: &Journal) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        STORE_KEY,
+        serde_json::to_value(journal).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn marks_are_idempotent() {
+        let mut j = Journal::default();
+        let end = at("2024-05-01T10:00:00Z");
+        assert!(j.mark_opened("evt_1", end));
+        assert!(!j.mark_opened("evt_1", end));
+        assert!(j.was_opened("evt_1"));
+        assert!(!j.was_notified("evt_1"));
+    }
+
+    #[test]
+    fn prune_keeps_meetings_until_shortly_after_they_end() {
+        let mut j = Journal::default();
+        j.mark_opened("ended", at("2024-05-01T09:00:00Z"));
+        j.mark_notified("ended", at("2024-05-01T09:00:00Z"));
+        j.mark_opened("just_ended", at("2024-05-01T09:55:00Z"));
+        j.mark_notified("running", at("2024-05-01T10:30:00Z"));
+
+        assert!(j.prune(at("2024-05-01T10:00:00Z")));
+        assert!(!j.was_opened("ended"));
+        assert!(!j.was_notified("ended"));
+        assert!(j.was_opened("just_ended"));
+        assert!(j.was_notified("running"));
+        assert!(!j.prune(at("2024-05-01T10:00:00Z")));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut j = Journal::default();
+        j.mark_opened("evt_1", at("2024-05-01T10:00:00Z"));
+        let value = serde_json::to_value(&j).unwrap();
+        assert_eq!(serde_json::from_value::<Journal>(value).unwrap(), j);
+    }
+}
@@ -1,16 +1,25 @@
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     LogicalPosition, Manager,
 };
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
-use tauri_plugin_store::StoreExt;
 use tauri_plugin_updater::UpdaterExt;
 
 mod calendar;
+mod conflicts;
+mod journal;
+mod meeting_info;
 mod meeting_url;
+mod notification_actions;
+mod overrides;
+mod pause;
+mod profiles;
+mod rules;
 mod scheduler;
+mod settings;
+mod settings_file;
+mod tray_menu;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -34,13 +43,30 @@ pub fn run() {
         }))
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
-        .manage(calendar::CalendarState::new())
         .invoke_handler(tauri::generate_handler![
             calendar::check_calendar_permission,
             calendar::request_calendar_permission,
             calendar::get_calendars,
             calendar::get_todays_events,
+            calendar::get_events_in_range,
             calendar::force_sync,
+            calendar::get_enabled_calendars,
+            calendar::set_enabled_calendars,
+            conflicts::get_conflicts,
+            overrides::get_event_overrides,
+            overrides::set_event_override,
+            meeting_url::get_meeting_providers,
+            meeting_info::get_meeting_info,
+            settings::get_settings,
+            settings::update_settings,
+            settings_file::export_settings,
+            settings_file::import_settings,
+            profiles::switch_profile,
+            profiles::save_profile,
+            profiles::delete_profile,
+            pause::get_pause,
+            pause::pause_auto_open,
+            pause::resume_auto_open,
             open_calendar_settings,
             quit_app,
             set_tray_title,
@@ -52,6 +78,18 @@ pub fn run() {
             #[cfg(target_os = "macos")]
             app.set_activation_policy(tauri::ActivationPolicy::Accessory);
 
+            // Settings (and the calendar sources chosen from them) are managed
+            // here, after the store plugin is up, rather than on the builder.
+            app.manage(settings::SettingsState::init(app.handle()));
+            let settings = settings::get(app.handle());
+            app.manage(calendar::CalendarState::new(calendar::SourceConfig {
+                ics_paths: settings.ics_paths,
+                caldav_accounts: settings.caldav_accounts,
+            }));
+            // Resync as soon as Calendar reports changes; the scheduler's poll stays as a fallback
+            calendar::watch_changes(app.handle().clone());
+            settings_file::watch_sync_file(app.handle().clone());
+
             // Request notification permission so reminders/auto-open notifications
             // are actually delivered on macOS. Without this the system silently
             // drops them.
@@ -76,16 +114,10 @@ pub fn run() {
                 .map(|l| l.starts_with("ja"))
                 .unwrap_or(false);
 
-            // Build tray menu (right-click only)
-            let coffee_label = if is_ja { "開発者にコーヒーを ☕" } else { "Buy Me a Coffee ☕" };
-            let coffee = MenuItemBuilder::with_id("buy_me_a_coffee", coffee_label).build(app)?;
-            let quit_label = if is_ja { "Galopen を終了" } else { "Quit Galopen" };
-            let quit = MenuItemBuilder::with_id("quit", quit_label).build(app)?;
-            let menu = MenuBuilder::new(app)
-                .items(&[&coffee])
-                .separator()
-                .items(&[&quit])
-                .build()?;
+            // Build tray menu (right-click only); rebuilt when settings change
+            // and when the scheduler sees the upcoming meetings change
+            app.manage(tray_menu::TrayMeetings::default());
+            let menu = tray_menu::build(app.handle())?;
 
             // Build tray icon with dedicated monochrome template icon
             let tray_icon = Image::from_bytes(include_bytes!("../icons/tray-icon@2x.png"))?;
@@ -94,15 +126,7 @@ pub fn run() {
                 .icon_as_template(true)
                 .menu(&menu)
                 .show_menu_on_left_click(false)
-                .on_menu_event(move |app, event| match event.id().as_ref() {
-                    "quit" => app.exit(0),
-                    "buy_me_a_coffee" => {
-                        if let Err(e) = open::that("https://buymeacoffee.com/piro0919") {
-                            log::warn!("Failed to open Buy Me a Coffee URL: {}", e);
-                        }
-                    }
-                    _ => {}
-                })
+                .on_menu_event(|app, event| tray_menu::handle_event(app, event.id().as_ref()))
                 .on_tray_icon_event(|tray, event| {
                     if let TrayIconEvent::Click {
                         button: MouseButton::Left,
@@ -196,6 +220,8 @@ pub fn run() {
                 .build(app)?;
 
             // Start background scheduler
+            app.manage(scheduler::SchedulerWake::default());
+            app.manage(notification_actions::WaitingNotifications::default());
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 scheduler::run_scheduler(app_handle).await;
@@ -251,45 +277,7 @@ fn set_tray_title(app: tauri::AppHandle, title: String) {
 
 #[tauri::command]
 fn open_meeting_url(app: tauri::AppHandle, url: String, account: Option<String>) {
-    // For Google Meet URLs, append ?authuser=<email> so the meeting opens with
-    // the calendar's owning account rather than the browser's default account.
-    let url = if url.contains("meet.google.com") && !url.contains("authuser") {
-        match account.as_deref() {
-            Some(acc) if acc.contains('@') => {
-                let separator = if url.contains('?') { "&" } else { "?" };
-                format!("{}{}authuser={}", url, separator, acc)
-            }
-            _ => url,
-        }
-    } else {
-        url
-    };
-
-    let open_with_app = meeting_url::detect_meeting_service(&url).and_then(|service| {
-        app.store("settings.json")
-            .ok()
-            .and_then(|store| store.get("openWith"))
-            .and_then(|v| v.as_object().cloned())
-            .and_then(|obj| obj.get(service).cloned())
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .filter(|s| s != "default")
-    });
-
-    match open_with_app {
-        Some(app_path) => {
-            if let Err(e) = open::with(&url, &app_path) {
-                log::warn!("Failed to open URL with {}: {}, falling back to default", app_path, e);
-                if let Err(e) = open::that(&url) {
-                    log::error!("Failed to open URL with default handler: {}", e);
-                }
-            }
-        }
-        None => {
-            if let Err(e) = open::that(&url) {
-                log::error!("Failed to open URL: {}", e);
-            }
-        }
-    }
+    meeting_url::open_for_account(&app, &url, account.as_deref());
 }
 
 #[derive(serde::Serialize)]
@@ -302,7 +290,7 @@ struct AppOption {
 
 #[tauri::command]
 fn get_installed_apps() -> Vec<AppOption> {
-    let candidates: Vec<(&str, Option<&str>)> = vec![
+    let mut candidates: Vec<(&str, Option<&str>)> = vec![
         // Browsers
         ("Google Chrome", None),
         ("Safari", None),
@@ -312,11 +300,13 @@ fn get_installed_apps() -> Vec<AppOption> {
         ("Brave Browser", None),
         ("Vivaldi", None),
         ("Opera", None),
-        // Native meeting apps
-        ("zoom.us", Some("zoom")),
-        ("Microsoft Teams", Some("teams")),
-        ("Webex", Some("webex")),
     ];
+    // Native meeting apps
+    candidates.extend(
+        meeting_url::NATIVE_APPS
+            .iter()
+            .map(|(name, service)| (*name, Some(*service))),
+    );
 
     candidates
         .into_iter()
@@ -0,0 +1,325 @@
+use crate::calendar::CalendarEvent;
+use crate::meeting_url::{self, ProviderRegistry};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").unwrap());
+static MEETING_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:meeting id|meeting number(?: \(access code\))?|access code)\s*[:：]?\s*(\d[\d ]{4,}\d)")
+        .unwrap()
+});
+static PASSCODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:meeting password|passcode|password)\s*[:：]\s*([^\s,;]+)").unwrap()
+});
+static CONFERENCE_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bphone conference id\s*[:：]?\s*(\d[\d ]*\d)\s*#?").unwrap()
+});
+static PIN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bPIN\s*[:：]?\s*(\d[\d ]*\d)\s*#?").unwrap());
+// A phone number, optionally followed by "one tap" dial codes as in
+// Zoom's +16699006833,,98765432109#,,,,*123456#
+static PHONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\+\d[\d ().-]{5,}\d)((?:,+\*?\d+#?)*)").unwrap());
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DialIn {
+    /// Number as written in the invite, e.g. "+1 669 900 6833".
+    pub number: String,
+    /// PIN / conference id to enter after dialing.
+    pub pin: Option<String>,
+    /// Numeric passcode some one-tap lines carry after the PIN.
+    pub passcode: Option<String>,
+    /// Location text next to the number, e.g. "US (San Jose)".
+    pub label: Option<String>,
+    /// tel: URI that dials the number and enters the PIN.
+    pub tel_uri: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingInfo {
+    pub service: Option<String>,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub meeting_id: Option<String>,
+    pub passcode: Option<String>,
+    pub dial_ins: Vec<DialIn>,
+}
+
+impl MeetingInfo {
+    /// Text to paste into a phone, as the window's copy button writes it:
+    /// first dial-in with PIN, then meeting id and passcode.
+    pub fn dial_in_text(&self, is_ja: bool) -> Option<String> {
+        let first = self.dial_ins.first()?;
+        let pin = first
+            .pin
+            .as_ref()
+            .map(|pin| format!(",,{}#", pin.split_whitespace().collect::<String>()))
+            .unwrap_or_default();
+        let label = first.label.as_ref().map(|l| format!(" ({})", l)).unwrap_or_default();
+        let mut lines = vec![format!("{}{}{}", first.number, pin, label)];
+        if let Some(id) = &self.meeting_id {
+            lines.push(format!("{}: {}", if is_ja { "ミーティングID" } else { "Meeting ID" }, id));
+        }
+        if let Some(passcode) = &self.passcode {
+            lines.push(format!("{}: {}", if is_ja { "パスコード" } else { "Passcode" }, passcode));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+fn collapse_spaces(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn digits(s: &str) -> String {
+    s.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+fn tel_uri(number: &str, pin: Option<&str>, passcode: Option<&str>) -> String {
+    let mut uri = format!("tel:+{}", digits(number));
+    if let Some(pin) = pin {
+        uri.push_str(&format!(",,{}%23", digits(pin)));
+    }
+    if let Some(passcode) = passcode {
+        uri.push_str(&format!(",,,,*{}%23", passcode));
+    }
+    uri
+}
+
+/// Text around a number on its line, minus dial codes and PIN, e.g. "US (San Jose)".
+fn line_label(line: &str, number: &str) -> Option<String> {
+    let rest = line.replacen(number, " ", 1);
+    let rest = PIN.replace(&rest, " ");
+    let label = collapse_spaces(&rest);
+    let label = label.trim_matches(|c: char| c == ',' || c == '-' || c == ':' || c.is_whitespace());
+    (!label.is_empty()).then(|| label.to_string())
+}
+
+/// Meeting id or passcode encoded in the join URL itself.
+fn from_url(service: &str, url: &str) -> (Option<String>, Option<String>) {
+    let path = url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .and_then(|rest| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or("");
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let segment = |prefix: &str| path.strip_prefix(prefix).map(|s| s.trim_end_matches('/').to_string());
+    match service {
+        "zoom" => (segment("/j/").filter(|id| id.chars().all(|c| c.is_ascii_digit())), None),
+        "googleMeet" => (segment("/"), None),
+        "teams" => {
+            let passcode = url
+                .split_once('?')
+                .and_then(|(_, q)| q.split('&').find_map(|p| p.strip_prefix("p=")))
+                .map(String::from);
+            (segment("/meet/"), passcode)
+        }
+        _ => (None, None),
+    }
+}
+
+/// Extract the join URL, meeting id, passcode and dial-in numbers from an event.
+pub fn extract(registry: &ProviderRegistry, event: &CalendarEvent) -> Option<MeetingInfo> {
+    let text = [event.location.as_deref(), event.description.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = HTML_TAG.replace_all(&text, "\n").replace("&nbsp;", " ");
+
+    let link = registry.find(event);
+    let capture = |re: &Regex| {
+        re.captures(&text)
+            .map(|c| collapse_spaces(c.get(1).unwrap().as_str()))
+    };
+    let mut meeting_id = capture(&MEETING_ID);
+    let mut passcode = capture(&PASSCODE);
+    let conference_id = capture(&CONFERENCE_ID);
+
+    if let Some(link) = &link {
+        let (url_id, url_passcode) = from_url(&link.service, &link.url);
+        meeting_id = meeting_id.or(url_id);
+        passcode = passcode.or(url_passcode);
+    }
+
+    // Dialing in asks for the phone conference id (Teams) or the meeting id (Zoom)
+    let default_pin = conference_id.clone().or_else(|| {
+        meeting_id
+            .clone()
+            .filter(|id| id.chars().all(|c| c.is_ascii_digit() || c == ' '))
+    });
+
+    let mut dial_ins: Vec<DialIn> = Vec::new();
+    for line in text.lines() {
+        for caps in PHONE.captures_iter(line) {
+            let number = caps[1].to_string();
+            let mut codes = caps[2].split(',').filter(|s| !s.is_empty());
+            let (pin, tap_passcode) = match codes.next() {
+                Some(first) => (
+                    Some(first.trim_end_matches('#').to_string()),
+                    codes
+                        .find_map(|s| s.strip_prefix('*'))
+                        .map(|s| s.trim_end_matches('#').to_string()),
+                ),
+                None => (
+                    PIN.captures(line)
+                        .map(|c| collapse_spaces(&c[1]))
+                        .or_else(|| default_pin.clone()),
+                    None,
+                ),
+            };
+            dial_ins.push(DialIn {
+                tel_uri: tel_uri(&number, pin.as_deref(), tap_passcode.as_deref()),
+                label: line_label(line, &caps[0]),
+                number,
+                pin,
+                passcode: tap_passcode,
+            });
+        }
+    }
+    // Zoom lists each number twice (one-tap and by location); keep the first
+    let mut seen = std::collections::HashSet::new();
+    dial_ins.retain(|d| seen.insert(digits(&d.number)));
+
+    if link.is_none() && dial_ins.is_empty() && meeting_id.is_none() {
+        return None;
+    }
+    Some(MeetingInfo {
+        service: link.as_ref().map(|l| l.service.clone()),
+        name: link.as_ref().map(|l| l.name.clone()),
+        url: link.map(|l| l.url),
+        meeting_id,
+        passcode,
+        dial_ins,
+    })
+}
+
+#[tauri::command]
+pub fn get_meeting_info(app: tauri::AppHandle, event: CalendarEvent) -> Option<MeetingInfo> {
+    extract(&meeting_url::registry(&app), &event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event(location: Option<&str>, description: Option<&str>) -> CalendarEvent {
+        CalendarEvent {
+            id: "test".to_string(),
+            summary: "Test".to_string(),
+            description: description.map(String::from),
+            location: location.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    fn info(description: &str) -> MeetingInfo {
+        extract(&ProviderRegistry::builtin(), &make_event(None, Some(description))).unwrap()
+    }
+
+    #[test]
+    fn parses_zoom_invitation() {
+        let info = info(
+            "Join Zoom Meeting\n\
+             https://us02web.zoom.us/j/98765432109?pwd=SGVsbG8\n\
+             \n\
+             Meeting ID: 987 6543 2109\n\
+             Passcode: 123456\n\
+             \n\
+             ---\n\
+             One tap mobile\n\
+             +16699006833,,98765432109#,,,,*123456# US (San Jose)\n\
+             +13462487799,,98765432109#,,,,*123456# US (Houston)\n\
+             \n\
+             Dial by your location\n\
+             \t+1 669 900 6833 US (San Jose)\n\
+             \t+1 929 205 6099 US (New York)\n",
+        );
+        assert_eq!(info.service.as_deref(), Some("zoom"));
+        assert_eq!(info.url.as_deref(), Some("https://us02web.zoom.us/j/98765432109?pwd=SGVsbG8"));
+        assert_eq!(info.meeting_id.as_deref(), Some("987 6543 2109"));
+        assert_eq!(info.passcode.as_deref(), Some("123456"));
+        assert_eq!(info.dial_ins.len(), 3);
+        assert_eq!(
+            info.dial_ins[0],
+            DialIn {
+                number: "+16699006833".to_string(),
+                pin: Some("98765432109".to_string()),
+                passcode: Some("123456".to_string()),
+                label: Some("US (San Jose)".to_string()),
+                tel_uri: "tel:+16699006833,,98765432109%23,,,,*123456%23".to_string(),
+            }
+        );
+        // Numbers only listed "by location" get the meeting id as PIN
+        assert_eq!(info.dial_ins[2].number, "+1 929 205 6099");
+        assert_eq!(info.dial_ins[2].pin.as_deref(), Some("987 6543 2109"));
+        assert_eq!(info.dial_ins[2].label.as_deref(), Some("US (New York)"));
+    }
+
+    #[test]
+    fn parses_teams_phone_conference_id() {
+        let info = info(
+            "Microsoft Teams meeting\n\
+             Join on your computer, mobile app or room device\n\
+             Click here to join the meeting <https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc%40thread.v2/0>\n\
+             Meeting ID: 123 456 789 012\n\
+             Passcode: AbC9xY\n\
+             Or call in (audio only)\n\
+             +1 323-555-0123,,987654321# United States, Los Angeles\n\
+             Phone Conference ID: 987 654 321#\n",
+        );
+        assert_eq!(info.service.as_deref(), Some("teams"));
+        assert_eq!(info.meeting_id.as_deref(), Some("123 456 789 012"));
+        assert_eq!(info.passcode.as_deref(), Some("AbC9xY"));
+        assert_eq!(info.dial_ins.len(), 1);
+        assert_eq!(info.dial_ins[0].pin.as_deref(), Some("987654321"));
+    }
+
+    #[test]
+    fn teams_dial_in_without_one_tap_uses_conference_id() {
+        let info = info(
+            "Join: https://teams.microsoft.com/l/meetup-join/xyz\n\
+             +44 20 3443 8762 United Kingdom, London\n\
+             Phone Conference ID: 555 123 456#\n",
+        );
+        assert_eq!(info.dial_ins[0].pin.as_deref(), Some("555 123 456"));
+        assert_eq!(info.dial_ins[0].tel_uri, "tel:+442034438762,,555123456%23");
+        assert_eq!(info.dial_ins[0].label.as_deref(), Some("United Kingdom, London"));
+    }
+
+    #[test]
+    fn parses_google_meet_dial_in() {
+        let info = info(
+            "Join with Google Meet: https://meet.google.com/abc-defg-hij\n\
+             Join by phone\n\
+             (US) +1 234-567-8900 PIN: 123 456 789#\n",
+        );
+        assert_eq!(info.service.as_deref(), Some("googleMeet"));
+        assert_eq!(info.meeting_id.as_deref(), Some("abc-defg-hij"));
+        assert_eq!(info.dial_ins[0].number, "+1 234-567-8900");
+        assert_eq!(info.dial_ins[0].pin.as_deref(), Some("123 456 789"));
+        assert_eq!(info.dial_ins[0].label.as_deref(), Some("(US)"));
+        assert_eq!(
+            info.dial_in_text(false).as_deref(),
+            Some("+1 234-567-8900,,123456789# ((US))\nMeeting ID: abc-defg-hij")
+        );
+    }
+
+    #[test]
+    fn dial_in_only_event_still_has_info() {
+        let event = make_event(Some("+1 800 555 0100 Access code: 4455667"), None);
+        let info = extract(&ProviderRegistry::builtin(), &event).unwrap();
+        assert_eq!(info.url, None);
+        assert_eq!(info.meeting_id.as_deref(), Some("4455667"));
+        assert_eq!(info.dial_ins[0].pin.as_deref(), Some("4455667"));
+    }
+
+    #[test]
+    fn returns_none_without_any_meeting_details() {
+        let event = make_event(Some("Room 4B"), Some("Bring snacks"));
+        assert!(extract(&ProviderRegistry::builtin(), &event).is_none());
+    }
+}
@@ -1,92 +1,435 @@
 use crate::calendar::CalendarEvent;
+use crate::settings;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
 
-pub fn extract_meeting_url(event: &CalendarEvent) -> Option<String> {
-    let url = extract_raw_meeting_url(event)?;
+/// Anything that looks like a URL in free text; providers decide whether it is a meeting.
+static URL_CANDIDATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'\[\]()]+"#).unwrap());
 
-    // For Google Meet URLs, append ?authuser=<email> if the calendar account looks like an email
-    if url.contains("meet.google.com") {
-        if let Some(ref account) = event.calendar_account_name {
-            if account.contains('@') && !url.contains("authuser") {
-                let separator = if url.contains('?') { "&" } else { "?" };
-                return Some(format!("{}{}authuser={}", url, separator, account));
-            }
+static BUILTIN: LazyLock<Arc<ProviderRegistry>> =
+    LazyLock::new(|| Arc::new(ProviderRegistry::builtin()));
+
+// Registry including custom providers, rebuilt only when the setting changes
+static CACHE: Mutex<Option<(Vec<CustomProvider>, Arc<ProviderRegistry>)>> = Mutex::new(None);
+
+type Normalize = fn(&str, Option<&str>) -> String;
+
+/// Native meeting apps (`/Applications/<name>.app`) and the service each one handles.
+pub const NATIVE_APPS: &[(&str, &str)] = &[
+    ("zoom.us", "zoom"),
+    ("Microsoft Teams", "teams"),
+    ("Webex", "webex"),
+];
+
+/// A meeting link found in an event, as shown by the UI badge.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingLink {
+    /// Provider id, also the key of the `openWith` map.
+    pub service: String,
+    pub name: String,
+    pub url: String,
+}
+
+/// A provider added through the `customProviders` setting.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomProvider {
+    pub id: String,
+    pub name: Option<String>,
+    /// Hosts the provider serves; subdomains match too.
+    pub hosts: Vec<String>,
+    /// Regex the URL path must match, e.g. "^/room/".
+    pub path_pattern: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+}
+
+enum HostMatch {
+    /// The domain itself or any subdomain.
+    Domain(String),
+    /// Hosts whose first label is this, e.g. "jitsi" for self-hosted jitsi.example.org.
+    FirstLabel(&'static str),
+}
+
+struct Pattern {
+    host: HostMatch,
+    path: Option<Regex>,
+}
+
+pub struct Provider {
+    pub id: String,
+    pub name: String,
+    patterns: Vec<Pattern>,
+    normalize: Option<Normalize>,
+}
+
+impl Provider {
+    fn new(id: &str, name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            patterns: Vec::new(),
+            normalize: None,
+        }
+    }
+
+    /// Match `domain` (and subdomains) when the path matches `path`.
+    fn host(mut self, domain: &str, path: Option<&str>) -> Self {
+        self.patterns.push(Pattern {
+            host: HostMatch::Domain(domain.to_string()),
+            path: path.map(|p| Regex::new(p).unwrap()),
+        });
+        self
+    }
+
+    fn first_label(mut self, label: &'static str, path: Option<&str>) -> Self {
+        self.patterns.push(Pattern {
+            host: HostMatch::FirstLabel(label),
+            path: path.map(|p| Regex::new(p).unwrap()),
+        });
+        self
+    }
+
+    fn normalized_by(mut self, normalize: Normalize) -> Self {
+        self.normalize = Some(normalize);
+        self
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        let Some((host, path)) = split_url(url) else {
+            return false;
+        };
+        self.patterns.iter().any(|p| {
+            let host_ok = match &p.host {
+                HostMatch::Domain(domain) => host_matches(&host, domain),
+                HostMatch::FirstLabel(label) => host
+                    .split_once('.')
+                    .is_some_and(|(first, rest)| first == *label && rest.contains('.')),
+            };
+            host_ok && p.path.as_ref().is_none_or(|re| re.is_match(path))
+        })
+    }
+}
+
+/// Lower-cased host and path (without query/fragment) of an http(s) URL.
+fn split_url(url: &str) -> Option<(String, &str)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| {
+            // Scheme is case-insensitive
+            let (scheme, rest) = url.split_once("://")?;
+            matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https").then_some(rest)
+        })?;
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let authority = &rest[..authority_end];
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.split(':').next().unwrap_or(host).to_ascii_lowercase();
+    let after = &rest[authority_end..];
+    let path_end = after.find(['?', '#']).unwrap_or(after.len());
+    Some((host, &after[..path_end]))
+}
+
+/// `host` is `domain` or one of its subdomains (so "zoom.us.evil.com" is not Zoom).
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Append ?authuser=<email> so Meet opens with the calendar's owning account
+/// rather than the browser's default account.
+fn google_meet_authuser(url: &str, account: Option<&str>) -> String {
+    match account {
+        Some(acc) if acc.contains('@') && !url.contains("authuser") => {
+            let separator = if url.contains('?') { "&" } else { "?" };
+            format!("{}{}authuser={}", url, separator, acc)
         }
+        _ => url.to_string(),
     }
+}
 
-    Some(url)
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let query = url.split_once('?')?.1;
+    let query = query.split('#').next().unwrap_or(query);
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
 }
 
-fn extract_raw_meeting_url(event: &CalendarEvent) -> Option<String> {
-    // Priority 1: event URL property
-    if let Some(ref url) = event.url {
-        if is_meeting_url(url) {
-            return Some(url.clone());
+/// Rewrite an https meeting URL to the native app's URL scheme so it opens
+/// straight in the app instead of bouncing through a browser tab.
+pub fn native_url(service: &str, url: &str) -> Option<String> {
+    let (host, path) = split_url(url)?;
+    // Everything after the host, e.g. "/l/meetup-join/...?context=..."
+    let rest = &url[url.find("://")? + 3..];
+    let rest = &rest[rest.find(['/', '?', '#']).unwrap_or(rest.len())..];
+    match service {
+        "zoom" => {
+            let confno = path.strip_prefix("/j/")?.trim_end_matches('/');
+            if confno.is_empty() || !confno.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let domain = if host_matches(&host, "zoomgov.com") { "zoomgov.com" } else { "zoom.us" };
+            let mut native = format!("zoommtg://{}/join?confno={}", domain, confno);
+            if let Some(pwd) = query_param(url, "pwd") {
+                native.push_str("&pwd=");
+                native.push_str(pwd);
+            }
+            Some(native)
+        }
+        "teams" if host == "teams.microsoft.com" && path.starts_with("/l/meetup-join/") => {
+            Some(format!("msteams:{}", rest))
         }
+        "webex" if host == "teams.webex.com" => Some(format!("webexteams://{}{}", host, rest)),
+        "webex" => Some(format!("wbx://{}{}", host, rest)),
+        _ => None,
     }
+}
+
+fn is_native_app(service: &str, app_path: &str) -> bool {
+    let stem = Path::new(app_path).file_stem().and_then(|s| s.to_str());
+    NATIVE_APPS
+        .iter()
+        .any(|(name, s)| *s == service && stem == Some(*name))
+}
+
+/// App configured in the `openWith` map for `service`, if any.
+pub fn open_with_app(app: &tauri::AppHandle, service: &str) -> Option<String> {
+    settings::get(app).open_with.remove(service)
+}
+
+/// Open `url` the way the window's join button does: normalized for the
+/// calendar `account`, with the app configured for its service. Returns the
+/// detected service.
+pub fn open_for_account(
+    app: &tauri::AppHandle,
+    url: &str,
+    account: Option<&str>,
+) -> Option<String> {
+    let providers = registry(app);
+    let url = providers.normalize(url, account);
+    let service = providers.detect_service(&url);
+    let open_with_app = service.and_then(|service| open_with_app(app, service));
+    open_meeting(&url, service, open_with_app.as_deref());
+    service.map(String::from)
+}
+
+/// Open a meeting URL, with `app_path` if given. Native meeting apps get their own
+/// URL scheme; if no handler is registered for it we fall back to the https URL.
+pub fn open_meeting(url: &str, service: Option<&str>, app_path: Option<&str>) {
+    let Some(app_path) = app_path else {
+        if let Err(e) = open::that(url) {
+            log::error!("Failed to open URL: {}", e);
+        }
+        return;
+    };
 
-    // Priority 2: location field
-    if let Some(ref location) = event.location {
-        if let Some(url) = find_meeting_url(location) {
-            return Some(url);
+    log::info!("Opening with: {}", app_path);
+    let native = service
+        .filter(|s| is_native_app(s, app_path))
+        .and_then(|s| native_url(s, url));
+    if let Some(native) = native {
+        match open::that(&native) {
+            Ok(()) => return,
+            Err(e) => log::warn!("No handler for {}: {}, falling back to https", native, e),
         }
     }
 
-    // Priority 3: description/notes field
-    if let Some(ref desc) = event.description {
-        if let Some(url) = find_meeting_url(desc) {
-            return Some(url);
+    if let Err(e) = open::with(url, app_path) {
+        log::warn!("Failed to open with {}: {}, falling back to default", app_path, e);
+        if let Err(e) = open::that(url) {
+            log::error!("Failed to open URL with default handler: {}", e);
         }
     }
+}
 
-    None
+/// Ordered meeting providers; the first provider matching a URL wins.
+pub struct ProviderRegistry {
+    providers: Vec<Provider>,
 }
 
-/// Returns a service key for the given URL, or None if not a recognized meeting service.
-pub fn detect_meeting_service(url: &str) -> Option<&'static str> {
-    if url.contains("meet.google.com") {
-        Some("googleMeet")
-    } else if url.contains("zoom.us") {
-        Some("zoom")
-    } else if url.contains("teams.microsoft.com") {
-        Some("teams")
-    } else if url.contains("webex.com") {
-        Some("webex")
-    } else {
-        None
+impl ProviderRegistry {
+    pub fn builtin() -> Self {
+        let providers = vec![
+            Provider::new("googleMeet", "Google Meet")
+                .host("meet.google.com", Some(r"^/[\w-]+"))
+                .normalized_by(google_meet_authuser),
+            Provider::new("zoom", "Zoom")
+                .host("zoom.us", Some(r"^/(j|my|w|s|wc)/"))
+                .host("zoomgov.com", Some(r"^/(j|my|w|s|wc)/")),
+            Provider::new("teams", "Teams")
+                .host("teams.microsoft.com", Some(r"^/(l/meetup-join|meet)/"))
+                .host("teams.live.com", Some(r"^/(l/meetup-join|meet)/")),
+            Provider::new("webex", "Webex").host("webex.com", Some(r"^/.")),
+            Provider::new("slackHuddle", "Slack Huddle").host("app.slack.com", Some(r"^/huddle/")),
+            Provider::new("around", "Around").host("around.co", Some(r"^/r/")),
+            Provider::new("whereby", "Whereby").host("whereby.com", Some(r"^/[\w-]+")),
+            Provider::new("jitsi", "Jitsi")
+                .host("meet.jit.si", Some(r"^/[^/]+"))
+                .host("8x8.vc", Some(r"^/[^/]+"))
+                .first_label("jitsi", Some(r"^/[^/]+")),
+            Provider::new("goToMeeting", "GoTo Meeting")
+                .host("gotomeeting.com", Some(r"^/join/\d+"))
+                .host("meet.goto.com", Some(r"^/[\w-]+"))
+                .host("gotomeet.me", Some(r"^/[\w-]+")),
+            Provider::new("blueJeans", "BlueJeans").host("bluejeans.com", Some(r"^/\d+")),
+            Provider::new("chime", "Amazon Chime")
+                .host("chime.aws", Some(r"^/\d+"))
+                .host("app.chime.aws", Some(r"^/meetings/")),
+            Provider::new("skype", "Skype").host("join.skype.com", Some(r"^/\w+")),
+            Provider::new("zohoMeeting", "Zoho Meeting")
+                .host("meeting.zoho.com", Some(r"^/."))
+                .host("meeting.zoho.eu", Some(r"^/."))
+                .host("meeting.zoho.in", Some(r"^/."))
+                .host("meeting.zoho.com.au", Some(r"^/.")),
+            Provider::new("lark", "Lark")
+                .host("vc.larksuite.com", Some(r"^/j/"))
+                .host("vc.feishu.cn", Some(r"^/j/")),
+            Provider::new("tencentMeeting", "Tencent Meeting")
+                .host("meeting.tencent.com", Some(r"^/(dm|dw|p|s|l)/"))
+                .host("voovmeeting.com", Some(r"^/(dm|dw|p|s|l)/")),
+            Provider::new("discord", "Discord")
+                .host("discord.com", Some(r"^/(events|channels)/\d+"))
+                .host("discord.gg", Some(r"^/\w+")),
+            Provider::new("gather", "Gather").host("gather.town", Some(r"^/(app|invite|events)/")),
+            Provider::new("livestorm", "Livestorm").host("app.livestorm.co", Some(r"^/.")),
+            Provider::new("vowel", "Vowel").host("app.vowel.com", None),
+        ];
+        Self { providers }
     }
-}
 
-fn is_meeting_url(url: &str) -> bool {
-    url.contains("zoom.us")
-        || url.contains("meet.google.com")
-        || url.contains("teams.microsoft.com")
-        || url.contains("webex.com")
+    /// Built-in providers plus custom ones. Custom providers are checked first so an
+    /// internal tool can claim a host; invalid entries are logged and skipped.
+    pub fn with_custom(custom: Vec<CustomProvider>) -> Self {
+        let mut providers: Vec<Provider> = custom
+            .into_iter()
+            .filter_map(|c| {
+                let path = match c.path_pattern.as_deref().map(Regex::new).transpose() {
+                    Ok(path) => path,
+                    Err(e) => {
+                        log::warn!("Skipping custom provider '{}': {}", c.id, e);
+                        return None;
+                    }
+                };
+                let patterns = c
+                    .hosts
+                    .iter()
+                    .map(|h| Pattern {
+                        host: HostMatch::Domain(h.to_ascii_lowercase()),
+                        path: path.clone(),
+                    })
+                    .collect();
+                Some(Provider {
+                    name: c.name.unwrap_or_else(|| c.id.clone()),
+                    id: c.id,
+                    patterns,
+                    normalize: None,
+                })
+            })
+            .collect();
+        providers.extend(Self::builtin().providers);
+        Self { providers }
+    }
+
+    pub fn providers(&self) -> &[Provider] {
+        &self.providers
+    }
+
+    /// The provider serving `url`, or None if it is not a recognized meeting URL.
+    pub fn provider_for(&self, url: &str) -> Option<&Provider> {
+        self.providers.iter().find(|p| p.matches(url))
+    }
+
+    /// Service key for `url` (see `MeetingLink::service`).
+    pub fn detect_service(&self, url: &str) -> Option<&str> {
+        self.provider_for(url).map(|p| p.id.as_str())
+    }
+
+    /// First meeting URL in free text, in order of appearance.
+    fn find_in_text<'a>(&'a self, text: &str) -> Option<(&'a Provider, String)> {
+        URL_CANDIDATE.find_iter(text).find_map(|m| {
+            let url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            self.provider_for(url).map(|p| (p, url.to_string()))
+        })
+    }
+
+    /// Apply the provider's normalisation to a URL opened for `account`.
+    pub fn normalize(&self, url: &str, account: Option<&str>) -> String {
+        match self.provider_for(url).and_then(|p| p.normalize) {
+            Some(normalize) => normalize(url, account),
+            None => url.to_string(),
+        }
+    }
+
+    /// The event's meeting link: the URL property first, then location, then notes.
+    pub fn find(&self, event: &CalendarEvent) -> Option<MeetingLink> {
+        let found = event
+            .url
+            .as_deref()
+            .and_then(|url| self.provider_for(url).map(|p| (p, url.to_string())))
+            .or_else(|| event.location.as_deref().and_then(|t| self.find_in_text(t)))
+            .or_else(|| event.description.as_deref().and_then(|t| self.find_in_text(t)));
+
+        let (provider, url) = found?;
+        let url = match provider.normalize {
+            Some(normalize) => normalize(&url, event.calendar_account_name.as_deref()),
+            None => url,
+        };
+        Some(MeetingLink {
+            service: provider.id.clone(),
+            name: provider.name.clone(),
+            url,
+        })
+    }
 }
 
-fn find_meeting_url(text: &str) -> Option<String> {
-    let patterns = [
-        r"https?://[\w-]*\.?zoom\.us/j/\S+",
-        r"https?://meet\.google\.com/[\w-]+",
-        r"https?://teams\.microsoft\.com/l/meetup-join/\S+",
-        r"https?://[\w-]+\.webex\.com/\S+",
-    ];
+/// The registry for the current settings (built-ins plus `customProviders`).
+pub fn registry(app: &tauri::AppHandle) -> Arc<ProviderRegistry> {
+    let custom = settings::get(app).custom_providers;
+    if custom.is_empty() {
+        return BUILTIN.clone();
+    }
 
-    for pattern in &patterns {
-        if let Ok(re) = Regex::new(pattern) {
-            if let Some(m) = re.find(text) {
-                return Some(m.as_str().to_string());
-            }
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached, registry)) = cache.as_ref() {
+        if *cached == custom {
+            return registry.clone();
         }
     }
+    let registry = Arc::new(ProviderRegistry::with_custom(custom.clone()));
+    *cache = Some((custom, registry.clone()));
+    registry
+}
 
-    None
+#[tauri::command]
+pub fn get_meeting_providers(app: tauri::AppHandle) -> Vec<ProviderInfo> {
+    let registry = registry(&app);
+    let mut seen = std::collections::HashSet::new();
+    registry
+        .providers()
+        .iter()
+        .filter(|p| seen.insert(p.id.clone()))
+        .map(|p| ProviderInfo { id: p.id.clone(), name: p.name.clone() })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::calendar::{CalendarEvent, EventDateTime};
+    use crate::calendar::CalendarEvent;
 
     fn make_event(
         url: Option<&str>,
@@ -96,20 +439,21 @@ mod tests {
         CalendarEvent {
             id: "test".to_string(),
             summary: "Test".to_string(),
-            start: EventDateTime { date_time: None, date: None },
-            end: EventDateTime { date_time: None, date: None },
             description: description.map(String::from),
             location: location.map(String::from),
             url: url.map(String::from),
-            is_all_day: false,
-            status: None,
-            calendar_id: None,
-            calendar_name: None,
-            calendar_account_name: None,
-            external_url: None,
+            ..Default::default()
         }
     }
 
+    fn extract_meeting_url(event: &CalendarEvent) -> Option<String> {
+        BUILTIN.find(event).map(|m| m.url)
+    }
+
+    fn detect_meeting_service(url: &str) -> Option<&'static str> {
+        BUILTIN.detect_service(url)
+    }
+
     #[test]
     fn detects_known_services() {
         assert_eq!(detect_meeting_service("https://meet.google.com/abc-defg-hij"), Some("googleMeet"));
@@ -119,6 +463,71 @@ mod tests {
         assert_eq!(detect_meeting_service("https://example.com/not-a-meeting"), None);
     }
 
+    #[test]
+    fn detects_additional_services() {
+        let cases = [
+            ("https://app.slack.com/huddle/T0123ABC/C0456DEF", "slackHuddle"),
+            ("https://around.co/r/design-review", "around"),
+            ("https://whereby.com/acme-standup", "whereby"),
+            ("https://meet.jit.si/AcmeWeeklySync", "jitsi"),
+            ("https://8x8.vc/vpaas-magic-cookie-123/room", "jitsi"),
+            ("https://global.gotomeeting.com/join/123456789", "goToMeeting"),
+            ("https://meet.goto.com/123456789", "goToMeeting"),
+            ("https://bluejeans.com/123456789/1234", "blueJeans"),
+            ("https://chime.aws/1234567890", "chime"),
+            ("https://join.skype.com/AbCdEfGhIjKl", "skype"),
+            ("https://meeting.zoho.com/meeting/join?key=123456789", "zohoMeeting"),
+            ("https://meeting.zoho.eu/meeting/join?key=123456789", "zohoMeeting"),
+            ("https://vc.larksuite.com/j/123456789", "lark"),
+            ("https://vc.feishu.cn/j/123456789", "lark"),
+            ("https://meeting.tencent.com/dm/AbCdEfGh", "tencentMeeting"),
+            ("https://discord.com/events/123456789012345678/987654321098765432", "discord"),
+            ("https://discord.gg/acme?event=987654321098765432", "discord"),
+            ("https://app.gather.town/app/AbCdEf/acme-office", "gather"),
+            ("https://app.livestorm.co/acme/product-demo", "livestorm"),
+            ("https://app.vowel.com/#/g/abc-123", "vowel"),
+            ("https://teams.live.com/meet/9876543210", "teams"),
+            ("https://acme.zoomgov.com/j/1601234567", "zoom"),
+        ];
+        for (url, service) in cases {
+            assert_eq!(detect_meeting_service(url), Some(service), "{}", url);
+        }
+    }
+
+    #[test]
+    fn detects_self_hosted_jitsi() {
+        assert_eq!(detect_meeting_service("https://jitsi.example.org/TeamSync"), Some("jitsi"));
+        assert_eq!(detect_meeting_service("https://jitsi.example.org/"), None);
+        assert_eq!(detect_meeting_service("https://notjitsi.example.org/TeamSync"), None);
+    }
+
+    #[test]
+    fn ignores_non_meeting_pages_on_service_domains() {
+        assert_eq!(detect_meeting_service("https://app.slack.com/client/T0123/C0456"), None);
+        assert_eq!(detect_meeting_service("https://www.gotomeeting.com/pricing"), None);
+        assert_eq!(detect_meeting_service("https://discord.com/download"), None);
+        assert_eq!(detect_meeting_service("https://gather.town/pricing"), None);
+    }
+
+    #[test]
+    fn extracts_new_services_from_description() {
+        let event = make_event(
+            None,
+            Some("Room 4B"),
+            Some("Agenda: https://docs.example.com/agenda\nJoin: https://whereby.com/acme-standup"),
+        );
+        assert_eq!(extract_meeting_url(&event).as_deref(), Some("https://whereby.com/acme-standup"));
+    }
+
+    #[test]
+    fn host_matching_is_anchored() {
+        assert_eq!(detect_meeting_service("https://zoom.us.evil.com/j/123"), None);
+        assert_eq!(detect_meeting_service("https://notzoom.us/j/123"), None);
+        assert_eq!(detect_meeting_service("https://example.com/?next=https://zoom.us/j/1"), None);
+        assert_eq!(detect_meeting_service("HTTPS://US02WEB.ZOOM.US/j/123"), Some("zoom"));
+        assert_eq!(detect_meeting_service("https://us02web.zoom.us:443/j/123"), Some("zoom"));
+    }
+
     #[test]
     fn url_field_takes_priority_over_location_and_description() {
         let event = make_event(
@@ -162,6 +571,16 @@ mod tests {
         assert!(url.starts_with("https://us02web.zoom.us/j/9876543210"));
     }
 
+    #[test]
+    fn trims_surrounding_punctuation_and_markup() {
+        let event = make_event(
+            None,
+            None,
+            Some("Link: <https://us02web.zoom.us/j/111>. Or (https://meet.google.com/abc-defg-hij)."),
+        );
+        assert_eq!(extract_meeting_url(&event).as_deref(), Some("https://us02web.zoom.us/j/111"));
+    }
+
     #[test]
     fn returns_none_when_no_meeting_url_present() {
         let event = make_event(
@@ -205,4 +624,73 @@ mod tests {
             Some("https://meet.google.com/abc-defg-hij")
         );
     }
+
+    #[test]
+    fn zoom_links_become_zoommtg() {
+        assert_eq!(
+            native_url("zoom", "https://us02web.zoom.us/j/9876543210?pwd=abc123&uname=x").as_deref(),
+            Some("zoommtg://zoom.us/join?confno=9876543210&pwd=abc123")
+        );
+        assert_eq!(
+            native_url("zoom", "https://acme.zoomgov.com/j/1601234567").as_deref(),
+            Some("zoommtg://zoomgov.com/join?confno=1601234567")
+        );
+        // Personal room links have no meeting number to hand over
+        assert_eq!(native_url("zoom", "https://zoom.us/my/jdoe"), None);
+    }
+
+    #[test]
+    fn teams_and_webex_links_use_native_schemes() {
+        assert_eq!(
+            native_url(
+                "teams",
+                "https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc%40thread.v2/0?context=%7b%7d"
+            )
+            .as_deref(),
+            Some("msteams:/l/meetup-join/19%3ameeting_abc%40thread.v2/0?context=%7b%7d")
+        );
+        assert_eq!(native_url("teams", "https://teams.live.com/meet/9876543210"), None);
+        assert_eq!(
+            native_url("webex", "https://acme.webex.com/meet/jdoe").as_deref(),
+            Some("wbx://acme.webex.com/meet/jdoe")
+        );
+        assert_eq!(
+            native_url("webex", "https://teams.webex.com/spaces/abc").as_deref(),
+            Some("webexteams://teams.webex.com/spaces/abc")
+        );
+        assert_eq!(native_url("googleMeet", "https://meet.google.com/abc-defg-hij"), None);
+    }
+
+    #[test]
+    fn native_scheme_only_for_native_apps() {
+        assert!(is_native_app("zoom", "/Applications/zoom.us.app"));
+        assert!(is_native_app("teams", "/Applications/Microsoft Teams.app"));
+        assert!(!is_native_app("zoom", "/Applications/Google Chrome.app"));
+        assert!(!is_native_app("teams", "/Applications/zoom.us.app"));
+    }
+
+    #[test]
+    fn custom_providers_are_matched_before_builtins() {
+        let custom: Vec<CustomProvider> = serde_json::from_value(serde_json::json!([
+            { "id": "acmeVideo", "name": "Acme Video", "hosts": ["Video.Acme.internal"], "pathPattern": "^/room/" },
+            { "id": "broken", "hosts": ["x.example"], "pathPattern": "(" }
+        ]))
+        .unwrap();
+        let registry = ProviderRegistry::with_custom(custom);
+
+        assert_eq!(registry.detect_service("https://video.acme.internal/room/42"), Some("acmeVideo"));
+        assert_eq!(registry.detect_service("https://video.acme.internal/admin"), None);
+        assert_eq!(registry.detect_service("https://x.example/anything"), None);
+        assert_eq!(registry.detect_service("https://us02web.zoom.us/j/1"), Some("zoom"));
+
+        let event = make_event(None, None, Some("Join https://video.acme.internal/room/42 today"));
+        assert_eq!(
+            registry.find(&event),
+            Some(MeetingLink {
+                service: "acmeVideo".to_string(),
+                name: "Acme Video".to_string(),
+                url: "https://video.acme.internal/room/42".to_string(),
+            })
+        );
+    }
 }
@@ -0,0 +1,332 @@
+use crate::calendar::CalendarEvent;
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+
+/// Minutes the snooze button puts a reminder off by.
+pub const SNOOZE_MINUTES: i64 = 2;
+/// Notifications with buttons that may wait for an answer at once; further
+/// ones are posted without buttons.
+const MAX_WAITING: usize = 8;
+
+/// Buttons offered on meeting notifications.
+///
+/// Presses are handed to the scheduler (see `scheduler::queue_action`), which
+/// owns the opened/notified journal and the per-event overrides.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotificationAction {
+    /// Open the meeting now, the same way the window's join button does
+    JoinNow,
+    /// Remind again in `SNOOZE_MINUTES`
+    Snooze,
+    /// Don't auto-open this occurrence
+    Skip,
+}
+
+impl NotificationAction {
+    pub const ALL: [NotificationAction; 3] = [Self::JoinNow, Self::Snooze, Self::Skip];
+
+    fn label(self, is_ja: bool) -> String {
+        match (self, is_ja) {
+            (Self::JoinNow, true) => "今すぐ参加".to_string(),
+            (Self::JoinNow, false) => "Join now".to_string(),
+            (Self::Snooze, true) => format!("{}分後に再通知", SNOOZE_MINUTES),
+            (Self::Snooze, false) => format!("Snooze {} min", SNOOZE_MINUTES),
+            (Self::Skip, true) => "開かない".to_string(),
+            (Self::Skip, false) => "Don't open this one".to_string(),
+        }
+    }
+
+}
+
+/// A notification button: `action` on `event_id` when pressed.
+#[derive(Clone, Debug, PartialEq)]
+struct Button {
+    label: String,
+    event_id: String,
+    action: NotificationAction,
+}
+
+impl Button {
+    fn for_actions(event_id: &str, actions: &[NotificationAction], is_ja: bool) -> Vec<Self> {
+        actions
+            .iter()
+            .map(|&action| Self {
+                label: action.label(is_ja),
+                event_id: event_id.to_string(),
+                action,
+            })
+            .collect()
+    }
+
+    /// "Join <summary>" for each meeting.
+    fn for_choice(events: &[&CalendarEvent], is_ja: bool) -> Vec<Self> {
+        events
+            .iter()
+            .map(|e| Self {
+                label: if is_ja {
+                    format!("参加: {}", e.summary)
+                } else {
+                    format!("Join {}", e.summary)
+                },
+                event_id: e.id.clone(),
+                action: NotificationAction::JoinNow,
+            })
+            .collect()
+    }
+
+    /// The notification reports the pressed button by its title.
+    fn pressed<'a>(buttons: &'a [Self], label: &str) -> Option<&'a Self> {
+        buttons.iter().find(|b| b.label == label)
+    }
+}
+
+/// Notifications with buttons that are still waiting for an answer.
+///
+/// mac-notification-sys blocks a thread per notification until it is
+/// answered or leaves Notification Center, and reminders the user never
+/// touches stay there indefinitely. `expire` removes them once their meeting
+/// has ended, which ends their threads; `MAX_WAITING` bounds how many can be
+/// waiting in the meantime.
+///
+/// The crate picks the notification identifiers itself, so each one is found
+/// again by a ticket `tag` invisibly appended to its text.
+#[derive(Default)]
+pub struct WaitingNotifications(Mutex<Waiting>);
+
+#[derive(Default)]
+struct Waiting {
+    next_id: u64,
+    entries: Vec<WaitingEntry>,
+}
+
+struct WaitingEntry {
+    id: u64,
+    until: DateTime<Utc>,
+}
+
+/// Zero-width marker for ticket `id`: a word joiner, then its bits.
+fn tag(id: u64) -> String {
+    let bits = format!("{:b}", id);
+    std::iter::once('\u{2060}')
+        .chain(bits.chars().map(|b| if b == '1' { '\u{200C}' } else { '\u{200B}' }))
+        .collect()
+}
+
+impl WaitingNotifications {
+    /// Track a notification about to wait for an answer until `until`.
+    /// None if `MAX_WAITING` are waiting already.
+    fn register(&self, until: DateTime<Utc>) -> Option<u64> {
+        let mut waiting = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if waiting.entries.len() >= MAX_WAITING {
+            return None;
+        }
+        waiting.next_id += 1;
+        let id = waiting.next_id;
+        waiting.entries.push(WaitingEntry { id, until });
+        Some(id)
+    }
+
+    fn answered(&self, id: u64) {
+        let mut waiting = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        waiting.entries.retain(|e| e.id != id);
+    }
+
+    /// Stop tracking the notifications whose meeting has ended by `now` and
+    /// return their tickets, so they no longer count against `MAX_WAITING`
+    /// even if taking them down fails.
+    fn expired(&self, now: DateTime<Utc>) -> Vec<u64> {
+        let mut waiting = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let (ended, waiting_on): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut waiting.entries).into_iter().partition(|e| e.until <= now);
+        waiting.entries = waiting_on;
+        ended.into_iter().map(|e| e.id).collect()
+    }
+}
+
+/// Show a notification about `event_id` with `actions` as buttons. `until`
+/// is when the meeting ends; answers after that are ignored.
+pub fn show(
+    app: &tauri::AppHandle,
+    body: String,
+    event_id: &str,
+    until: DateTime<Utc>,
+    actions: &[NotificationAction],
+    is_ja: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    post(app, body, until, Button::for_actions(event_id, actions, is_ja), is_ja)
+}
+
+/// Ask which of the overlapping `events` to join, with a join button for
+/// each. `until` is when the overlap ends; answers after that are ignored.
+pub fn show_choice(
+    app: &tauri::AppHandle,
+    body: String,
+    events: &[&CalendarEvent],
+    until: DateTime<Utc>,
+    is_ja: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    post(app, body, until, Button::for_choice(events, is_ja), is_ja)
+}
+
+/// Buttons need the macOS notification API directly, which blocks until the
+/// notification is answered, so it is posted from its own thread (see
+/// `WaitingNotifications`). Elsewhere, and while too many are waiting, this
+/// falls back to a plain notification.
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+fn post(
+    app: &tauri::AppHandle,
+    body: String,
+    until: DateTime<Utc>,
+    buttons: Vec<Button>,
+    is_ja: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(target_os = "macos")]
+    {
+        use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+        use tauri::Manager;
+
+        let ticket = app
+            .try_state::<WaitingNotifications>()
+            .and_then(|waiting| waiting.inner().register(until));
+        if let Some(ticket) = ticket {
+            // The notification plugin already registered our bundle id with
+            // mac-notification-sys at startup, so the app's icon and name are used.
+            let app = app.clone();
+            let message = body + &tag(ticket);
+            std::thread::spawn(move || {
+                let labels: Vec<&str> = buttons.iter().map(|b| b.label.as_str()).collect();
+                let main_button = match labels.as_slice() {
+                    [single] => MainButton::SingleAction(*single),
+                    _ => MainButton::DropdownActions(if is_ja { "操作" } else { "Actions" }, &labels),
+                };
+                let response = Notification::new()
+                    .title("Galopen")
+                    .message(&message)
+                    .main_button(main_button)
+                    .close_button(if is_ja { "閉じる" } else { "Close" })
+                    .send();
+                if let Some(waiting) = app.try_state::<WaitingNotifications>() {
+                    waiting.inner().answered(ticket);
+                }
+                match response {
+                    Ok(NotificationResponse::ActionButton(label)) if Utc::now() > until => {
+                        log::info!("Ignoring notification action for ended meeting: {}", label);
+                    }
+                    Ok(NotificationResponse::ActionButton(label)) => {
+                        match Button::pressed(&buttons, &label) {
+                            Some(b) => crate::scheduler::queue_action(&app, &b.event_id, b.action),
+                            None => log::warn!("Unknown notification action: {}", label),
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to show notification: {}", e),
+                }
+            });
+            return Ok(());
+        }
+        log::info!("Too many notifications waiting for an answer, posting without buttons");
+    }
+
+    use tauri_plugin_notification::NotificationExt;
+    app.notification().builder().title("Galopen").body(body).show()?;
+    Ok(())
+}
+
+/// Take notifications whose meeting has ended out of Notification Center,
+/// which ends the threads waiting on them. Called on every scheduler pass.
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+pub fn expire(app: &tauri::AppHandle, now: DateTime<Utc>) {
+    #[cfg(target_os = "macos")]
+    {
+        use tauri::Manager;
+        let Some(waiting) = app.try_state::<WaitingNotifications>() else {
+            return;
+        };
+        let tags: Vec<String> = waiting.inner().expired(now).into_iter().map(tag).collect();
+        if tags.is_empty() {
+            return;
+        }
+        if let Err(e) = app.run_on_main_thread(move || remove_delivered(&tags)) {
+            log::warn!("Failed to remove ended notifications: {}", e);
+        }
+    }
+}
+
+/// mac-notification-sys posts with NSUserNotification and treats one that
+/// disappears from the delivered list as dismissed.
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn remove_delivered(tags: &[String]) {
+    use objc2_foundation::NSUserNotificationCenter;
+    let center = NSUserNotificationCenter::defaultUserNotificationCenter();
+    for notification in center.deliveredNotifications().iter() {
+        let text = notification.informativeText().map(|text| text.to_string());
+        if text.is_some_and(|t| tags.iter().any(|tag| t.ends_with(tag.as_str()))) {
+            center.removeDeliveredNotification(&notification);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_map_back_to_their_action() {
+        for is_ja in [false, true] {
+            let buttons = Button::for_actions("evt_1", &NotificationAction::ALL, is_ja);
+            for action in NotificationAction::ALL {
+                let pressed = Button::pressed(&buttons, &action.label(is_ja)).unwrap();
+                assert_eq!((pressed.event_id.as_str(), pressed.action), ("evt_1", action));
+            }
+        }
+        let english = Button::for_actions("evt_1", &NotificationAction::ALL, false);
+        assert_eq!(Button::pressed(&english, "今すぐ参加"), None);
+        assert_eq!(NotificationAction::Snooze.label(false), "Snooze 2 min");
+    }
+
+    #[test]
+    fn choice_buttons_join_their_own_meeting() {
+        let meeting = |id: &str, summary: &str| CalendarEvent {
+            id: id.to_string(),
+            summary: summary.to_string(),
+            ..Default::default()
+        };
+        let (standup, review) = (meeting("a_1", "Standup"), meeting("b_1", "Review"));
+        let buttons = Button::for_choice(&[&standup, &review], false);
+        let pressed = Button::pressed(&buttons, "Join Review").unwrap();
+        assert_eq!((pressed.event_id.as_str(), pressed.action), ("b_1", NotificationAction::JoinNow));
+    }
+
+    #[test]
+    fn waiting_notifications_are_capped_and_expire_with_their_meeting() {
+        let now = Utc::now();
+        let waiting = WaitingNotifications::default();
+        let first = waiting.register(now).unwrap();
+        let mut later = Vec::new();
+        for i in 1..MAX_WAITING {
+            later.push(waiting.register(now + chrono::Duration::hours(i as i64)).unwrap());
+        }
+        assert_eq!(waiting.register(now), None);
+
+        // Ending the meeting frees its slot, whether or not its thread returns
+        assert_eq!(waiting.expired(now), vec![first]);
+        assert!(waiting.expired(now).is_empty());
+        assert!(waiting.register(now + chrono::Duration::hours(9)).is_some());
+        assert_eq!(waiting.register(now), None);
+        waiting.answered(later[0]);
+        assert!(waiting.register(now).is_some());
+    }
+
+    #[test]
+    fn tags_are_invisible_and_tell_tickets_apart() {
+        let tags: Vec<String> = (1..=8).map(tag).collect();
+        for (i, a) in tags.iter().enumerate() {
+            assert!(a.chars().all(|c| matches!(c, '\u{2060}' | '\u{200B}' | '\u{200C}')));
+            for b in &tags[i + 1..] {
+                let text = format!("Starts in 5 min: Standup{}", b);
+                assert!(!text.ends_with(a.as_str()), "{:?} matches {:?}", a, b);
+            }
+        }
+    }
+}
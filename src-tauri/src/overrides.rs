@@ -0,0 +1,277 @@
+use crate::calendar::CalendarEvent;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "overrides.json";
+const STORE_KEY: &str = "overrides";
+// Occurrence overrides are dropped once the occurrence started this long ago
+const PRUNE_AFTER_HOURS: i64 = 24;
+// Skipped series are dropped once no synced event belonged to them for this long
+const PRUNE_SERIES_AFTER_DAYS: i64 = 90;
+
+/// Per-event exceptions to the scheduler's normal behavior.
+///
+/// Occurrence overrides are keyed on `CalendarEvent.id`, which always ends in
+/// `_<start timestamp>` (`<eventIdentifier>_<start>` from EventKit,
+/// `<calendarId>#<uid>_<start>` from ICS and CalDAV). Series overrides are
+/// keyed on `CalendarEvent.series_id`, or the occurrence id for events
+/// without one.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventOverrides {
+    /// Occurrences that should not be auto-opened.
+    #[serde(default)]
+    pub skip_auto_open: HashSet<String>,
+    /// Occurrence id → time the reminder should fire again.
+    #[serde(default)]
+    pub snoozed_until: HashMap<String, DateTime<Utc>>,
+    /// Recurring series that should never be auto-opened.
+    #[serde(default)]
+    pub skipped_series: HashSet<String>,
+    /// Skipped series → when a synced event last belonged to it.
+    #[serde(default)]
+    pub series_seen: HashMap<String, DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OverrideAction {
+    /// Don't auto-open this occurrence.
+    Skip,
+    /// Re-send the reminder `minutes` from now.
+    Snooze { minutes: i64 },
+    /// Don't auto-open any occurrence of this recurring series.
+    SkipSeries,
+    /// Remove every override for this occurrence and its series.
+    Clear,
+}
+
+fn series_key(event: &CalendarEvent) -> &str {
+    event.series_id.as_deref().unwrap_or(&event.id)
+}
+
+fn occurrence_start(event_id: &str) -> Option<DateTime<Utc>> {
+    let (_, ts) = event_id.rsplit_once('_')?;
+    DateTime::from_timestamp(ts.parse().ok()?, 0)
+}
+
+impl EventOverrides {
+    /// `series_id` is the occurrence's `CalendarEvent.series_id`.
+    pub fn apply(
+        &mut self,
+        event_id: &str,
+        series_id: Option<&str>,
+        action: &OverrideAction,
+        now: DateTime<Utc>,
+    ) {
+        let series = series_id.unwrap_or(event_id);
+        match action {
+            OverrideAction::Skip => {
+                self.skip_auto_open.insert(event_id.to_string());
+            }
+            OverrideAction::Snooze { minutes } => {
+                self.snoozed_until
+                    .insert(event_id.to_string(), now + Duration::minutes((*minutes).max(1)));
+            }
+            OverrideAction::SkipSeries => {
+                self.skipped_series.insert(series.to_string());
+                self.series_seen.insert(series.to_string(), now);
+            }
+            OverrideAction::Clear => {
+                self.skip_auto_open.remove(event_id);
+                self.snoozed_until.remove(event_id);
+                self.skipped_series.remove(series);
+                self.series_seen.remove(series);
+            }
+        }
+    }
+
+    pub fn skips_auto_open(&self, event: &CalendarEvent) -> bool {
+        self.skip_auto_open.contains(&event.id) || self.skipped_series.contains(series_key(event))
+    }
+
+    pub fn snoozed_until(&self, event_id: &str) -> Option<DateTime<Utc>> {
+        self.snoozed_until.get(event_id).copied()
+    }
+
+    /// Drop occurrence overrides for meetings that are long over.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::hours(PRUNE_AFTER_HOURS);
+        let keep = |id: &String| occurrence_start(id).is_none_or(|start| start > cutoff);
+        self.skip_auto_open.retain(keep);
+        self.snoozed_until.retain(|id, _| keep(id));
+    }
+
+    /// Note which skipped series the synced `events` still belong to, and
+    /// drop the ones none did for `PRUNE_SERIES_AFTER_DAYS`. The synced window
+    /// is only days long, so a series isn't dropped the moment it leaves it.
+    /// Returns true if anything changed.
+    pub fn prune_series(&mut self, events: &[CalendarEvent], now: DateTime<Utc>) -> bool {
+        let before = self.clone();
+        for event in events {
+            let key = series_key(event);
+            if self.skipped_series.contains(key) {
+                let seen = self.series_seen.entry(key.to_string()).or_insert(now);
+                // Day granularity, so every sync doesn't rewrite the store
+                if now - *seen > Duration::days(1) {
+                    *seen = now;
+                }
+            }
+        }
+        for series in &self.skipped_series {
+            // Skipped before this was tracked: start counting now
+            self.series_seen.entry(series.clone()).or_insert(now);
+        }
+        let cutoff = now - Duration::days(PRUNE_SERIES_AFTER_DAYS);
+        let seen = &self.series_seen;
+        self.skipped_series.retain(|series| seen[series] > cutoff);
+        let skipped = &self.skipped_series;
+        self.series_seen.retain(|series, _| skipped.contains(series));
+        *self != before
+    }
+}
+
+pub fn load(app: &tauri::AppHandle) -> EventOverrides {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(STORE_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, overrides: &EventOverrides) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(
+        STORE_KEY,
+        serde_json::to_value(overrides).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Remove a snooze once its reminder has fired.
+pub fn consume_snooze(app: &tauri::AppHandle, event_id: &str) -> Result<(), String> {
+    let mut overrides = load(app);
+    if overrides.snoozed_until.remove(event_id).is_some() {
+        save(app, &overrides)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_event_overrides(app: tauri::AppHandle) -> EventOverrides {
+    load(&app)
+}
+
+/// Forget skipped series no synced event has belonged to in a long time;
+/// see `EventOverrides::prune_series`.
+pub fn prune_series(app: &tauri::AppHandle, events: &[CalendarEvent]) -> Result<(), String> {
+    let mut overrides = load(app);
+    if overrides.prune_series(events, Utc::now()) {
+        save(app, &overrides)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_event_override(
+    app: tauri::AppHandle,
+    event_id: String,
+    series_id: Option<String>,
+    action: OverrideAction,
+) -> Result<EventOverrides, String> {
+    let now = Utc::now();
+    let mut overrides = load(&app);
+    overrides.apply(&event_id, series_id.as_deref(), &action, now);
+    overrides.prune(now);
+    save(&app, &overrides)?;
+    crate::scheduler::wake(&app);
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn occurrence(id: &str, series_id: Option<&str>) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            series_id: series_id.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn skip_applies_to_single_occurrence() {
+        let mut o = EventOverrides::default();
+        o.apply("evt_100", Some("evt"), &OverrideAction::Skip, at("2024-05-01T00:00:00Z"));
+        assert!(o.skips_auto_open(&occurrence("evt_100", Some("evt"))));
+        assert!(!o.skips_auto_open(&occurrence("evt_200", Some("evt"))));
+    }
+
+    #[test]
+    fn skip_series_applies_to_every_occurrence() {
+        let now = at("2024-05-01T00:00:00Z");
+        let mut o = EventOverrides::default();
+        o.apply("ics:/a.ics#uid_100", Some("uid"), &OverrideAction::SkipSeries, now);
+        assert!(o.skips_auto_open(&occurrence("ics:/a.ics#uid_100", Some("uid"))));
+        assert!(o.skips_auto_open(&occurrence("ics:/a.ics#uid_200", Some("uid"))));
+        assert!(!o.skips_auto_open(&occurrence("ics:/a.ics#other_100", Some("other"))));
+
+        o.apply("ics:/a.ics#uid_300", Some("uid"), &OverrideAction::Clear, now);
+        assert!(!o.skips_auto_open(&occurrence("ics:/a.ics#uid_200", Some("uid"))));
+
+        // Without a series id only that occurrence is skipped
+        o.apply("single_100", None, &OverrideAction::SkipSeries, now);
+        assert!(o.skips_auto_open(&occurrence("single_100", None)));
+        assert!(!o.skips_auto_open(&occurrence("single_200", None)));
+    }
+
+    #[test]
+    fn snooze_sets_resume_time() {
+        let mut o = EventOverrides::default();
+        let now = at("2024-05-01T09:00:00Z");
+        o.apply("evt_100", None, &OverrideAction::Snooze { minutes: 5 }, now);
+        assert_eq!(o.snoozed_until("evt_100"), Some(at("2024-05-01T09:05:00Z")));
+    }
+
+    #[test]
+    fn prune_drops_old_occurrences_but_keeps_series() {
+        let mut o = EventOverrides::default();
+        let now = at("2024-05-03T00:00:00Z");
+        let old = format!("evt_{}", at("2024-05-01T09:00:00Z").timestamp());
+        let upcoming = format!("evt_{}", at("2024-05-03T09:00:00Z").timestamp());
+        o.apply(&old, Some("evt"), &OverrideAction::Skip, now);
+        o.apply(&upcoming, Some("evt"), &OverrideAction::Skip, now);
+        o.apply(&old, Some("evt"), &OverrideAction::SkipSeries, now);
+        o.prune(now);
+        assert!(!o.skip_auto_open.contains(&old));
+        assert!(o.skip_auto_open.contains(&upcoming));
+        assert!(o.skipped_series.contains("evt"));
+    }
+
+    #[test]
+    fn skipped_series_are_forgotten_once_long_gone() {
+        let now = at("2024-05-01T00:00:00Z");
+        let mut o = EventOverrides::default();
+        o.apply("weekly_100", Some("weekly"), &OverrideAction::SkipSeries, now);
+        o.apply("gone_100", Some("gone"), &OverrideAction::SkipSeries, now);
+        let synced = [occurrence("weekly_200", Some("weekly"))];
+
+        // Out of the synced window for a while is fine
+        assert!(!o.prune_series(&synced, now + Duration::hours(1)));
+        let later = now + Duration::days(60);
+        assert!(o.prune_series(&synced, later));
+        assert!(o.skipped_series.contains("gone"));
+
+        assert!(o.prune_series(&synced, later + Duration::days(40)));
+        assert!(o.skipped_series.contains("weekly"));
+        assert!(!o.skipped_series.contains("gone"));
+        assert!(!o.series_seen.contains_key("gone"));
+    }
+}
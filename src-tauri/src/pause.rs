@@ -0,0 +1,151 @@
+use crate::scheduler::next_local_midnight;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "pause.json";
+const STORE_KEY: &str = "pause";
+
+/// Tauri event emitted when auto-open is paused or resumed; payload `Option<Pause>`.
+pub const PAUSE_CHANGED: &str = "pause-changed";
+
+/// Auto-open paused from the tray menu. While paused the scheduler opens
+/// nothing; reminders keep coming if `pauseKeepsReminders` is on.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Pause {
+    Until { until: DateTime<Utc> },
+    /// Until the user resumes
+    Indefinite,
+}
+
+/// How long to pause for, as offered in the tray menu.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseLength {
+    OneHour,
+    UntilTomorrow,
+    UntilResumed,
+}
+
+impl Pause {
+    pub fn starting(length: PauseLength, now: DateTime<Utc>) -> Self {
+        match length {
+            PauseLength::OneHour => Self::Until { until: now + Duration::hours(1) },
+            PauseLength::UntilTomorrow => Self::Until { until: next_local_midnight(now) },
+            PauseLength::UntilResumed => Self::Indefinite,
+        }
+    }
+
+    pub fn ends_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Until { until } => Some(*until),
+            Self::Indefinite => None,
+        }
+    }
+
+    pub fn is_over(&self, now: DateTime<Utc>) -> bool {
+        self.ends_at().is_some_and(|until| until <= now)
+    }
+
+    /// Shown in the menu bar in front of the countdown.
+    pub fn tray_label(&self, now: DateTime<Utc>, is_ja: bool) -> String {
+        match self.ends_at() {
+            None => "⏸".to_string(),
+            Some(until) if until == next_local_midnight(now) => {
+                if is_ja { "⏸ 明日まで" } else { "⏸ until tomorrow" }.to_string()
+            }
+            Some(until) => {
+                let time = until.with_timezone(&Local).format("%H:%M");
+                if is_ja {
+                    format!("⏸ {}まで", time)
+                } else {
+                    format!("⏸ until {}", time)
+                }
+            }
+        }
+    }
+}
+
+fn load(app: &tauri::AppHandle) -> Option<Pause> {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(STORE_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// The pause in effect at `now`, without side effects.
+pub fn active(app: &tauri::AppHandle, now: DateTime<Utc>) -> Option<Pause> {
+    load(app).filter(|pause| !pause.is_over(now))
+}
+
+/// The pause in effect at `now`. A pause that has run out is cleared.
+pub fn current(app: &tauri::AppHandle, now: DateTime<Utc>) -> Option<Pause> {
+    let pause = load(app)?;
+    if !pause.is_over(now) {
+        return Some(pause);
+    }
+    log::info!("Auto-open pause ended");
+    if let Err(e) = set(app, None) {
+        log::warn!("Failed to clear pause: {}", e);
+    }
+    None
+}
+
+/// Pause (Some) or resume (None) auto-open, then update the scheduler, the
+/// tray menu and the frontend.
+pub fn set(app: &tauri::AppHandle, pause: Option<Pause>) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(STORE_KEY, serde_json::to_value(pause).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+    crate::scheduler::wake(app);
+    crate::tray_menu::refresh(app);
+    if let Err(e) = app.emit(PAUSE_CHANGED, pause) {
+        log::warn!("Failed to emit {}: {}", PAUSE_CHANGED, e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_pause(app: tauri::AppHandle) -> Option<Pause> {
+    active(&app, Utc::now())
+}
+
+#[tauri::command]
+pub fn pause_auto_open(app: tauri::AppHandle, length: PauseLength) -> Result<Pause, String> {
+    let pause = Pause::starting(length, Utc::now());
+    set(&app, Some(pause))?;
+    Ok(pause)
+}
+
+#[tauri::command]
+pub fn resume_auto_open(app: tauri::AppHandle) -> Result<(), String> {
+    set(&app, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn pauses_end_when_asked() {
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 13, 20, 0).unwrap().with_timezone(&Utc);
+        let hour = Pause::starting(PauseLength::OneHour, now);
+        assert!(!hour.is_over(now + Duration::minutes(59)));
+        assert!(hour.is_over(now + Duration::hours(1)));
+        assert_eq!(hour.tray_label(now, false), "⏸ until 14:20");
+        assert_eq!(hour.tray_label(now, true), "⏸ 14:20まで");
+
+        let tomorrow = Pause::starting(PauseLength::UntilTomorrow, now);
+        assert_eq!(tomorrow.tray_label(now, false), "⏸ until tomorrow");
+        let midnight = Local.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap().with_timezone(&Utc);
+        assert!(!tomorrow.is_over(midnight - Duration::seconds(1)));
+        assert!(tomorrow.is_over(midnight));
+
+        let resumed = Pause::starting(PauseLength::UntilResumed, now);
+        assert!(!resumed.is_over(now + Duration::days(365)));
+        assert_eq!(resumed.tray_label(now, false), "⏸");
+    }
+}
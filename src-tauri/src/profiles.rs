@@ -0,0 +1,257 @@
+use crate::rules::in_time_window;
+use crate::settings::{self, Settings};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A named set of the settings that differ between e.g. Work and Personal.
+///
+/// The active profile's values live in the regular top-level settings, so
+/// everything that reads settings follows the active profile. Switching
+/// saves the top-level values back into the profile being left first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Profile {
+    pub name: String,
+    pub enabled_calendars: Option<Vec<String>>,
+    pub minutes_before: i64,
+    pub notification_minutes_before: i64,
+    pub auto_open: bool,
+    pub open_with: HashMap<String, String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::capture(String::new(), &Settings::default())
+    }
+}
+
+impl Profile {
+    /// The profile values currently in `settings`.
+    pub fn capture(name: String, settings: &Settings) -> Self {
+        Self {
+            name,
+            enabled_calendars: settings.enabled_calendars.clone(),
+            minutes_before: settings.minutes_before,
+            notification_minutes_before: settings.notification_minutes_before,
+            auto_open: settings.auto_open,
+            open_with: settings.open_with.clone(),
+        }
+    }
+
+    fn apply_to(&self, settings: &mut Settings) {
+        settings.enabled_calendars = self.enabled_calendars.clone();
+        settings.minutes_before = self.minutes_before;
+        settings.notification_minutes_before = self.notification_minutes_before;
+        settings.auto_open = self.auto_open;
+        settings.open_with = self.open_with.clone();
+    }
+}
+
+/// One entry of the `profileSchedule` setting: use `profile` on `days`
+/// between `from` and `to` (local "HH:MM"; `to` is exclusive and may wrap
+/// past midnight). Missing days or times match always.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSchedule {
+    pub profile: String,
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+fn parse_clock(value: &Option<String>) -> Result<Option<NaiveTime>, chrono::ParseError> {
+    value.as_deref().map(|v| NaiveTime::parse_from_str(v, "%H:%M")).transpose()
+}
+
+impl ProfileSchedule {
+    fn matches(&self, now: DateTime<Local>) -> bool {
+        if !self.days.is_empty() && !self.days.contains(&now.weekday()) {
+            return false;
+        }
+        match (parse_clock(&self.from), parse_clock(&self.to)) {
+            (Ok(from), Ok(to)) => in_time_window(now.time(), from, to),
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn!("Skipping schedule entry for '{}': invalid time: {}", self.profile, e);
+                false
+            }
+        }
+    }
+}
+
+/// The profile the schedule asks for at `now`; the first matching entry wins.
+pub fn scheduled_profile(schedule: &[ProfileSchedule], now: DateTime<Local>) -> Option<&str> {
+    schedule
+        .iter()
+        .find(|entry| entry.matches(now))
+        .map(|entry| entry.profile.as_str())
+}
+
+/// The next time after `now` at which an entry of the schedule starts or
+/// ends, so the scheduler wakes up to switch. Day changes are covered by
+/// the scheduler's midnight wake-up.
+pub fn next_schedule_change(
+    schedule: &[ProfileSchedule],
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(&Local);
+    schedule
+        .iter()
+        .flat_map(|entry| [&entry.from, &entry.to])
+        .filter_map(|time| parse_clock(time).ok().flatten())
+        .filter_map(|time| {
+            let today = local.date_naive().and_time(time);
+            let next = if today > local.naive_local() {
+                today
+            } else {
+                today + Duration::days(1)
+            };
+            Local.from_local_datetime(&next).earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .min()
+}
+
+/// `settings` with `name` made the active profile.
+pub fn switched(settings: &Settings, name: &str) -> Result<Settings, String> {
+    let mut next = settings.clone();
+    if let Some(active) = &settings.active_profile {
+        if let Some(profile) = next.profiles.iter_mut().find(|p| &p.name == active) {
+            *profile = Profile::capture(active.clone(), settings);
+        }
+    }
+    let target = next
+        .profiles
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| format!("No profile named '{}'", name))?;
+    target.apply_to(&mut next);
+    next.active_profile = Some(target.name);
+    Ok(next)
+}
+
+/// `settings` with the current values saved as profile `name` (replacing a
+/// profile of that name), which becomes the active one.
+pub fn saved_as(settings: &Settings, name: &str) -> Result<Settings, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    let mut next = settings.clone();
+    let profile = Profile::capture(name.to_string(), settings);
+    match next.profiles.iter_mut().find(|p| p.name == name) {
+        Some(existing) => *existing = profile,
+        None => next.profiles.push(profile),
+    }
+    next.active_profile = Some(name.to_string());
+    Ok(next)
+}
+
+/// `settings` without profile `name`. The current values stay as they are.
+pub fn deleted(settings: &Settings, name: &str) -> Settings {
+    let mut next = settings.clone();
+    next.profiles.retain(|p| p.name != name);
+    if next.active_profile.as_deref() == Some(name) {
+        next.active_profile = None;
+    }
+    next
+}
+
+/// Make `name` the active profile, e.g. from the tray menu.
+pub fn switch(app: &tauri::AppHandle, name: &str) -> Result<Settings, String> {
+    let next = switched(&settings::get(app), name)?;
+    settings::replace(app, next.clone())?;
+    log::info!("Switched to profile '{}'", name);
+    Ok(next)
+}
+
+/// Switch profiles when the `profileSchedule` moves on to another profile.
+///
+/// `last` is the profile the schedule asked for on the previous pass. Only
+/// changes are acted on, so a profile picked by hand stays until the
+/// schedule's next boundary.
+pub fn follow_schedule(app: &tauri::AppHandle, last: &mut Option<String>) {
+    let settings = settings::get(app);
+    let scheduled = scheduled_profile(&settings.profile_schedule, Local::now()).map(String::from);
+    if scheduled == *last {
+        return;
+    }
+    *last = scheduled.clone();
+    if let Some(name) = scheduled {
+        if settings.active_profile.as_deref() != Some(name.as_str()) {
+            if let Err(e) = switch(app, &name) {
+                log::warn!("Failed to switch to scheduled profile: {}", e);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn switch_profile(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    switch(&app, &name)
+}
+
+/// Save the current settings as profile `name` and make it active.
+#[tauri::command]
+pub fn save_profile(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    let next = saved_as(&settings::get(&app), &name)?;
+    settings::replace(&app, next.clone())?;
+    Ok(next)
+}
+
+#[tauri::command]
+pub fn delete_profile(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    let next = deleted(&settings::get(&app), &name);
+    settings::replace(&app, next.clone())?;
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_keeps_edits_to_the_profile_being_left() {
+        let work = saved_as(&Settings::default(), "Work").unwrap();
+        let mut personal = saved_as(&work, "Personal").unwrap();
+        personal.auto_open = false;
+        personal.enabled_calendars = Some(vec!["home".to_string()]);
+
+        let mut back_at_work = switched(&personal, "Work").unwrap();
+        assert_eq!(back_at_work.active_profile.as_deref(), Some("Work"));
+        assert!(back_at_work.auto_open);
+        assert_eq!(back_at_work.enabled_calendars, None);
+        back_at_work.minutes_before = 3;
+
+        let again = switched(&back_at_work, "Personal").unwrap();
+        assert!(!again.auto_open);
+        assert_eq!(again.enabled_calendars, Some(vec!["home".to_string()]));
+        assert_eq!(again.profiles.iter().find(|p| p.name == "Work").unwrap().minutes_before, 3);
+
+        assert!(switched(&again, "Gym").is_err());
+        let without = deleted(&again, "Personal");
+        assert_eq!(without.active_profile, None);
+        assert_eq!(without.profiles.len(), 1);
+    }
+
+    #[test]
+    fn schedule_picks_first_matching_entry() {
+        let schedule: Vec<ProfileSchedule> = serde_json::from_value(serde_json::json!([
+            { "profile": "Personal", "days": ["sat", "sun"] },
+            { "profile": "Work", "from": "09:00", "to": "18:00" },
+            { "profile": "Personal" },
+        ]))
+        .unwrap();
+        let at = |d: u32, h: u32| Local.with_ymd_and_hms(2024, 5, d, h, 0, 0).unwrap();
+        // 2024-05-01 is a Wednesday, 2024-05-04 a Saturday
+        assert_eq!(scheduled_profile(&schedule, at(1, 10)), Some("Work"));
+        assert_eq!(scheduled_profile(&schedule, at(1, 19)), Some("Personal"));
+        assert_eq!(scheduled_profile(&schedule, at(4, 10)), Some("Personal"));
+        assert_eq!(scheduled_profile(&schedule[1..2], at(1, 8)), None);
+
+        let now = at(1, 10).with_timezone(&Utc);
+        assert_eq!(next_schedule_change(&schedule, now), Some(at(1, 18).with_timezone(&Utc)));
+    }
+}
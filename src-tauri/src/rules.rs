@@ -0,0 +1,343 @@
+use crate::calendar::CalendarEvent;
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleAction {
+    /// Auto-open the meeting URL (the default behavior).
+    Open,
+    /// Send a notification at the lead time but don't open anything.
+    NotifyOnly,
+    /// No reminder, no auto-open.
+    Ignore,
+}
+
+/// Conditions a rule matches on. Every condition that is set must hold;
+/// an empty match applies to every event.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleMatch {
+    /// Any of these calendar ids.
+    #[serde(default)]
+    pub calendar_ids: Vec<String>,
+    /// Calendar account (source) name, case-insensitive.
+    pub account_name: Option<String>,
+    /// Case-insensitive regex against the event title.
+    pub title_regex: Option<String>,
+    /// Case-insensitive regex against the event notes.
+    pub description_regex: Option<String>,
+    /// Any of these service keys (see `meeting_url::MeetingLink::service`).
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Match only tentative (true) or only non-tentative (false) events.
+    pub tentative: Option<bool>,
+    /// Local start time window "HH:MM"; `to` is exclusive and may wrap past midnight.
+    pub start_time_from: Option<String>,
+    pub start_time_to: Option<String>,
+    /// Attendee count range, inclusive; e.g. at most 2 for 1:1s. Events
+    /// without invitees have 0 attendees.
+    pub min_attendees: Option<usize>,
+    pub max_attendees: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, rename = "match")]
+    pub conditions: RuleMatch,
+    pub action: RuleAction,
+    /// Lead time for this rule; falls back to the global `minutesBefore`.
+    pub minutes_before: Option<i64>,
+    /// App path to open with; falls back to the `openWith` map.
+    pub open_with: Option<String>,
+}
+
+/// What the scheduler should do with one event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub action: RuleAction,
+    pub minutes_before: i64,
+    pub open_with: Option<String>,
+}
+
+struct CompiledRule {
+    rule: Rule,
+    title: Option<Regex>,
+    description: Option<Regex>,
+    from: Option<NaiveTime>,
+    to: Option<NaiveTime>,
+}
+
+/// Ordered auto-open rules; the first matching rule wins.
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+fn compile_regex(pattern: &Option<String>, rule: &str) -> Result<Option<Regex>, ()> {
+    match pattern {
+        None => Ok(None),
+        Some(p) => RegexBuilder::new(p)
+            .case_insensitive(true)
+            .build()
+            .map(Some)
+            .map_err(|e| log::warn!("Skipping rule '{}': invalid regex '{}': {}", rule, p, e)),
+    }
+}
+
+fn parse_clock(value: &Option<String>, rule: &str) -> Result<Option<NaiveTime>, ()> {
+    match value {
+        None => Ok(None),
+        Some(v) => NaiveTime::parse_from_str(v, "%H:%M")
+            .map(Some)
+            .map_err(|e| log::warn!("Skipping rule '{}': invalid time '{}': {}", rule, v, e)),
+    }
+}
+
+impl RuleSet {
+    /// Rules that fail to compile are logged and dropped.
+    pub fn new(rules: Vec<Rule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let c = &rule.conditions;
+                Some(CompiledRule {
+                    title: compile_regex(&c.title_regex, &rule.name).ok()?,
+                    description: compile_regex(&c.description_regex, &rule.name).ok()?,
+                    from: parse_clock(&c.start_time_from, &rule.name).ok()?,
+                    to: parse_clock(&c.start_time_to, &rule.name).ok()?,
+                    rule,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        Self::new(crate::settings::get(app).rules)
+    }
+
+    /// Decide what to do with `event`. `service` is the detected meeting
+    /// service, if any; `default_minutes_before` is the global lead time.
+    pub fn evaluate(
+        &self,
+        event: &CalendarEvent,
+        start: DateTime<Utc>,
+        service: Option<&str>,
+        default_minutes_before: i64,
+    ) -> Decision {
+        let matched = self
+            .rules
+            .iter()
+            .find(|r| r.matches(event, start, service));
+        match matched {
+            Some(r) => Decision {
+                action: r.rule.action,
+                minutes_before: r.rule.minutes_before.unwrap_or(default_minutes_before),
+                open_with: r.rule.open_with.clone().filter(|s| s != "default"),
+            },
+            None => Decision {
+                action: RuleAction::Open,
+                minutes_before: default_minutes_before,
+                open_with: None,
+            },
+        }
+    }
+}
+
+impl CompiledRule {
+    fn matches(&self, event: &CalendarEvent, start: DateTime<Utc>, service: Option<&str>) -> bool {
+        let c = &self.rule.conditions;
+
+        if !c.calendar_ids.is_empty()
+            && !event
+                .calendar_id
+                .as_ref()
+                .is_some_and(|id| c.calendar_ids.contains(id))
+        {
+            return false;
+        }
+        if let Some(account) = &c.account_name {
+            if !event
+                .calendar_account_name
+                .as_ref()
+                .is_some_and(|a| a.eq_ignore_ascii_case(account))
+            {
+                return false;
+            }
+        }
+        if let Some(re) = &self.title {
+            if !re.is_match(&event.summary) {
+                return false;
+            }
+        }
+        if let Some(re) = &self.description {
+            if !event.description.as_deref().is_some_and(|d| re.is_match(d)) {
+                return false;
+            }
+        }
+        if !c.services.is_empty() && !service.is_some_and(|s| c.services.iter().any(|x| x == s)) {
+            return false;
+        }
+        if let Some(tentative) = c.tentative {
+            if (event.status.as_deref() == Some("tentative")) != tentative {
+                return false;
+            }
+        }
+        let attendees = event.attendees.len();
+        if c.min_attendees.is_some_and(|min| attendees < min)
+            || c.max_attendees.is_some_and(|max| attendees > max)
+        {
+            return false;
+        }
+        if !in_time_window(start.with_timezone(&Local).time(), self.from, self.to) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Whether `t` is in [from, to); `to` may wrap past midnight. A missing end
+/// of the window is open.
+pub fn in_time_window(t: NaiveTime, from: Option<NaiveTime>, to: Option<NaiveTime>) -> bool {
+    let start = from.unwrap_or(NaiveTime::MIN);
+    match to {
+        Some(to) if to <= start => t >= start || t < to, // wraps past midnight
+        Some(to) => t >= start && t < to,
+        None => t >= start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{Participant, ParticipantRole, ParticipationStatus};
+    use chrono::TimeZone;
+
+    fn make_event(summary: &str, account: &str, status: Option<&str>) -> CalendarEvent {
+        CalendarEvent {
+            id: "test".to_string(),
+            summary: summary.to_string(),
+            status: status.map(String::from),
+            calendar_id: Some("cal-1".to_string()),
+            calendar_account_name: Some(account.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn rules(json: serde_json::Value) -> RuleSet {
+        RuleSet::new(serde_json::from_value(json).unwrap())
+    }
+
+    fn local_start(h: u32, m: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(2024, 5, 1, h, m, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn no_rules_means_open_with_default_lead_time() {
+        let set = RuleSet::new(Vec::new());
+        let event = make_event("Sync", "work@example.com", None);
+        assert_eq!(
+            set.evaluate(&event, local_start(10, 0), Some("zoom"), 1),
+            Decision { action: RuleAction::Open, minutes_before: 1, open_with: None }
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let set = rules(serde_json::json!([
+            { "name": "focus", "match": { "titleRegex": "^focus" }, "action": "ignore" },
+            {
+                "name": "1:1s",
+                "match": { "titleRegex": "1:1", "accountName": "WORK@example.com", "services": ["zoom"] },
+                "action": "open",
+                "minutesBefore": 2,
+                "openWith": "/Applications/zoom.us.app"
+            },
+            { "match": {}, "action": "notifyOnly" }
+        ]));
+
+        let focus = make_event("Focus time", "work@example.com", None);
+        assert_eq!(set.evaluate(&focus, local_start(10, 0), None, 1).action, RuleAction::Ignore);
+
+        let one_on_one = make_event("Alice / Bob 1:1", "work@example.com", None);
+        assert_eq!(
+            set.evaluate(&one_on_one, local_start(10, 0), Some("zoom"), 1),
+            Decision {
+                action: RuleAction::Open,
+                minutes_before: 2,
+                open_with: Some("/Applications/zoom.us.app".to_string()),
+            }
+        );
+        // Same title on Meet falls through to the catch-all
+        assert_eq!(
+            set.evaluate(&one_on_one, local_start(10, 0), Some("googleMeet"), 1).action,
+            RuleAction::NotifyOnly
+        );
+    }
+
+    #[test]
+    fn matches_tentative_and_calendar() {
+        let set = rules(serde_json::json!([
+            { "match": { "tentative": true, "calendarIds": ["cal-1"] }, "action": "notifyOnly" }
+        ]));
+        let tentative = make_event("Maybe", "a", Some("tentative"));
+        let confirmed = make_event("Sure", "a", Some("confirmed"));
+        assert_eq!(set.evaluate(&tentative, local_start(10, 0), None, 1).action, RuleAction::NotifyOnly);
+        assert_eq!(set.evaluate(&confirmed, local_start(10, 0), None, 1).action, RuleAction::Open);
+    }
+
+    #[test]
+    fn matches_attendee_count() {
+        let set = rules(serde_json::json!([
+            { "name": "1:1s", "match": { "minAttendees": 2, "maxAttendees": 2 }, "action": "open" },
+            { "name": "all-hands", "match": { "minAttendees": 20 }, "action": "notifyOnly" },
+            { "match": {}, "action": "ignore" }
+        ]));
+        let with_attendees = |n: usize| {
+            let mut event = make_event("Meeting", "a", None);
+            event.attendees = (0..n)
+                .map(|i| Participant {
+                    name: None,
+                    email: Some(format!("p{}@example.com", i)),
+                    role: ParticipantRole::Required,
+                    status: ParticipationStatus::Accepted,
+                    is_self: i == 0,
+                })
+                .collect();
+            event
+        };
+        let action = |n| set.evaluate(&with_attendees(n), local_start(10, 0), None, 1).action;
+        assert_eq!(action(2), RuleAction::Open);
+        assert_eq!(action(30), RuleAction::NotifyOnly);
+        assert_eq!(action(3), RuleAction::Ignore);
+        assert_eq!(action(0), RuleAction::Ignore);
+    }
+
+    #[test]
+    fn time_window_can_wrap_midnight() {
+        let set = rules(serde_json::json!([
+            { "match": { "startTimeFrom": "18:00", "startTimeTo": "08:00" }, "action": "ignore" }
+        ]));
+        let event = make_event("Late call", "a", None);
+        assert_eq!(set.evaluate(&event, local_start(19, 30), None, 1).action, RuleAction::Ignore);
+        assert_eq!(set.evaluate(&event, local_start(7, 0), None, 1).action, RuleAction::Ignore);
+        assert_eq!(set.evaluate(&event, local_start(12, 0), None, 1).action, RuleAction::Open);
+    }
+
+    #[test]
+    fn invalid_rules_are_dropped() {
+        let set = rules(serde_json::json!([
+            { "match": { "titleRegex": "(" }, "action": "ignore" },
+            { "match": { "startTimeFrom": "25:99" }, "action": "ignore" }
+        ]));
+        let event = make_event("Anything", "a", None);
+        assert_eq!(set.evaluate(&event, local_start(10, 0), None, 1).action, RuleAction::Open);
+    }
+}
@@ -1,86 +1,393 @@
-use crate::calendar::{has_permission, sync_events, CalendarState};
-use crate::meeting_url::{detect_meeting_service, extract_meeting_url};
-use chrono::{DateTime, Utc};
-use std::collections::HashSet;
+use crate::calendar::{
+    check_permission, emit_events_updated, enabled_events, refresh_sources, sync_events,
+    CalendarEvent, CalendarState, InvitationFilter,
+};
+use crate::conflicts::{self, Conflict, ConflictSettings};
+use crate::journal::{self, Journal};
+use crate::meeting_url;
+use crate::notification_actions::{self, NotificationAction};
+use crate::overrides::{self, OverrideAction};
+use crate::pause;
+use crate::profiles;
+use crate::rules::{RuleAction, RuleSet};
+use crate::tray_menu;
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
 use std::sync::Mutex;
 use std::time::Duration;
-use tauri::Manager;
-use tauri_plugin_store::StoreExt;
+use tokio::sync::Notify;
+use tauri::{Emitter, Manager};
 
 const POLL_INTERVAL_SECS: u64 = 5 * 60; // 5 minutes
-const CHECK_INTERVAL_SECS: u64 = 10; // Reduced from 30 for more responsive tray updates
-const DEFAULT_MINUTES_BEFORE: i64 = 1;
-const DEFAULT_NOTIFY_MINUTES_BEFORE: i64 = 5;
-const DEFAULT_TRAY_COUNTDOWN_MINUTES: i64 = 30;
+// Monotonic timers pause while the Mac sleeps, so never sleep longer than this
+// or a wall-clock deadline could be missed after wake
+const MAX_SLEEP_SECS: u64 = 60;
+// Retry delay when permission is missing or a sync failed
+const RETRY_SECS: u64 = 10;
+// Wall clock may drift this far from the monotonic clock between ticks before we call it a jump
+const CLOCK_JUMP_TOLERANCE_SECS: i64 = 30;
+// A tick arriving this much later than planned means the process was suspended (App Nap)
+const MAX_TICK_LATE_SECS: i64 = 60;
+
+/// Tauri event emitted after a meeting was auto-opened; payload `MeetingOpened`.
+pub const MEETING_OPENED: &str = "meeting-opened";
+/// Tauri event emitted after a reminder notification was sent; payload `ReminderSent`.
+pub const REMINDER_SENT: &str = "reminder-sent";
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingOpened {
+    pub event_id: String,
+    pub summary: String,
+    pub url: String,
+    pub service: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReminderKind {
+    /// Regular or notify-only reminder before the meeting starts
+    Upcoming,
+    /// A snoozed reminder firing
+    Snoozed,
+    /// Offer to join a meeting that started while the Mac was asleep
+    JoinNow,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderSent {
+    pub event_id: String,
+    pub summary: String,
+    pub kind: ReminderKind,
+    /// Negative once the meeting has started
+    pub minutes_until: i64,
+}
 
 /// Check if the current system locale is Japanese
-fn is_japanese_locale() -> bool {
+pub(crate) fn is_japanese_locale() -> bool {
     sys_locale::get_locale()
         .map(|l| l.starts_with("ja"))
         .unwrap_or(false)
 }
 
+/// Wakes the scheduler before its next deadline, e.g. after a setting changed
+/// or a notification button was pressed.
+#[derive(Default)]
+pub struct SchedulerWake {
+    notify: Notify,
+    /// Notification buttons pressed since the last pass, by event id
+    actions: Mutex<Vec<(String, NotificationAction)>>,
+}
+
+impl SchedulerWake {
+    fn notified(&self) -> tokio::sync::futures::Notified<'_> {
+        self.notify.notified()
+    }
+
+    fn take_actions(&self) -> Vec<(String, NotificationAction)> {
+        std::mem::take(&mut *self.actions.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+pub fn wake(app: &tauri::AppHandle) {
+    if let Some(wake) = app.try_state::<SchedulerWake>() {
+        wake.inner().notify.notify_one();
+    }
+}
+
+/// Hand a notification button press to the scheduler, which carries it out
+/// on its next pass.
+pub fn queue_action(app: &tauri::AppHandle, event_id: &str, action: NotificationAction) {
+    if let Some(wake) = app.try_state::<SchedulerWake>() {
+        let wake = wake.inner();
+        wake.actions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((event_id.to_string(), action));
+        wake.notify.notify_one();
+    }
+}
+
 struct SchedulerState {
-    opened_meetings: Mutex<HashSet<String>>,
-    notified_meetings: Mutex<HashSet<String>>,
+    /// Opened/notified meetings, mirrored to disk on every change
+    journal: Mutex<Journal>,
     last_poll: Mutex<std::time::Instant>,
+    /// Conflicts the user was asked about and hasn't answered. Their meetings
+    /// stay closed meanwhile; kept in memory only, so a restart asks again.
+    asked: Mutex<Vec<Conflict>>,
+}
+
+impl SchedulerState {
+    fn was_opened(&self, event_id: &str) -> bool {
+        self.journal.lock().unwrap_or_else(|e| e.into_inner()).was_opened(event_id)
+    }
+
+    fn was_notified(&self, event_id: &str) -> bool {
+        self.journal.lock().unwrap_or_else(|e| e.into_inner()).was_notified(event_id)
+    }
+
+    fn mark_opened(&self, app: &tauri::AppHandle, event_id: &str, end: DateTime<Utc>) {
+        let mut journal = self.journal.lock().unwrap_or_else(|e| e.into_inner());
+        if journal.mark_opened(event_id, end) {
+            save_journal(app, &journal);
+        }
+    }
+
+    fn mark_notified(&self, app: &tauri::AppHandle, event_id: &str, end: DateTime<Utc>) {
+        let mut journal = self.journal.lock().unwrap_or_else(|e| e.into_inner());
+        if journal.mark_notified(event_id, end) {
+            save_journal(app, &journal);
+        }
+    }
+
+    fn was_asked(&self, event_id: &str) -> bool {
+        let asked = self.asked.lock().unwrap_or_else(|e| e.into_inner());
+        asked.iter().any(|c| c.contains(event_id))
+    }
+
+    fn mark_asked(&self, conflict: &Conflict) {
+        self.asked.lock().unwrap_or_else(|e| e.into_inner()).push(conflict.clone());
+    }
+
+    /// The user chose `event_id`: the conflicts they were asked about with it
+    /// are settled, and the meetings passed over stay closed.
+    fn answer(&self, app: &tauri::AppHandle, event_id: &str, events: &[CalendarEvent]) {
+        let settled: Vec<Conflict> = {
+            let mut asked = self.asked.lock().unwrap_or_else(|e| e.into_inner());
+            let (settled, open) = std::mem::take(&mut *asked)
+                .into_iter()
+                .partition(|c| c.contains(event_id));
+            *asked = open;
+            settled
+        };
+        for conflict in settled {
+            for event in events.iter().filter(|e| e.id != event_id && conflict.contains(&e.id)) {
+                self.mark_opened(app, &event.id, event_end(event));
+            }
+        }
+    }
+
+    fn prune(&self, app: &tauri::AppHandle, now: DateTime<Utc>) {
+        self.asked
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|c| c.end > now);
+        let mut journal = self.journal.lock().unwrap_or_else(|e| e.into_inner());
+        if journal.prune(now) {
+            save_journal(app, &journal);
+        }
+    }
+}
+
+fn save_journal(app: &tauri::AppHandle, journal: &Journal) {
+    if let Err(e) = journal::save(app, journal) {
+        log::warn!("Failed to save scheduler journal: {}", e);
+    }
+}
+
+/// Wall clock, monotonic clock and UTC offset as seen by one scheduler tick.
+struct TickClock {
+    wall: DateTime<Utc>,
+    mono: std::time::Instant,
+    offset_secs: i32,
+}
+
+impl TickClock {
+    fn now() -> Self {
+        Self {
+            wall: Utc::now(),
+            mono: std::time::Instant::now(),
+            offset_secs: Local::now().offset().local_minus_utc(),
+        }
+    }
+}
+
+/// Why the cached events and timers can't be trusted on this tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Disruption {
+    /// The wall clock moved differently from the monotonic clock, e.g. the Mac
+    /// slept (the monotonic clock stops) or the time was set manually.
+    ClockJump,
+    /// The tick came much later than scheduled.
+    Gap,
+    /// The system timezone changed, so "today" means something else now.
+    TimezoneChange,
+}
+
+/// `planned` is how long the scheduler meant to sleep between the two ticks.
+fn detect_disruption(prev: &TickClock, now: &TickClock, planned: Duration) -> Option<Disruption> {
+    let mono_secs = now.mono.saturating_duration_since(prev.mono).as_secs() as i64;
+    let wall_secs = (now.wall - prev.wall).num_seconds();
+    if now.offset_secs != prev.offset_secs {
+        Some(Disruption::TimezoneChange)
+    } else if (wall_secs - mono_secs).abs() > CLOCK_JUMP_TOLERANCE_SECS {
+        Some(Disruption::ClockJump)
+    } else if mono_secs > planned.as_secs() as i64 + MAX_TICK_LATE_SECS {
+        Some(Disruption::Gap)
+    } else {
+        None
+    }
+}
+
+/// When the tray countdown for a meeting starting at `start` next changes: when it
+/// enters the `threshold_minutes` window (0 = always shown), then every minute.
+fn next_tray_change(
+    start: DateTime<Utc>,
+    threshold_minutes: i64,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let seconds_until = (start - now).num_seconds().max(0);
+    let mins = (seconds_until + 59) / 60;
+    if threshold_minutes != 0 && mins > threshold_minutes {
+        start - chrono::Duration::minutes(threshold_minutes)
+    } else {
+        start - chrono::Duration::minutes((seconds_until - 1).max(0) / 60)
+    }
+}
+
+/// Start of the next local day, when "today's" events need refetching.
+pub(crate) fn next_local_midnight(now: DateTime<Utc>) -> DateTime<Utc> {
+    let tomorrow: NaiveDate = now.with_timezone(&Local).date_naive() + Days::new(1);
+    let midnight = tomorrow.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        // Midnight skipped by a DST change: the day starts an hour later
+        .or_else(|| Local.from_local_datetime(&(midnight + chrono::Duration::hours(1))).earliest())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or(now + chrono::Duration::hours(1))
+}
+
+/// How long to sleep until `deadline`, capped at `MAX_SLEEP_SECS`.
+fn sleep_until(deadline: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Duration {
+    let max = Duration::from_secs(MAX_SLEEP_SECS);
+    deadline
+        .and_then(|d| (d - now).to_std().ok())
+        .map_or(max, |d| d.min(max))
 }
 
 pub async fn run_scheduler(app: tauri::AppHandle) {
+    // Reload what was opened/notified before a restart so nothing fires twice
     let state = SchedulerState {
-        opened_meetings: Mutex::new(HashSet::new()),
-        notified_meetings: Mutex::new(HashSet::new()),
+        journal: Mutex::new(journal::load(&app)),
         last_poll: Mutex::new(std::time::Instant::now() - Duration::from_secs(POLL_INTERVAL_SECS)),
+        asked: Mutex::new(Vec::new()),
     };
 
+    let wake = app.state::<SchedulerWake>();
+    let calendar_state = app.state::<CalendarState>();
+    let mut last_poll_day = Local::now().date_naive();
+    let mut sleep_for = Duration::from_secs(RETRY_SECS);
+    // Profile the `profileSchedule` asked for on the last pass. Seeded with the
+    // current slot so a restart doesn't undo a manual switch made inside it.
+    let mut scheduled_profile = profiles::scheduled_profile(
+        &crate::settings::get(&app).profile_schedule,
+        Local::now(),
+    )
+    .map(String::from);
+
+    // Each pass handles whatever is due, then sleeps until the next reminder,
+    // auto-open, tray label change, poll or day rollover. Syncs that change the
+    // events and setting changes wake it early.
     loop {
-        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        // Taken after the pass so only the sleep is measured, not a slow sync
+        let slept_at = TickClock::now();
+        tokio::select! {
+            _ = tokio::time::sleep(sleep_for) => {}
+            _ = wake.notified() => log::debug!("Scheduler woken: settings or action"),
+            _ = calendar_state.updated.notified() => log::debug!("Scheduler woken: events updated"),
+        }
 
-        let calendar_state = app.state::<CalendarState>();
+        let disruption = detect_disruption(&slept_at, &TickClock::now(), sleep_for);
+        if let Some(reason) = disruption {
+            log::info!("Scheduler disrupted ({:?}), forcing calendar sync", reason);
+        }
+        sleep_for = Duration::from_secs(RETRY_SECS);
+        notification_actions::expire(&app, Utc::now());
 
         // Check if we have calendar permission
-        if !has_permission(&calendar_state) {
+        if !check_permission(&app, &calendar_state) {
             continue;
         }
 
-        // Poll calendar if enough time has passed.
+        profiles::follow_schedule(&app, &mut scheduled_profile);
+        let settings = crate::settings::get(&app);
+        let window_changed = calendar_state.set_window_days(settings.scheduler_window_days);
+
+        // Poll calendar if enough time has passed, the day rolled over, the window
+        // changed, or right away after sleep/clock changes.
         // Recover from Mutex poisoning so a panic in one branch doesn't kill the scheduler.
-        let should_poll = {
+        let today = Local::now().date_naive();
+        let should_poll = disruption.is_some() || window_changed || today != last_poll_day || {
             let last = state.last_poll.lock().unwrap_or_else(|e| e.into_inner());
             last.elapsed() >= Duration::from_secs(POLL_INTERVAL_SECS)
         };
 
         if should_poll {
-            if let Err(e) = sync_events(&calendar_state) {
-                log::error!("Calendar sync failed: {}", e);
-                continue;
+            refresh_sources(&calendar_state);
+            match sync_events(&calendar_state) {
+                Ok(diff) => {
+                    emit_events_updated(&app, &diff);
+                    let synced = calendar_state.events.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Err(e) = overrides::prune_series(&app, &synced) {
+                        log::warn!("Failed to prune skipped series: {}", e);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Calendar sync failed: {}", e);
+                    continue;
+                }
             }
             *state.last_poll.lock().unwrap_or_else(|e| e.into_inner()) =
                 std::time::Instant::now();
+            last_poll_day = today;
         }
 
-        // Read minutes_before setting from store
-        let minutes_before = app
-            .store("settings.json")
-            .ok()
-            .and_then(|store| store.get("minutesBefore"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(DEFAULT_MINUTES_BEFORE);
-
-        let notify_minutes_before = app
-            .store("settings.json")
-            .ok()
-            .and_then(|store| store.get("notificationMinutesBefore"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(DEFAULT_NOTIFY_MINUTES_BEFORE);
-
-        // Check for upcoming meetings
-        let events = calendar_state
-            .events
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .clone();
+        // Check for upcoming meetings, skipping calendars disabled in the filter
+        // so auto-open and the tray countdown match what the window shows.
+        // Invitations the user declined (or chose to skip) are left alone entirely.
+        let invitations = InvitationFilter::load(&app);
+        let events: Vec<CalendarEvent> = enabled_events(&app, &calendar_state)
+            .into_iter()
+            .filter(|e| !invitations.skips(e))
+            .collect();
+        handle_notification_actions(&app, &state, wake.take_actions(), &events);
+        let event_overrides = overrides::load(&app);
+        let rules = RuleSet::load(&app);
         let now = Utc::now();
+        let paused = pause::current(&app, now);
+        // With auto-open off, meetings are announced at their lead time instead.
+        // A pause does the same, or silences them entirely without reminders.
+        let decide = |event: &CalendarEvent, start: DateTime<Utc>, service: Option<&str>| {
+            let mut decision = rules.evaluate(event, start, service, settings.minutes_before);
+            if paused.is_some() && !settings.pause_keeps_reminders {
+                decision.action = RuleAction::Ignore;
+            } else if (paused.is_some() || !settings.auto_open)
+                && decision.action == RuleAction::Open
+            {
+                decision.action = RuleAction::NotifyOnly;
+            }
+            decision
+        };
+
+        // Meetings still waiting to auto-open at overlapping times; the conflict
+        // policy decides which one (if any) actually opens.
+        let auto_open: Vec<&CalendarEvent> = events
+            .iter()
+            .filter(|e| e.meeting.is_some() && !event_overrides.skips_auto_open(e))
+            .filter(|e| awaiting_open(e, state.was_opened(&e.id), now))
+            .filter(|e| {
+                parse_event_time(&e.start.date_time).is_some_and(|start| {
+                    let service = e.meeting.as_ref().map(|m| m.service.as_str());
+                    decide(e, start, service).action == RuleAction::Open
+                })
+            })
+            .collect();
+        let conflict_settings = ConflictSettings::load(&app);
+
+        // Upcoming times at which this loop has something to do
+        let mut deadlines: Vec<DateTime<Utc>> = Vec::new();
 
         for event in &events {
             let start_time = match parse_event_time(&event.start.date_time) {
@@ -90,43 +397,106 @@ pub async fn run_scheduler(app: tauri::AppHandle) {
 
             let minutes_until = (start_time - now).num_minutes();
             let seconds_until = (start_time - now).num_seconds();
+            let end_time = parse_event_time(&event.end.date_time).unwrap_or(start_time);
 
-            // Reminder notification (independent of URL auto-open)
-            if notify_minutes_before > 0
-                && seconds_until <= (notify_minutes_before * 60)
+            let meeting_url = event.meeting.as_ref().map(|m| m.url.clone());
+            let service = event.meeting.as_ref().map(|m| m.service.as_str());
+            let decision = decide(event, start_time, service);
+            if decision.action == RuleAction::Ignore {
+                continue;
+            }
+
+            let snoozed_until = event_overrides.snoozed_until(&event.id);
+            deadlines.extend(snoozed_until);
+            if settings.notification_minutes_before > 0 && !state.was_notified(&event.id) {
+                deadlines.push(
+                    start_time
+                        - chrono::Duration::minutes(settings.notification_minutes_before),
+                );
+            }
+            let open_pending = match decision.action {
+                RuleAction::NotifyOnly => !state.was_notified(&event.id),
+                _ => meeting_url.is_some() && !state.was_opened(&event.id),
+            };
+            if open_pending && !event_overrides.skips_auto_open(event) {
+                deadlines.push(start_time - chrono::Duration::minutes(decision.minutes_before));
+            }
+
+            // A snoozed reminder replaces the regular one and fires once the
+            // snooze ends, even if the regular reminder was already sent.
+            if let Some(until) = snoozed_until {
+                let ended = parse_event_time(&event.end.date_time).is_some_and(|end| end <= now);
+                if now >= until && !ended {
+                    let mins_until_display = ((seconds_until + 59) / 60).max(0);
+                    send_reminder(&app, event, ReminderKind::Snoozed, mins_until_display);
+                    state.mark_notified(&app, &event.id, end_time);
+                }
+                if now >= until {
+                    if let Err(e) = overrides::consume_snooze(&app, &event.id) {
+                        log::warn!("Failed to clear snooze: {}", e);
+                    }
+                }
+            } else if settings.notification_minutes_before > 0
+                && seconds_until <= (settings.notification_minutes_before * 60)
                 && minutes_until >= -2
             {
-                let already_notified = state
-                    .notified_meetings
-                    .lock()
-                    .unwrap_or_else(|e| e.into_inner())
-                    .contains(&event.id);
+                // Reminder notification (independent of URL auto-open)
+                let already_notified = state.was_notified(&event.id);
 
                 if !already_notified {
                     let mins_until_display = ((seconds_until + 59) / 60).max(0);
-                    if let Err(e) =
-                        send_reminder_notification(&app, &event.summary, mins_until_display)
-                    {
-                        log::warn!("Failed to send reminder notification: {}", e);
-                    }
-                    state
-                        .notified_meetings
-                        .lock()
-                        .unwrap_or_else(|e| e.into_inner())
-                        .insert(event.id.clone());
+                    send_reminder(&app, event, ReminderKind::Upcoming, mins_until_display);
+                    state.mark_notified(&app, &event.id, end_time);
+                }
+            }
+
+            // After sleep or a clock jump, a meeting that is already running missed
+            // its auto-open window; offer to join it instead of dropping it.
+            if disruption.is_some()
+                && minutes_until < -2
+                && end_time > now
+                && meeting_url.is_some()
+                && !event_overrides.skips_auto_open(event)
+                && !state.was_opened(&event.id)
+            {
+                if let Err(e) = send_join_now_notification(&app, event, -minutes_until) {
+                    log::warn!("Failed to send join-now notification: {}", e);
+                } else {
+                    emit_reminder_sent(&app, event, ReminderKind::JoinNow, minutes_until);
                 }
+                // Counts as handled so the offer isn't repeated on the next disruption
+                state.mark_notified(&app, &event.id, end_time);
+                state.mark_opened(&app, &event.id, end_time);
+                continue;
             }
 
-            // Open if within minutes_before and not already started more than 2 minutes ago
-            if seconds_until <= (minutes_before * 60) && minutes_until >= -2 {
-                let already_opened = state
-                    .opened_meetings
-                    .lock()
-                    .unwrap_or_else(|e| e.into_inner())
-                    .contains(&event.id);
+            // Open if within the rule's lead time and not already started more than 2 minutes ago,
+            // unless the user skipped this occurrence or its series
+            if seconds_until <= (decision.minutes_before * 60)
+                && minutes_until >= -2
+                && !event_overrides.skips_auto_open(event)
+            {
+                // Notify-only rules remind at the lead time instead of opening
+                if decision.action == RuleAction::NotifyOnly {
+                    let already_notified = state.was_notified(&event.id);
+                    if !already_notified {
+                        let mins_until_display = ((seconds_until + 59) / 60).max(0);
+                        send_reminder(&app, event, ReminderKind::Upcoming, mins_until_display);
+                        state.mark_notified(&app, &event.id, end_time);
+                    }
+                    continue;
+                }
+
+                let already_opened = state.was_opened(&event.id);
+                let conflict = conflicts::conflict_with(event, &auto_open, &conflict_settings);
+                if !already_opened
+                    && conflict.is_some_and(|c| !resolve_conflict(&app, &state, &c, event, &events))
+                {
+                    continue;
+                }
 
                 if !already_opened {
-                    if let Some(url) = extract_meeting_url(event) {
+                    if let Some(url) = meeting_url {
                         log::info!(
                             "Opening meeting: {} ({})",
                             event.summary,
@@ -135,106 +505,92 @@ pub async fn run_scheduler(app: tauri::AppHandle) {
 
                         // Send "opening now" notification only if we haven't already
                         // sent a reminder for this meeting.
-                        let already_notified = state
-                            .notified_meetings
-                            .lock()
-                            .unwrap_or_else(|e| e.into_inner())
-                            .contains(&event.id);
+                        let already_notified = state.was_notified(&event.id);
                         if !already_notified {
                             if let Err(e) = send_notification(&app, &event.summary) {
                                 log::warn!("Failed to send notification: {}", e);
                             }
-                            state
-                                .notified_meetings
-                                .lock()
-                                .unwrap_or_else(|e| e.into_inner())
-                                .insert(event.id.clone());
+                            state.mark_notified(&app, &event.id, end_time);
                         }
 
                         // Brief delay before opening
                         tokio::time::sleep(Duration::from_secs(3)).await;
 
-                        // Determine which app to open with based on service type
-                        let open_with_app = detect_meeting_service(&url)
-                            .and_then(|service| {
-                                app.store("settings.json")
-                                    .ok()
-                                    .and_then(|store| store.get("openWith"))
-                                    .and_then(|v| v.as_object().cloned())
-                                    .and_then(|obj| obj.get(service).cloned())
-                                    .and_then(|v| v.as_str().map(|s| s.to_string()))
-                                    .filter(|s| s != "default")
-                            });
-
-                        match open_with_app {
-                            Some(app_path) => {
-                                log::info!("Opening with: {}", app_path);
-                                if let Err(e) = open::with(&url, &app_path) {
-                                    log::warn!("Failed to open with {}: {}, falling back to default", app_path, e);
-                                    if let Err(e) = open::that(&url) {
-                                        log::error!("Failed to open URL with default handler: {}", e);
-                                    }
-                                }
-                            }
-                            None => {
-                                if let Err(e) = open::that(&url) {
-                                    log::error!("Failed to open URL: {}", e);
-                                }
-                            }
-                        }
+                        // Determine which app to open with: the matching rule first,
+                        // then the per-service setting
+                        let open_with_app = decision.open_with.clone().or_else(|| {
+                            service.and_then(|service| meeting_url::open_with_app(&app, service))
+                        });
+                        meeting_url::open_meeting(&url, service, open_with_app.as_deref());
 
-                        state
-                            .opened_meetings
-                            .lock()
-                            .unwrap_or_else(|e| e.into_inner())
-                            .insert(event.id.clone());
+                        state.mark_opened(&app, &event.id, end_time);
+                        let opened = MeetingOpened {
+                            event_id: event.id.clone(),
+                            summary: event.summary.clone(),
+                            url,
+                            service: service.map(String::from),
+                        };
+                        if let Err(e) = app.emit(MEETING_OPENED, opened) {
+                            log::warn!("Failed to emit {}: {}", MEETING_OPENED, e);
+                        }
                     }
                 }
             }
         }
 
-        // Clean up old entries from opened_meetings (events no longer in today's list)
-        let events_ref = calendar_state
-            .events
-            .lock()
-            .unwrap_or_else(|e| e.into_inner());
-        let event_ids: HashSet<String> = events_ref.iter().map(|e| e.id.clone()).collect();
-        state
-            .opened_meetings
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .retain(|id| event_ids.contains(id));
-        state
-            .notified_meetings
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .retain(|id| event_ids.contains(id));
+        // Forget meetings that have ended
+        state.prune(&app, now);
 
         // Update tray title with countdown to next event
-        update_tray_title(&app, &events);
+        update_tray_title(&app, &events, settings.tray_countdown_minutes, paused.as_ref());
+        tray_menu::set_meetings(&app, &events, settings.tray_meeting_count);
+
+        let now = Utc::now();
+        if let Some((_, start)) = next_tray_event(&events, now) {
+            deadlines.push(next_tray_change(start, settings.tray_countdown_minutes, now));
+        }
+        deadlines.push(next_local_midnight(now));
+        deadlines.extend(paused.and_then(|p| p.ends_at()));
+        deadlines.extend(profiles::next_schedule_change(&settings.profile_schedule, now));
+        let since_poll = state.last_poll.lock().unwrap_or_else(|e| e.into_inner()).elapsed();
+        deadlines.push(
+            now + chrono::Duration::from_std(
+                Duration::from_secs(POLL_INTERVAL_SECS).saturating_sub(since_poll),
+            )
+            .unwrap_or_default(),
+        );
+        let next = deadlines.into_iter().filter(|d| *d > now).min();
+        sleep_for = sleep_until(next, now);
+        log::debug!("Scheduler sleeping for {:?} (next deadline {:?})", sleep_for, next);
     }
 }
 
-fn update_tray_title(app: &tauri::AppHandle, events: &[crate::calendar::CalendarEvent]) {
-    let tray_countdown_minutes = app
-        .store("settings.json")
-        .ok()
-        .and_then(|store| store.get("trayCountdownMinutes"))
-        .and_then(|v| v.as_i64())
-        .unwrap_or(DEFAULT_TRAY_COUNTDOWN_MINUTES);
-
-    let now = Utc::now();
-
-    // Find next non-all-day event that starts in the future
+/// Next non-all-day event that starts in the future.
+fn next_tray_event(
+    events: &[CalendarEvent],
+    now: DateTime<Utc>,
+) -> Option<(&CalendarEvent, DateTime<Utc>)> {
     // Use filter + min_by to explicitly find the closest future event
-    let next_event_with_start = events
+    events
         .iter()
         .filter(|e| !e.is_all_day)
         .filter_map(|e| {
             parse_event_time(&e.start.date_time).map(|start| (e, start))
         })
         .filter(|(_, start)| *start > now)
-        .min_by_key(|(_, start)| *start);
+        .min_by_key(|(_, start)| *start)
+}
+
+fn update_tray_title(
+    app: &tauri::AppHandle,
+    events: &[CalendarEvent],
+    tray_countdown_minutes: i64,
+    paused: Option<&pause::Pause>,
+) {
+    let now = Utc::now();
+
+    // Find next non-all-day event that starts in the future
+    let next_event_with_start = next_tray_event(events, now);
 
     let title = if let Some((event, start)) = next_event_with_start {
         let seconds_until = (start - now).num_seconds();
@@ -261,6 +617,14 @@ fn update_tray_title(app: &tauri::AppHandle, events: &[crate::calendar::Calendar
         log::debug!("Tray countdown: No upcoming events, clearing title");
         None
     };
+    // Keep the countdown visible next to the pause so it's clear what isn't opening
+    let title = match (paused, title) {
+        (Some(p), Some(countdown)) => {
+            Some(format!("{} · {}", p.tray_label(now, is_japanese_locale()), countdown))
+        }
+        (Some(p), None) => Some(p.tray_label(now, is_japanese_locale())),
+        (None, title) => title,
+    };
 
     if let Some(tray) = app.tray_by_id("main") {
         // Use empty string to clear title instead of None
@@ -315,6 +679,22 @@ fn parse_event_time(date_time_str: &Option<String>) -> Option<DateTime<Utc>> {
         .map(|dt| dt.with_timezone(&Utc))
 }
 
+/// Whether `event` may still auto-open at `now`: not opened yet and at most
+/// 2 minutes in, like the open check in `run_scheduler`. A meeting already
+/// running doesn't compete with one about to start.
+fn awaiting_open(event: &CalendarEvent, opened: bool, now: DateTime<Utc>) -> bool {
+    !opened
+        && parse_event_time(&event.start.date_time)
+            .is_some_and(|start| (start - now).num_minutes() >= -2)
+}
+
+/// When `event` ends; its start for events without an end.
+fn event_end(event: &CalendarEvent) -> DateTime<Utc> {
+    parse_event_time(&event.end.date_time)
+        .or_else(|| parse_event_time(&event.start.date_time))
+        .unwrap_or_else(Utc::now)
+}
+
 fn send_notification(app: &tauri::AppHandle, summary: &str) -> Result<(), Box<dyn std::error::Error>> {
     use tauri_plugin_notification::NotificationExt;
     let is_ja = is_japanese_locale();
@@ -327,12 +707,153 @@ fn send_notification(app: &tauri::AppHandle, summary: &str) -> Result<(), Box<dy
     Ok(())
 }
 
+/// Carry out buttons pressed on meeting notifications.
+fn handle_notification_actions(
+    app: &tauri::AppHandle,
+    state: &SchedulerState,
+    actions: Vec<(String, NotificationAction)>,
+    events: &[CalendarEvent],
+) {
+    for (event_id, action) in actions {
+        let override_action = match action {
+            NotificationAction::JoinNow => {
+                let Some(event) = events.iter().find(|e| e.id == event_id) else {
+                    log::warn!("Join now: event {} is no longer scheduled", event_id);
+                    continue;
+                };
+                let Some(meeting) = &event.meeting else {
+                    continue;
+                };
+                log::info!("Joining from notification: {}", event.summary);
+                let service = meeting_url::open_for_account(
+                    app,
+                    &meeting.url,
+                    event.calendar_account_name.as_deref(),
+                );
+                // Counts as opened so auto-open and reminders leave it alone
+                let end = parse_event_time(&event.end.date_time).unwrap_or_else(Utc::now);
+                state.mark_notified(app, &event.id, end);
+                state.mark_opened(app, &event.id, end);
+                state.answer(app, &event.id, events);
+                let opened = MeetingOpened {
+                    event_id: event.id.clone(),
+                    summary: event.summary.clone(),
+                    url: meeting.url.clone(),
+                    service,
+                };
+                if let Err(e) = app.emit(MEETING_OPENED, opened) {
+                    log::warn!("Failed to emit {}: {}", MEETING_OPENED, e);
+                }
+                continue;
+            }
+            NotificationAction::Snooze => OverrideAction::Snooze {
+                minutes: notification_actions::SNOOZE_MINUTES,
+            },
+            NotificationAction::Skip => OverrideAction::Skip,
+        };
+        let series_id = events
+            .iter()
+            .find(|e| e.id == event_id)
+            .and_then(|e| e.series_id.clone());
+        if let Err(e) =
+            overrides::set_event_override(app.clone(), event_id, series_id, override_action)
+        {
+            log::warn!("Failed to apply notification action: {}", e);
+        }
+    }
+}
+
+/// Apply the conflict policy when `event` is due to open while overlapping
+/// other meetings (`conflict`, from `conflicts::conflict_with`). Returns true
+/// if `event` should open.
+///
+/// Meetings the policy passes over are marked opened so they stay closed.
+/// Without a clear winner nothing opens: a notification with a join button
+/// per meeting asks which one to join, and the window is brought up to pick
+/// from too. Each clash is asked about once; the meetings stay closed until
+/// the user picks one (see `SchedulerState::answer`).
+fn resolve_conflict(
+    app: &tauri::AppHandle,
+    state: &SchedulerState,
+    conflict: &Conflict,
+    event: &CalendarEvent,
+    events: &[CalendarEvent],
+) -> bool {
+    let end_of = |e: &CalendarEvent| {
+        parse_event_time(&e.end.date_time)
+            .or_else(|| parse_event_time(&e.start.date_time))
+            .unwrap_or(conflict.end)
+    };
+    match &conflict.preferred {
+        Some(id) if *id == event.id => return true,
+        Some(id) => {
+            log::info!("Not opening {}: overlaps preferred meeting {}", event.summary, id);
+            state.mark_opened(app, &event.id, end_of(event));
+            return false;
+        }
+        None => {}
+    }
+    if state.was_asked(&event.id) {
+        return false;
+    }
+    let members: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|e| conflict.contains(&e.id))
+        .collect();
+    let summaries: Vec<&str> = members.iter().map(|e| e.summary.as_str()).collect();
+    log::info!("Overlapping meetings, asking which to join: {}", summaries.join(", "));
+    if let Err(e) = send_conflict_notification(app, &members, conflict.end) {
+        log::warn!("Failed to send conflict notification: {}", e);
+    }
+    if let Err(e) = app.emit(conflicts::CONFLICT_DETECTED, conflict) {
+        log::warn!("Failed to emit {}: {}", conflicts::CONFLICT_DETECTED, e);
+    }
+    // The schedule flags the overlap and has a join button per meeting
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+            log::warn!("Failed to show window for conflict: {}", e);
+        }
+    }
+    state.mark_asked(conflict);
+    false
+}
+
+/// Send a reminder notification and tell the frontend about it.
+fn send_reminder(
+    app: &tauri::AppHandle,
+    event: &CalendarEvent,
+    kind: ReminderKind,
+    mins_until: i64,
+) {
+    match send_reminder_notification(app, event, mins_until) {
+        Ok(()) => emit_reminder_sent(app, event, kind, mins_until),
+        Err(e) => log::warn!("Failed to send reminder notification: {}", e),
+    }
+}
+
+fn emit_reminder_sent(
+    app: &tauri::AppHandle,
+    event: &CalendarEvent,
+    kind: ReminderKind,
+    minutes_until: i64,
+) {
+    let payload = ReminderSent {
+        event_id: event.id.clone(),
+        summary: event.summary.clone(),
+        kind,
+        minutes_until,
+    };
+    if let Err(e) = app.emit(REMINDER_SENT, payload) {
+        log::warn!("Failed to emit {}: {}", REMINDER_SENT, e);
+    }
+}
+
 fn send_reminder_notification(
     app: &tauri::AppHandle,
-    summary: &str,
+    event: &CalendarEvent,
     mins_until: i64,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use tauri_plugin_notification::NotificationExt;
+    let summary = &event.summary;
     let is_ja = is_japanese_locale();
     let body = if is_ja {
         if mins_until <= 0 {
@@ -345,6 +866,187 @@ fn send_reminder_notification(
     } else {
         format!("Starts in {} min: {}", mins_until, summary)
     };
-    app.notification().builder().title("Galopen").body(body).show()?;
-    Ok(())
+    // Join only makes sense with a link; skip and snooze apply either way
+    let actions: &[NotificationAction] = if event.meeting.is_some() {
+        &NotificationAction::ALL
+    } else {
+        &[NotificationAction::Snooze, NotificationAction::Skip]
+    };
+    notification_actions::show(app, body, &event.id, event_end(event), actions, is_ja)
+}
+
+fn send_conflict_notification(
+    app: &tauri::AppHandle,
+    members: &[&CalendarEvent],
+    until: DateTime<Utc>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let summaries: Vec<&str> = members.iter().map(|e| e.summary.as_str()).collect();
+    let is_ja = is_japanese_locale();
+    let body = if is_ja {
+        format!("会議が重なっています。参加する会議を選んでください: {}", summaries.join(" / "))
+    } else {
+        format!("Overlapping meetings, choose one to join: {}", summaries.join(" / "))
+    };
+    notification_actions::show_choice(app, body, members, until, is_ja)
+}
+
+fn send_join_now_notification(
+    app: &tauri::AppHandle,
+    event: &CalendarEvent,
+    mins_ago: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let summary = &event.summary;
+    let is_ja = is_japanese_locale();
+    let body = if is_ja {
+        format!("今すぐ参加（{}分前に開始）: {}", mins_ago, summary)
+    } else {
+        format!("Join now (started {} min ago): {}", mins_ago, summary)
+    };
+    let join = [NotificationAction::JoinNow];
+    notification_actions::show(app, body, &event.id, event_end(event), &join, is_ja)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::EventDateTime;
+
+    fn clock(wall_secs: i64, mono_secs: u64, offset_secs: i32, base: std::time::Instant) -> TickClock {
+        TickClock {
+            wall: DateTime::from_timestamp(1_714_550_000 + wall_secs, 0).unwrap(),
+            mono: base + Duration::from_secs(mono_secs),
+            offset_secs,
+        }
+    }
+
+    #[test]
+    fn regular_tick_is_not_a_disruption() {
+        let base = std::time::Instant::now();
+        let prev = clock(0, 0, 0, base);
+        let planned = Duration::from_secs(10);
+        assert_eq!(detect_disruption(&prev, &clock(10, 10, 0, base), planned), None);
+        assert_eq!(detect_disruption(&prev, &clock(12, 11, 0, base), planned), None);
+        // Woken early by a notify
+        assert_eq!(detect_disruption(&prev, &clock(2, 2, 0, base), Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn sleep_shows_up_as_clock_jump() {
+        // Monotonic clock stops during sleep while the wall clock keeps going
+        let base = std::time::Instant::now();
+        let prev = clock(0, 0, 0, base);
+        assert_eq!(
+            detect_disruption(&prev, &clock(45 * 60, 10, 0, base), Duration::from_secs(10)),
+            Some(Disruption::ClockJump)
+        );
+        // Clock set back manually
+        assert_eq!(
+            detect_disruption(&prev, &clock(-600, 10, 0, base), Duration::from_secs(10)),
+            Some(Disruption::ClockJump)
+        );
+    }
+
+    #[test]
+    fn late_tick_is_a_gap() {
+        let base = std::time::Instant::now();
+        let prev = clock(0, 0, 0, base);
+        assert_eq!(
+            detect_disruption(&prev, &clock(300, 300, 0, base), Duration::from_secs(10)),
+            Some(Disruption::Gap)
+        );
+        let planned = Duration::from_secs(60);
+        assert_eq!(detect_disruption(&prev, &clock(60, 60, 0, base), planned), None);
+    }
+
+    #[test]
+    fn timezone_change_is_detected() {
+        let base = std::time::Instant::now();
+        let prev = clock(0, 0, 9 * 3600, base);
+        assert_eq!(
+            detect_disruption(&prev, &clock(10, 10, -7 * 3600, base), Duration::from_secs(10)),
+            Some(Disruption::TimezoneChange)
+        );
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn tray_changes_when_entering_window_then_every_minute() {
+        let start = at("2024-05-01T10:00:00Z");
+        // 45 min out with a 30 min window: next change is when the label appears
+        assert_eq!(
+            next_tray_change(start, 30, at("2024-05-01T09:15:00Z")),
+            at("2024-05-01T09:30:00Z")
+        );
+        // Inside the window the label ticks down on minute boundaries relative to start
+        assert_eq!(
+            next_tray_change(start, 30, at("2024-05-01T09:50:30Z")),
+            at("2024-05-01T09:51:00Z")
+        );
+        assert_eq!(
+            next_tray_change(start, 30, at("2024-05-01T09:51:00Z")),
+            at("2024-05-01T09:52:00Z")
+        );
+        // Last minute: the label clears at start
+        assert_eq!(next_tray_change(start, 30, at("2024-05-01T09:59:30Z")), start);
+        // 0 = always shown
+        assert_eq!(
+            next_tray_change(start, 0, at("2024-05-01T07:00:30Z")),
+            at("2024-05-01T07:01:00Z")
+        );
+    }
+
+    #[test]
+    fn next_midnight_is_start_of_next_local_day() {
+        let now = at("2024-05-01T12:34:56Z");
+        let midnight = next_local_midnight(now);
+        assert!(midnight > now);
+        assert!(midnight - now <= chrono::Duration::hours(25));
+        let local = midnight.with_timezone(&Local);
+        assert_eq!(local.date_naive(), now.with_timezone(&Local).date_naive() + Days::new(1));
+    }
+
+    #[test]
+    fn back_to_back_meetings_only_clash_before_either_opens() {
+        let meeting = |id: &str, start: &str, end: &str| CalendarEvent {
+            id: id.to_string(),
+            summary: id.to_string(),
+            start: EventDateTime { date_time: Some(start.to_string()), date: None },
+            end: EventDateTime { date_time: Some(end.to_string()), date: None },
+            ..Default::default()
+        };
+        let first = meeting("first", "2024-05-01T09:00:00Z", "2024-05-01T10:00:00Z");
+        let second = meeting("second", "2024-05-01T09:55:00Z", "2024-05-01T10:30:00Z");
+        let settings = ConflictSettings::default();
+        let pending = |opened: &[&str], now: DateTime<Utc>| -> Vec<&CalendarEvent> {
+            [&first, &second]
+                .into_iter()
+                .filter(|e| awaiting_open(e, opened.contains(&e.id.as_str()), now))
+                .collect()
+        };
+
+        // Before either opens they tie, so the user would be asked
+        let before = pending(&[], at("2024-05-01T08:59:00Z"));
+        let conflict = conflicts::conflict_with(&first, &before, &settings).unwrap();
+        assert_eq!(conflict.preferred, None);
+
+        // Once the first is running, the second opens on its own
+        for opened in [&["first"][..], &[]] {
+            let due = pending(opened, at("2024-05-01T09:54:00Z"));
+            assert_eq!(due.len(), 1);
+            assert_eq!(conflicts::conflict_with(&second, &due, &settings), None);
+        }
+    }
+
+    #[test]
+    fn sleep_is_capped_and_never_negative() {
+        let now = at("2024-05-01T10:00:00Z");
+        assert_eq!(sleep_until(Some(at("2024-05-01T10:00:07Z")), now), Duration::from_secs(7));
+        let max = Duration::from_secs(MAX_SLEEP_SECS);
+        assert_eq!(sleep_until(Some(at("2024-05-01T11:00:00Z")), now), max);
+        assert_eq!(sleep_until(None, now), max);
+        assert_eq!(sleep_until(Some(at("2024-05-01T09:59:00Z")), now), max);
+    }
 }
@@ -0,0 +1,414 @@
+use crate::calendar::{move_passwords_to_keychain, CalDavAccount, DEFAULT_WINDOW_DAYS};
+use crate::conflicts::ConflictPolicy;
+use crate::meeting_url::CustomProvider;
+use crate::profiles::{Profile, ProfileSchedule};
+use crate::rules::Rule;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "settings.json";
+/// Bump together with a new entry in `MIGRATIONS`.
+pub const SCHEMA_VERSION: u32 = 1;
+pub const MAX_MINUTES_BEFORE: i64 = 60;
+pub const MAX_NOTIFY_MINUTES_BEFORE: i64 = 120;
+pub const MAX_TRAY_COUNTDOWN_MINUTES: i64 = 24 * 60;
+pub const MAX_WINDOW_DAYS: u32 = 60;
+pub const MAX_TRAY_MEETINGS: u32 = 10;
+
+/// Tauri event emitted after settings were changed; payload `Settings`.
+pub const SETTINGS_CHANGED: &str = "settings-changed";
+
+/// Everything stored in `settings.json`.
+///
+/// The store keeps one flat camelCase key per field. Missing keys take the
+/// defaults below; values of the wrong type are reported and ignored, and
+/// out-of-range numbers are clamped by `validate`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub schema_version: u32,
+    /// Auto-open lead time
+    pub minutes_before: i64,
+    /// Reminder lead time; 0 turns reminders off
+    pub notification_minutes_before: i64,
+    /// Off: meetings are announced at their lead time instead of opened
+    pub auto_open: bool,
+    /// While auto-open is paused from the tray, still send reminders
+    pub pause_keeps_reminders: bool,
+    /// Show the tray countdown this long before a meeting; 0 = always
+    pub tray_countdown_minutes: i64,
+    /// Upcoming meetings listed in the tray menu; 0 hides the list
+    pub tray_meeting_count: u32,
+    /// Days after today the background sync covers
+    pub scheduler_window_days: u32,
+    /// Meeting service id → app to open it with
+    pub open_with: HashMap<String, String>,
+    pub custom_providers: Vec<CustomProvider>,
+    pub rules: Vec<Rule>,
+    pub ics_paths: Vec<PathBuf>,
+    pub caldav_accounts: Vec<CalDavAccount>,
+    /// None until the user narrows the calendar filter down
+    pub enabled_calendars: Option<Vec<String>>,
+    pub skip_declined: bool,
+    pub skip_tentative: bool,
+    pub skip_needs_action: bool,
+    pub hide_skipped_invitations: bool,
+    pub conflict_policy: ConflictPolicy,
+    pub conflict_calendar_id: Option<String>,
+    /// Days shown in the window (1-3)
+    pub display_range: u8,
+    pub weekdays_only: bool,
+    /// Shared settings file re-imported whenever it changes (see settings_file.rs)
+    pub sync_file_path: Option<PathBuf>,
+    /// Saved profiles; the active one's values are the ones above (see profiles.rs)
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+    pub profile_schedule: Vec<ProfileSchedule>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            minutes_before: 1,
+            notification_minutes_before: 5,
+            auto_open: true,
+            pause_keeps_reminders: true,
+            tray_countdown_minutes: 30,
+            tray_meeting_count: 5,
+            scheduler_window_days: DEFAULT_WINDOW_DAYS,
+            open_with: HashMap::new(),
+            custom_providers: Vec::new(),
+            rules: Vec::new(),
+            ics_paths: Vec::new(),
+            caldav_accounts: Vec::new(),
+            enabled_calendars: None,
+            skip_declined: true,
+            skip_tentative: false,
+            skip_needs_action: false,
+            hide_skipped_invitations: false,
+            conflict_policy: ConflictPolicy::default(),
+            conflict_calendar_id: None,
+            display_range: 1,
+            weekdays_only: false,
+            sync_file_path: None,
+            profiles: Vec::new(),
+            active_profile: None,
+            profile_schedule: Vec::new(),
+        }
+    }
+}
+
+/// A stored key that didn't make it into `Settings`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsIssue {
+    pub key: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    /// Not a setting this version knows; left alone in the store
+    Unknown,
+    /// Wrong type or shape; the current value is kept
+    Invalid,
+    /// Machine-specific setting in an imported file; not applied
+    Local,
+}
+
+impl Settings {
+    /// Clamp values to the ranges the app supports.
+    pub fn validate(&mut self) {
+        self.minutes_before = self.minutes_before.clamp(0, MAX_MINUTES_BEFORE);
+        self.notification_minutes_before =
+            self.notification_minutes_before.clamp(0, MAX_NOTIFY_MINUTES_BEFORE);
+        self.tray_countdown_minutes =
+            self.tray_countdown_minutes.clamp(0, MAX_TRAY_COUNTDOWN_MINUTES);
+        self.scheduler_window_days = self.scheduler_window_days.clamp(1, MAX_WINDOW_DAYS);
+        self.tray_meeting_count = self.tray_meeting_count.min(MAX_TRAY_MEETINGS);
+        self.display_range = self.display_range.clamp(1, 3);
+        // "default" in the Settings UI means no override
+        self.open_with.retain(|_, app| app != "default");
+        for rule in &mut self.rules {
+            rule.minutes_before = rule.minutes_before.map(|m| m.clamp(0, MAX_MINUTES_BEFORE));
+        }
+        for profile in &mut self.profiles {
+            profile.minutes_before = profile.minutes_before.clamp(0, MAX_MINUTES_BEFORE);
+            profile.notification_minutes_before =
+                profile.notification_minutes_before.clamp(0, MAX_NOTIFY_MINUTES_BEFORE);
+            profile.open_with.retain(|_, app| app != "default");
+        }
+        if let Some(active) = &self.active_profile {
+            if !self.profiles.iter().any(|p| &p.name == active) {
+                self.active_profile = None;
+            }
+        }
+    }
+
+    /// Build settings from stored keys: migrate, then take every known key
+    /// whose value parses, then validate.
+    pub fn from_map(mut map: Map<String, Value>) -> (Self, Vec<SettingsIssue>) {
+        migrate(&mut map);
+        Self::default().merge(&map)
+    }
+
+    /// Apply the keys in `patch` (already migrated) on top of these settings.
+    /// Keys that are unknown or don't parse are reported and keep their
+    /// current value.
+    pub fn merge(&self, patch: &Map<String, Value>) -> (Self, Vec<SettingsIssue>) {
+        let current = self.to_map();
+        let mut merged = current.clone();
+        let mut issues = Vec::new();
+        let mut keys: Vec<&String> = patch.keys().filter(|k| *k != "schemaVersion").collect();
+        keys.sort();
+        for key in keys {
+            if !current.contains_key(key) {
+                issues.push(SettingsIssue {
+                    key: key.clone(),
+                    kind: IssueKind::Unknown,
+                    message: "not a known setting".to_string(),
+                });
+                continue;
+            }
+            // Check each key on its own so one bad value doesn't reset the rest
+            let mut candidate = current.clone();
+            candidate.insert(key.clone(), patch[key].clone());
+            match serde_json::from_value::<Self>(Value::Object(candidate)) {
+                Ok(_) => {
+                    merged.insert(key.clone(), patch[key].clone());
+                }
+                Err(e) => issues.push(SettingsIssue {
+                    key: key.clone(),
+                    kind: IssueKind::Invalid,
+                    message: e.to_string(),
+                }),
+            }
+        }
+
+        let mut settings: Self =
+            serde_json::from_value(Value::Object(merged)).unwrap_or_else(|_| self.clone());
+        settings.schema_version = SCHEMA_VERSION;
+        settings.validate();
+        (settings, issues)
+    }
+
+    pub fn to_map(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+}
+
+/// `MIGRATIONS[n]` upgrades stored keys from schema version n to n + 1.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [
+    // 0 → 1: unversioned settings written before this module existed. Lead
+    // times edited by hand as floats ("minutesBefore": 2.0) were silently
+    // ignored by the old `as_i64` readers; keep what the user meant.
+    |map| {
+        for key in [
+            "minutesBefore",
+            "notificationMinutesBefore",
+            "trayCountdownMinutes",
+            "schedulerWindowDays",
+            "displayRange",
+        ] {
+            if let Some(n) = map.get(key).and_then(Value::as_f64) {
+                map.insert(key.to_string(), Value::from(n.round() as i64));
+            }
+        }
+    },
+];
+
+/// Upgrade `map` from the schema version it was written with.
+pub fn migrate(map: &mut Map<String, Value>) {
+    let version = map
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as usize;
+    for migration in MIGRATIONS.iter().skip(version) {
+        migration(map);
+    }
+    map.insert("schemaVersion".to_string(), Value::from(SCHEMA_VERSION));
+}
+
+/// Settings as loaded at startup and after every change, so readers don't
+/// hit the store (and its parsing) on every scheduler pass.
+pub struct SettingsState(Mutex<Settings>);
+
+impl SettingsState {
+    /// Load, migrate and write back the settings once at startup.
+    pub fn init(app: &tauri::AppHandle) -> Self {
+        let (mut settings, issues) = read_store(app);
+        // Older versions kept CalDAV passwords in settings.json
+        move_passwords_to_keychain(&mut settings.caldav_accounts);
+        for issue in issues.iter().filter(|i| i.kind == IssueKind::Invalid) {
+            log::warn!("Ignoring invalid {} setting: {}", issue.key, issue.message);
+        }
+        if let Err(e) = write_store(app, &settings) {
+            log::warn!("Failed to save migrated settings: {}", e);
+        }
+        Self(Mutex::new(settings))
+    }
+}
+
+fn read_store(app: &tauri::AppHandle) -> (Settings, Vec<SettingsIssue>) {
+    let map = app
+        .store(STORE_FILE)
+        .map(|store| store.entries().into_iter().collect())
+        .unwrap_or_default();
+    Settings::from_map(map)
+}
+
+fn write_store(app: &tauri::AppHandle, settings: &Settings) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    for (key, value) in settings.to_map() {
+        store.set(key, value);
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+/// The current settings.
+pub fn get(app: &tauri::AppHandle) -> Settings {
+    match app.try_state::<SettingsState>() {
+        Some(state) => state.inner().0.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        None => read_store(app).0,
+    }
+}
+
+/// Apply the keys in `patch` on top of the current settings, save them and
+/// tell everyone that reads settings. Invalid or unknown keys reject the
+/// whole patch.
+pub fn update(app: &tauri::AppHandle, patch: Map<String, Value>) -> Result<Settings, String> {
+    let (settings, issues) = get(app).merge(&patch);
+    if let Some(issue) = issues.first() {
+        return Err(format!("{}: {}", issue.key, issue.message));
+    }
+    replace(app, settings.clone())?;
+    Ok(settings)
+}
+
+/// Save `settings` as a whole, then notify the scheduler and the frontend.
+/// CalDAV passwords go to the Keychain rather than the store.
+pub fn replace(app: &tauri::AppHandle, mut settings: Settings) -> Result<(), String> {
+    move_passwords_to_keychain(&mut settings.caldav_accounts);
+    write_store(app, &settings)?;
+    if let Some(state) = app.try_state::<SettingsState>() {
+        *state.inner().0.lock().unwrap_or_else(|e| e.into_inner()) = settings.clone();
+    }
+    crate::scheduler::wake(app);
+    crate::tray_menu::refresh(app);
+    if let Err(e) = app.emit(SETTINGS_CHANGED, settings) {
+        log::warn!("Failed to emit {}: {}", SETTINGS_CHANGED, e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(app: tauri::AppHandle) -> Settings {
+    get(&app)
+}
+
+/// Change some settings; returns the settings as saved (after clamping).
+#[tauri::command]
+pub fn update_settings(
+    app: tauri::AppHandle,
+    patch: Map<String, Value>,
+) -> Result<Settings, String> {
+    update(&app, patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn keeps_valid_keys_and_reports_the_rest() {
+        let (settings, issues) = Settings::from_map(map(json!({
+            "minutesBefore": 3,
+            "notificationMinutesBefore": "ten",
+            "openWith": { "zoom": "/Applications/zoom.us.app", "meet": "default" },
+            "someFutureSetting": true,
+        })));
+        assert_eq!(settings.minutes_before, 3);
+        assert_eq!(settings.notification_minutes_before, 5);
+        assert_eq!(settings.open_with.len(), 1);
+        assert!(settings.skip_declined);
+        assert_eq!(
+            issues.iter().map(|i| (i.key.as_str(), i.kind)).collect::<Vec<_>>(),
+            vec![
+                ("notificationMinutesBefore", IssueKind::Invalid),
+                ("someFutureSetting", IssueKind::Unknown),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_clamps_to_supported_ranges() {
+        let (settings, issues) = Settings::from_map(map(json!({
+            "schemaVersion": SCHEMA_VERSION,
+            "minutesBefore": -4,
+            "trayCountdownMinutes": 100_000,
+            "schedulerWindowDays": 0,
+            "displayRange": 9,
+            "rules": [
+                { "action": "open", "minutesBefore": -5 },
+                { "action": "open", "minutesBefore": 1_000_000 },
+                { "action": "open" },
+            ],
+        })));
+        assert!(issues.is_empty());
+        assert_eq!(settings.minutes_before, 0);
+        assert_eq!(settings.tray_countdown_minutes, MAX_TRAY_COUNTDOWN_MINUTES);
+        assert_eq!(settings.scheduler_window_days, 1);
+        assert_eq!(settings.display_range, 3);
+        let rule_leads: Vec<Option<i64>> = settings.rules.iter().map(|r| r.minutes_before).collect();
+        assert_eq!(rule_leads, vec![Some(0), Some(MAX_MINUTES_BEFORE), None]);
+    }
+
+    #[test]
+    fn unversioned_settings_are_migrated() {
+        let (settings, issues) = Settings::from_map(map(json!({
+            "minutesBefore": 2.0,
+            "displayRange": 2.0,
+        })));
+        assert!(issues.is_empty());
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert_eq!(settings.minutes_before, 2);
+        assert_eq!(settings.display_range, 2);
+
+        // Round trip through the store layout is lossless
+        let (again, issues) = Settings::from_map(settings.to_map());
+        assert!(issues.is_empty());
+        assert_eq!(again.to_map(), settings.to_map());
+    }
+
+    #[test]
+    fn caldav_passwords_are_read_but_never_written() {
+        let (settings, issues) = Settings::from_map(map(json!({
+            "caldavAccounts": [
+                { "url": "https://dav.example.com/", "username": "alice", "password": "secret" },
+            ],
+        })));
+        assert!(issues.is_empty());
+        assert_eq!(settings.caldav_accounts[0].password.as_deref(), Some("secret"));
+        let stored = Value::Object(settings.to_map()).to_string();
+        assert!(!stored.contains("secret"), "{}", stored);
+    }
+}
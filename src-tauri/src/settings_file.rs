@@ -0,0 +1,189 @@
+use crate::settings::{self, IssueKind, Settings, SettingsIssue, SCHEMA_VERSION};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// How often the sync file is checked for changes
+const SYNC_POLL_SECS: u64 = 5;
+
+/// Settings that only make sense on the Mac they were set on (credentials,
+/// local paths, calendar ids). Exports leave them out and imports skip them.
+/// Profiles carry calendar ids too.
+const LOCAL_KEYS: [&str; 8] = [
+    "caldavAccounts",
+    "icsPaths",
+    "enabledCalendars",
+    "conflictCalendarId",
+    "syncFilePath",
+    "profiles",
+    "activeProfile",
+    "profileSchedule",
+];
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub settings: Settings,
+    /// Keys in the file that were not applied
+    pub issues: Vec<SettingsIssue>,
+}
+
+/// The shareable part of `settings`, as written to an export file.
+pub fn to_export(settings: &Settings) -> Map<String, Value> {
+    let mut map = settings.to_map();
+    for key in LOCAL_KEYS {
+        map.remove(key);
+    }
+    map
+}
+
+/// Apply an exported file on top of `current`. Keys that are unknown,
+/// invalid or machine-specific are reported and leave the current value alone.
+pub fn apply_export(
+    current: &Settings,
+    contents: &str,
+) -> Result<(Settings, Vec<SettingsIssue>), String> {
+    let mut map = match serde_json::from_str(contents) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => return Err("Not a settings file: expected a JSON object".to_string()),
+        Err(e) => return Err(format!("Not a settings file: {}", e)),
+    };
+    let version = map.get("schemaVersion").and_then(Value::as_u64).unwrap_or(0);
+    if version > u64::from(SCHEMA_VERSION) {
+        return Err(format!(
+            "Settings file is from a newer Galopen (schema {}, supported {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    let local: Vec<SettingsIssue> = LOCAL_KEYS
+        .into_iter()
+        .filter(|key| map.remove(*key).is_some())
+        .map(|key| SettingsIssue {
+            key: key.to_string(),
+            kind: IssueKind::Local,
+            message: "only applies to the Mac it was set on".to_string(),
+        })
+        .collect();
+    settings::migrate(&mut map);
+    let (settings, mut issues) = current.merge(&map);
+    issues.extend(local);
+    Ok((settings, issues))
+}
+
+fn import_file(app: &tauri::AppHandle, path: &Path) -> Result<ImportReport, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (settings, issues) = apply_export(&settings::get(app), &contents)?;
+    settings::replace(app, settings.clone())?;
+    Ok(ImportReport { settings, issues })
+}
+
+#[tauri::command]
+pub fn export_settings(app: tauri::AppHandle, path: PathBuf) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&to_export(&settings::get(&app)))
+        .map_err(|e| e.to_string())?;
+    std::fs::write(&path, json + "\n")
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn import_settings(app: tauri::AppHandle, path: PathBuf) -> Result<ImportReport, String> {
+    import_file(&app, &path)
+}
+
+/// Re-import the `syncFilePath` file whenever it changes, e.g. after a pull
+/// in a dotfiles repo. It is also applied once at startup, so the shared
+/// keys in the file win over local edits.
+pub fn watch_sync_file(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut applied: Option<(PathBuf, SystemTime)> = None;
+        loop {
+            if let Some(path) = settings::get(&app).sync_file_path {
+                match std::fs::metadata(&path).and_then(|m| m.modified()) {
+                    Ok(modified) => {
+                        let seen = applied
+                            .as_ref()
+                            .is_some_and(|(p, t)| *p == path && *t == modified);
+                        if !seen {
+                            match import_file(&app, &path) {
+                                Ok(report) => {
+                                    log::info!("Applied settings from {}", path.display());
+                                    for issue in report.issues {
+                                        log::warn!("Skipped {}: {}", issue.key, issue.message);
+                                    }
+                                }
+                                Err(e) => log::warn!("Failed to apply sync file: {}", e),
+                            }
+                            // Also after a failure, so a broken file isn't retried every poll
+                            applied = Some((path, modified));
+                        }
+                    }
+                    Err(e) => log::debug!("Sync file {} unavailable: {}", path.display(), e),
+                }
+            }
+            std::thread::sleep(Duration::from_secs(SYNC_POLL_SECS));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn export_leaves_out_local_settings() {
+        let settings = Settings {
+            ics_paths: vec![PathBuf::from("/Users/me/cal.ics")],
+            enabled_calendars: Some(vec!["work".to_string()]),
+            ..Settings::default()
+        };
+        let exported = to_export(&settings);
+        assert!(exported.contains_key("minutesBefore"));
+        assert!(exported.contains_key("schemaVersion"));
+        assert!(LOCAL_KEYS.iter().all(|key| !exported.contains_key(*key)));
+    }
+
+    #[test]
+    fn import_applies_valid_keys_and_reports_the_rest() {
+        let current = Settings {
+            notification_minutes_before: 10,
+            enabled_calendars: Some(vec!["work".to_string()]),
+            ..Settings::default()
+        };
+        let file = json!({
+            "schemaVersion": 1,
+            "minutesBefore": 3,
+            "notificationMinutesBefore": "soon",
+            "openWith": { "zoom": "/Applications/zoom.us.app" },
+            "enabledCalendars": ["home"],
+            "teamName": "platform",
+        });
+
+        let (settings, issues) = apply_export(&current, &file.to_string()).unwrap();
+        assert_eq!(settings.minutes_before, 3);
+        assert_eq!(settings.open_with.len(), 1);
+        // Invalid and local keys don't clobber what was there
+        assert_eq!(settings.notification_minutes_before, 10);
+        assert_eq!(settings.enabled_calendars, Some(vec!["work".to_string()]));
+        assert_eq!(
+            issues.iter().map(|i| (i.key.as_str(), i.kind)).collect::<Vec<_>>(),
+            vec![
+                ("notificationMinutesBefore", IssueKind::Invalid),
+                ("teamName", IssueKind::Unknown),
+                ("enabledCalendars", IssueKind::Local),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_rejects_non_settings_and_newer_files() {
+        let current = Settings::default();
+        assert!(apply_export(&current, "[1, 2]").is_err());
+        assert!(apply_export(&current, "minutesBefore = 3").is_err());
+        let newer = json!({ "schemaVersion": SCHEMA_VERSION + 1, "minutesBefore": 3 });
+        assert!(apply_export(&current, &newer.to_string()).is_err());
+    }
+}
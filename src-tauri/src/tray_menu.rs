@@ -0,0 +1,384 @@
+use crate::calendar::CalendarEvent;
+use crate::meeting_info;
+use crate::meeting_url::{self, ProviderRegistry};
+use crate::pause::{self, Pause, PauseLength};
+use crate::profiles;
+use crate::scheduler::is_japanese_locale;
+use crate::settings;
+use chrono::{DateTime, Local, Utc};
+use std::io::Write;
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use tauri::Manager;
+
+// Menu item ids for profiles are this prefix plus the profile name
+const PROFILE_PREFIX: &str = "profile:";
+// Menu item ids for meeting actions are one of these plus the event id
+const JOIN_PREFIX: &str = "join:";
+const COPY_LINK_PREFIX: &str = "copy_link:";
+const COPY_DIAL_IN_PREFIX: &str = "copy_dial_in:";
+const OPEN_CALENDAR_PREFIX: &str = "open_calendar:";
+// Longer titles are cut off so the menu stays narrow
+const MAX_TITLE_CHARS: usize = 40;
+
+/// An upcoming meeting as listed in the tray menu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrayMeeting {
+    pub event_id: String,
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    /// Display name of the meeting service, e.g. "Zoom"
+    pub service: Option<String>,
+    pub url: Option<String>,
+    pub account: Option<String>,
+    /// As copied by the window's dial-in button
+    pub dial_in: Option<String>,
+    pub external_url: Option<String>,
+}
+
+/// The meetings the menu was last built with, kept up to date by the scheduler.
+#[derive(Default)]
+pub struct TrayMeetings(Mutex<Vec<TrayMeeting>>);
+
+/// The next `count` timed meetings that haven't ended, in start order.
+pub fn upcoming(
+    registry: &ProviderRegistry,
+    events: &[CalendarEvent],
+    now: DateTime<Utc>,
+    count: usize,
+    is_ja: bool,
+) -> Vec<TrayMeeting> {
+    let parse = |s: &Option<String>| {
+        DateTime::parse_from_rfc3339(s.as_deref()?)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    let mut timed: Vec<(&CalendarEvent, DateTime<Utc>)> = events
+        .iter()
+        .filter(|e| !e.is_all_day)
+        .filter_map(|e| {
+            let start = parse(&e.start.date_time)?;
+            let end = parse(&e.end.date_time).unwrap_or(start);
+            (end > now).then_some((e, start))
+        })
+        .collect();
+    timed.sort_by_key(|(_, start)| *start);
+    timed
+        .into_iter()
+        .take(count)
+        .map(|(event, start)| TrayMeeting {
+            event_id: event.id.clone(),
+            summary: event.summary.clone(),
+            start,
+            service: event.meeting.as_ref().map(|m| m.name.clone()),
+            url: event.meeting.as_ref().map(|m| m.url.clone()),
+            account: event.calendar_account_name.clone(),
+            dial_in: meeting_info::extract(registry, event).and_then(|i| i.dial_in_text(is_ja)),
+            external_url: event.external_url.clone(),
+        })
+        .collect()
+}
+
+/// Update the listed meetings from the scheduler's events; the menu is only
+/// rebuilt when the list changed.
+pub fn set_meetings(app: &tauri::AppHandle, events: &[CalendarEvent], count: u32) {
+    let Some(state) = app.try_state::<TrayMeetings>() else {
+        return;
+    };
+    let meetings = upcoming(
+        &meeting_url::registry(app),
+        events,
+        Utc::now(),
+        count as usize,
+        is_japanese_locale(),
+    );
+    {
+        let mut current = state.inner().0.lock().unwrap_or_else(|e| e.into_inner());
+        if *current == meetings {
+            return;
+        }
+        *current = meetings;
+    }
+    refresh(app);
+}
+
+fn meetings(app: &tauri::AppHandle) -> Vec<TrayMeeting> {
+    app.try_state::<TrayMeetings>()
+        .map(|state| state.inner().0.lock().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// "10:00  Standup", with the date for meetings on other days. Menus treat
+/// `&` as a mnemonic marker, so it is doubled.
+fn meeting_label(meeting: &TrayMeeting, now: DateTime<Utc>, is_ja: bool) -> String {
+    let start = meeting.start.with_timezone(&Local);
+    let time = if start.date_naive() == now.with_timezone(&Local).date_naive() {
+        start.format("%H:%M").to_string()
+    } else {
+        start.format("%-m/%-d %H:%M").to_string()
+    };
+    let summary = match meeting.summary.trim() {
+        "" if is_ja => "（タイトルなし）".to_string(),
+        "" => "(No title)".to_string(),
+        s if s.chars().count() > MAX_TITLE_CHARS => {
+            format!("{}…", s.chars().take(MAX_TITLE_CHARS).collect::<String>())
+        }
+        s => s.to_string(),
+    };
+    format!("{}  {}", time, summary).replace('&', "&&")
+}
+
+/// macOS only; the app runs nowhere else.
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut child = std::process::Command::new("pbcopy")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    child.wait().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+const PAUSE_ITEMS: [(&str, PauseLength); 3] = [
+    ("pause_one_hour", PauseLength::OneHour),
+    ("pause_until_tomorrow", PauseLength::UntilTomorrow),
+    ("pause_until_resumed", PauseLength::UntilResumed),
+];
+
+fn pause_label(length: PauseLength, is_ja: bool) -> &'static str {
+    match (length, is_ja) {
+        (PauseLength::OneHour, true) => "1時間停止",
+        (PauseLength::OneHour, false) => "Pause for 1 Hour",
+        (PauseLength::UntilTomorrow, true) => "明日まで停止",
+        (PauseLength::UntilTomorrow, false) => "Pause Until Tomorrow",
+        (PauseLength::UntilResumed, true) => "再開するまで停止",
+        (PauseLength::UntilResumed, false) => "Pause Until I Resume",
+    }
+}
+
+/// The tray icon's right-click menu, built from the current settings and
+/// the meetings last handed over by the scheduler.
+pub fn build(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let is_ja = is_japanese_locale();
+    let settings = settings::get(app);
+    let now = Utc::now();
+    let mut menu = MenuBuilder::new(app);
+
+    // Upcoming meetings: click to join, or copy / open them from the submenus
+    if settings.tray_meeting_count > 0 {
+        let meetings = meetings(app);
+        if meetings.is_empty() {
+            let label = if is_ja { "予定されている会議はありません" } else { "No Upcoming Meetings" };
+            menu = menu.item(&MenuItemBuilder::new(label).enabled(false).build(app)?);
+        }
+        for meeting in &meetings {
+            let label = match &meeting.service {
+                Some(service) => format!("{} · {}", meeting_label(meeting, now, is_ja), service),
+                None => meeting_label(meeting, now, is_ja),
+            };
+            let id = format!("{}{}", JOIN_PREFIX, meeting.event_id);
+            let item = MenuItemBuilder::with_id(id, label)
+                .enabled(meeting.url.is_some())
+                .build(app)?;
+            menu = menu.item(&item);
+        }
+
+        let submenus = [
+            (COPY_LINK_PREFIX, if is_ja { "リンクをコピー" } else { "Copy Link" }),
+            (COPY_DIAL_IN_PREFIX, if is_ja { "ダイヤルインをコピー" } else { "Copy Dial-In" }),
+            (OPEN_CALENDAR_PREFIX, if is_ja { "カレンダーで開く" } else { "Open in Calendar" }),
+        ];
+        let mut separated = false;
+        for (prefix, title) in submenus {
+            let available: Vec<&TrayMeeting> = meetings
+                .iter()
+                .filter(|m| match prefix {
+                    COPY_LINK_PREFIX => m.url.is_some(),
+                    COPY_DIAL_IN_PREFIX => m.dial_in.is_some(),
+                    _ => m.external_url.is_some(),
+                })
+                .collect();
+            if available.is_empty() {
+                continue;
+            }
+            if !separated {
+                menu = menu.separator();
+                separated = true;
+            }
+            let mut submenu = SubmenuBuilder::new(app, title);
+            for meeting in available {
+                let item = MenuItemBuilder::with_id(
+                    format!("{}{}", prefix, meeting.event_id),
+                    meeting_label(meeting, now, is_ja),
+                )
+                .build(app)?;
+                submenu = submenu.item(&item);
+            }
+            menu = menu.item(&submenu.build()?);
+        }
+        menu = menu.separator();
+    }
+
+    if !settings.profiles.is_empty() {
+        let mut submenu = SubmenuBuilder::new(app, if is_ja { "プロファイル" } else { "Profile" });
+        for profile in &settings.profiles {
+            let item = CheckMenuItemBuilder::with_id(
+                format!("{}{}", PROFILE_PREFIX, profile.name),
+                &profile.name,
+            )
+            .checked(settings.active_profile.as_deref() == Some(profile.name.as_str()))
+            .build(app)?;
+            submenu = submenu.item(&item);
+        }
+        menu = menu.item(&submenu.build()?).separator();
+    }
+
+    // Pausing only stops auto-open; the menu offers the lengths, or resuming
+    let paused = pause::active(app, now);
+    if let Some(p) = paused {
+        let status = match p {
+            Pause::Indefinite if is_ja => "⏸ 自動オープン停止中".to_string(),
+            Pause::Indefinite => "⏸ Auto-open paused".to_string(),
+            Pause::Until { .. } => p.tray_label(now, is_ja),
+        };
+        let status = MenuItemBuilder::new(status).enabled(false).build(app)?;
+        let resume_label = if is_ja { "自動オープンを再開" } else { "Resume Auto-Open" };
+        let resume = MenuItemBuilder::with_id("resume", resume_label).build(app)?;
+        menu = menu.items(&[&status, &resume]);
+    }
+    for (id, length) in PAUSE_ITEMS {
+        let item = MenuItemBuilder::with_id(id, pause_label(length, is_ja))
+            .enabled(paused != Some(Pause::Indefinite) || length != PauseLength::UntilResumed)
+            .build(app)?;
+        menu = menu.item(&item);
+    }
+    menu = menu.separator();
+
+    let coffee_label = if is_ja { "開発者にコーヒーを ☕" } else { "Buy Me a Coffee ☕" };
+    let coffee = MenuItemBuilder::with_id("buy_me_a_coffee", coffee_label).build(app)?;
+    let quit_label = if is_ja { "Galopen を終了" } else { "Quit Galopen" };
+    let quit = MenuItemBuilder::with_id("quit", quit_label).build(app)?;
+    menu.items(&[&coffee]).separator().items(&[&quit]).build()
+}
+
+/// Rebuild the menu after what it shows changed.
+pub fn refresh(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    match build(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                log::warn!("Failed to set tray menu: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to build tray menu: {}", e),
+    }
+}
+
+pub fn handle_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        "quit" => app.exit(0),
+        "buy_me_a_coffee" => {
+            if let Err(e) = open::that("https://buymeacoffee.com/piro0919") {
+                log::warn!("Failed to open Buy Me a Coffee URL: {}", e);
+            }
+        }
+        "resume" => {
+            if let Err(e) = pause::set(app, None) {
+                log::warn!("Failed to resume auto-open: {}", e);
+            }
+        }
+        _ => {
+            if let Some((_, length)) = PAUSE_ITEMS.iter().find(|(item, _)| *item == id) {
+                if let Err(e) = pause::set(app, Some(Pause::starting(*length, Utc::now()))) {
+                    log::warn!("Failed to pause auto-open: {}", e);
+                }
+            } else if let Some(name) = id.strip_prefix(PROFILE_PREFIX) {
+                if let Err(e) = profiles::switch(app, name) {
+                    log::warn!("Failed to switch profile: {}", e);
+                }
+            } else {
+                handle_meeting_event(app, id);
+            }
+        }
+    }
+}
+
+fn handle_meeting_event(app: &tauri::AppHandle, id: &str) {
+    let find = |prefix: &str| {
+        let event_id = id.strip_prefix(prefix)?;
+        meetings(app).into_iter().find(|m| m.event_id == event_id)
+    };
+    if let Some(meeting) = find(JOIN_PREFIX) {
+        // Same path as the window's join button (`open_meeting_url`)
+        if let Some(url) = &meeting.url {
+            meeting_url::open_for_account(app, url, meeting.account.as_deref());
+        }
+    } else if let Some(meeting) = find(COPY_LINK_PREFIX) {
+        if let Some(url) = &meeting.url {
+            if let Err(e) = copy_to_clipboard(url) {
+                log::warn!("Failed to copy meeting link: {}", e);
+            }
+        }
+    } else if let Some(meeting) = find(COPY_DIAL_IN_PREFIX) {
+        if let Some(dial_in) = &meeting.dial_in {
+            if let Err(e) = copy_to_clipboard(dial_in) {
+                log::warn!("Failed to copy dial-in: {}", e);
+            }
+        }
+    } else if let Some(meeting) = find(OPEN_CALENDAR_PREFIX) {
+        if let Some(url) = &meeting.external_url {
+            if let Err(e) = open::that(url) {
+                log::warn!("Failed to open event in calendar: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::EventDateTime;
+    use crate::meeting_url::MeetingLink;
+
+    fn event(id: &str, start: &str, end: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            summary: id.to_string(),
+            start: EventDateTime { date_time: Some(start.to_string()), date: None },
+            end: EventDateTime { date_time: Some(end.to_string()), date: None },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lists_next_meetings_that_have_not_ended() {
+        let mut zoom = event("zoom", "2024-05-01T11:00:00Z", "2024-05-01T11:30:00Z");
+        zoom.meeting = Some(MeetingLink {
+            service: "zoom".to_string(),
+            name: "Zoom".to_string(),
+            url: "https://zoom.us/j/123".to_string(),
+        });
+        zoom.location = Some("+1 669 900 6833,,123# US".to_string());
+        let mut all_day = event("all_day", "2024-05-01T00:00:00Z", "2024-05-02T00:00:00Z");
+        all_day.is_all_day = true;
+        let events = [
+            zoom,
+            event("ended", "2024-05-01T09:00:00Z", "2024-05-01T09:30:00Z"),
+            event("running", "2024-05-01T09:45:00Z", "2024-05-01T10:15:00Z"),
+            all_day,
+            event("later", "2024-05-01T15:00:00Z", "2024-05-01T16:00:00Z"),
+        ];
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().to_utc();
+
+        let listed = upcoming(&ProviderRegistry::builtin(), &events, now, 2, false);
+        let ids: Vec<&str> = listed.iter().map(|m| m.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["running", "zoom"]);
+        assert_eq!(listed[1].service.as_deref(), Some("Zoom"));
+        assert_eq!(listed[1].dial_in.as_deref(), Some("+1 669 900 6833,,123# (US)"));
+        assert_eq!(listed[0].url, None);
+    }
+}
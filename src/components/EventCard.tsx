@@ -1,21 +1,11 @@
 import { openUrl } from "@tauri-apps/plugin-opener";
-import { Check, Copy, ExternalLink, Video } from "lucide-react";
+import { AlertTriangle, Check, Copy, ExternalLink, Phone, Video } from "lucide-react";
 import { useEffect, useState } from "react";
 import { t } from "../i18n";
-import { openMeetingUrl } from "../lib/tauri";
-import type { CalendarEvent } from "../types";
+import { getMeetingInfo as fetchMeetingInfo, openMeetingUrl } from "../lib/tauri";
+import type { CalendarEvent, MeetingInfo } from "../types";
 import { calendarColor } from "./CalendarFilter";
 
-const URL_PATTERN =
-  /https?:\/\/(?:[a-z0-9-]+\.)*(?:zoom\.us|meet\.google\.com|teams\.microsoft\.com|webex\.com)\S*/i;
-
-const SERVICES: [RegExp, string][] = [
-  [/zoom\.us/i, "Zoom"],
-  [/meet\.google\.com/i, "Meet"],
-  [/teams\.microsoft\.com/i, "Teams"],
-  [/webex\.com/i, "Webex"],
-];
-
 function formatTimeRange(event: CalendarEvent): string {
   const startDt = event.start.dateTime;
   if (!startDt) return t.allDay;
@@ -39,33 +29,38 @@ function getMinutesUntil(event: CalendarEvent): number | null {
 export function getMeetingInfo(
   event: CalendarEvent,
 ): { type: string; url: string } | null {
-  if (event.url) {
-    for (const [re, name] of SERVICES) {
-      if (re.test(event.url)) return { type: name, url: event.url };
-    }
-  }
+  // Detection happens in Rust (meeting_url registry) so custom providers show up too
+  return event.meeting ? { type: event.meeting.name, url: event.meeting.url } : null;
+}
 
-  const text = `${event.location ?? ""} ${event.description ?? ""}`;
-  const match = text.match(URL_PATTERN);
-  if (match) {
-    for (const [re, name] of SERVICES) {
-      if (re.test(match[0])) return { type: name, url: match[0] };
-    }
+/** Text to paste into a phone: first dial-in with PIN, then meeting id and passcode. */
+export function formatDialIn(info: MeetingInfo): string {
+  const [first] = info.dialIns;
+  const lines: string[] = [];
+  if (first) {
+    const pin = first.pin ? `,,${first.pin.replace(/\s/g, "")}#` : "";
+    lines.push(`${first.number}${pin}${first.label ? ` (${first.label})` : ""}`);
   }
-
-  return null;
+  if (info.meetingId) lines.push(`${t.meetingIdLabel}: ${info.meetingId}`);
+  if (info.passcode) lines.push(`${t.passcodeLabel}: ${info.passcode}`);
+  return lines.join("\n");
 }
 
 export function EventCard({
   event,
   isNext,
+  conflict,
 }: {
   event: CalendarEvent;
   isNext?: boolean;
+  /** Overlaps another meeting in the schedule */
+  conflict?: boolean;
 }) {
   const [hovered, setHovered] = useState(false);
   const [badgeHovered, setBadgeHovered] = useState(false);
   const [copied, setCopied] = useState(false);
+  const [dialInCopied, setDialInCopied] = useState(false);
+  const [details, setDetails] = useState<MeetingInfo | null>(null);
   const [minutesLeft, setMinutesLeft] = useState<number | null>(() =>
     isNext ? getMinutesUntil(event) : null,
   );
@@ -73,6 +68,7 @@ export function EventCard({
   const time = formatTimeRange(event);
   const meeting = getMeetingInfo(event);
   const color = calendarColor(event.calendarName ?? "");
+  const hasDialIn = (details?.dialIns.length ?? 0) > 0;
 
   useEffect(() => {
     if (!isNext || !event.start.dateTime) return;
@@ -82,6 +78,17 @@ export function EventCard({
     return () => clearInterval(id);
   }, [isNext, event]);
 
+  useEffect(() => {
+    fetchMeetingInfo(event).then(setDetails).catch(() => setDetails(null));
+  }, [event]);
+
+  const handleCopyDialIn = async () => {
+    if (!details || !hasDialIn) return;
+    await navigator.clipboard.writeText(formatDialIn(details));
+    setDialInCopied(true);
+    setTimeout(() => setDialInCopied(false), 2000);
+  };
+
   const handleCopy = async () => {
     if (!meeting) return;
     await navigator.clipboard.writeText(meeting.url);
@@ -134,8 +141,13 @@ export function EventCard({
           ) : (
             <div style={styles.summary}>{event.summary || t.noTitle}</div>
           )}
+          {conflict && (
+            <span title={t.overlapsOtherMeeting} style={styles.conflict}>
+              <AlertTriangle size={12} strokeWidth={1.75} />
+            </span>
+          )}
         </div>
-        {(countdownText || meeting) && (
+        {(countdownText || meeting || hasDialIn) && (
           <div style={styles.row2}>
             {countdownText && (
               <span style={{ ...styles.countdown, ...countdownStyle }}>
@@ -174,6 +186,23 @@ export function EventCard({
                 )}
               </button>
             )}
+            {hasDialIn && (
+              <button
+                type="button"
+                onClick={handleCopyDialIn}
+                title={dialInCopied ? t.copied : t.copyDialIn}
+                style={{
+                  ...styles.copyBtn,
+                  ...(dialInCopied ? styles.copyBtnDone : {}),
+                }}
+              >
+                {dialInCopied ? (
+                  <Check size={12} strokeWidth={2} />
+                ) : (
+                  <Phone size={12} strokeWidth={1.75} />
+                )}
+              </button>
+            )}
           </div>
         )}
       </div>
@@ -276,6 +305,11 @@ const styles: Record<string, React.CSSProperties> = {
     background: "var(--countdown-urgent-bg)",
     color: "var(--countdown-urgent-text)",
   },
+  conflict: {
+    display: "flex",
+    flexShrink: 0,
+    color: "var(--countdown-soon-text)",
+  },
   badge: {
     display: "inline-flex",
     alignItems: "center",
@@ -37,9 +37,11 @@ function formatDateLabel(dateKey: string): string {
 export function EventList({
   events,
   loading,
+  conflictIds,
 }: {
   events: CalendarEvent[];
   loading: boolean;
+  conflictIds?: Set<string>;
 }) {
   const todayStrInit = new Date().toLocaleDateString("sv-SE");
 
@@ -107,6 +109,7 @@ export function EventList({
                   key={event.id}
                   event={event}
                   isNext={nextIds.has(event.id)}
+                  conflict={conflictIds?.has(event.id)}
                 />
               ))
             )}
@@ -1,23 +1,75 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { getVersion } from "@tauri-apps/api/app";
-import { Bell, CalendarRange, Clock, Coffee, Globe, LogOut, Power, Sun } from "lucide-react";
+import {
+  Bell,
+  CalendarRange,
+  Clock,
+  Coffee,
+  FileText,
+  Globe,
+  Layers,
+  List,
+  LogOut,
+  Power,
+  Share2,
+  Sun,
+  Trash2,
+  UserX,
+  Users,
+  Zap,
+} from "lucide-react";
+import { message, open, save as saveDialog } from "@tauri-apps/plugin-dialog";
 import { openUrl } from "@tauri-apps/plugin-opener";
 import { t } from "../i18n";
-import { load } from "@tauri-apps/plugin-store";
 import { enable, disable } from "@tauri-apps/plugin-autostart";
-import { getInstalledApps, type AppOption } from "../lib/tauri";
+import {
+  deleteProfile,
+  exportSettings,
+  getCalendars,
+  getInstalledApps,
+  getMeetingProviders,
+  getSettings,
+  importSettings,
+  onSettingsChanged,
+  saveProfile,
+  switchProfile,
+  updateSettings,
+  type AppOption,
+} from "../lib/tauri";
+import type { AppSettings, CalendarInfo, ConflictPolicy, MeetingProvider } from "../types";
 import type { DisplayRange } from "../hooks/useDisplaySettings";
 
 const MINUTE_OPTIONS = [1, 2, 3, 5, 10];
 const NOTIFY_OPTIONS = [0, 1, 2, 3, 5, 10, 15, 30];
 const TRAY_COUNTDOWN_OPTIONS = [15, 30, 60, 90, 0]; // 0 = always
-const MEETING_SERVICES = [
-  { key: "googleMeet", label: "Google Meet" },
-  { key: "zoom", label: "Zoom" },
-  { key: "teams", label: "Teams" },
-  { key: "webex", label: "Webex" },
-] as const;
+const TRAY_MEETING_OPTIONS = [0, 3, 5, 10]; // 0 = hide the list
+
+// Settings read by InvitationFilter in calendar.rs; defaults apply until loaded
+type InvitationKey =
+  | "skipDeclined"
+  | "skipTentative"
+  | "skipNeedsAction"
+  | "hideSkippedInvitations";
+
+const INVITATION_OPTIONS: {
+  key: InvitationKey;
+  label: () => string;
+  default: boolean;
+}[] = [
+  { key: "skipDeclined", label: () => t.skipDeclined, default: true },
+  { key: "skipTentative", label: () => t.skipTentative, default: false },
+  { key: "skipNeedsAction", label: () => t.skipNeedsAction, default: false },
+  { key: "hideSkippedInvitations", label: () => t.hideSkippedInvitations, default: false },
+];
+
+// `conflictPolicy` values, see ConflictPolicy in conflicts.rs
+const CONFLICT_POLICIES: { value: ConflictPolicy; label: () => string }[] = [
+  { value: "preferAccepted", label: () => t.conflictPreferAccepted },
+  { value: "preferOrganizer", label: () => t.conflictPreferOrganizer },
+  { value: "preferCalendar", label: () => t.conflictPreferCalendar },
+  { value: "ask", label: () => t.conflictAsk },
+];
 
 export function Settings({
   autostart,
@@ -26,6 +78,7 @@ export function Settings({
   onDisplayRangeChange,
   weekdaysOnly,
   onWeekdaysOnlyChange,
+  onEventFilterChange,
 }: {
   autostart: boolean | null;
   onAutostartChange: (value: boolean) => void;
@@ -33,57 +86,193 @@ export function Settings({
   onDisplayRangeChange: (value: DisplayRange) => void;
   weekdaysOnly: boolean;
   onWeekdaysOnlyChange: (value: boolean) => void;
+  onEventFilterChange: () => void;
 }) {
   const [minutesBefore, setMinutesBefore] = useState(1);
   const [notifyMinutes, setNotifyMinutes] = useState(5);
+  const [autoOpen, setAutoOpen] = useState(true);
+  const [pauseKeepsReminders, setPauseKeepsReminders] = useState(true);
+  const [profileNames, setProfileNames] = useState<string[]>([]);
+  const [activeProfile, setActiveProfile] = useState("");
+  const [newProfileName, setNewProfileName] = useState("");
   const [trayCountdown, setTrayCountdown] = useState(30);
+  const [trayMeetings, setTrayMeetings] = useState(5);
   const [quitHovered, setQuitHovered] = useState(false);
   const [version, setVersion] = useState("");
   const [installedApps, setInstalledApps] = useState<AppOption[]>([]);
   const [openWith, setOpenWith] = useState<Record<string, string>>({});
+  const [providers, setProviders] = useState<MeetingProvider[]>([]);
+  const [invitations, setInvitations] = useState<Record<string, boolean>>(() =>
+    Object.fromEntries(INVITATION_OPTIONS.map((o) => [o.key, o.default]))
+  );
+
+  const [conflictPolicy, setConflictPolicy] = useState<ConflictPolicy>("preferAccepted");
+  const [conflictCalendarId, setConflictCalendarId] = useState("");
+  const [calendars, setCalendars] = useState<CalendarInfo[]>([]);
+  const [syncFilePath, setSyncFilePath] = useState<string | null>(null);
+
+  const applySettings = (s: AppSettings) => {
+    setMinutesBefore(s.minutesBefore);
+    setNotifyMinutes(s.notificationMinutesBefore);
+    setAutoOpen(s.autoOpen);
+    setPauseKeepsReminders(s.pauseKeepsReminders);
+    setProfileNames(s.profiles.map((p) => p.name));
+    setActiveProfile(s.activeProfile ?? "");
+    setTrayCountdown(s.trayCountdownMinutes);
+    setTrayMeetings(s.trayMeetingCount);
+    setOpenWith(s.openWith);
+    setInvitations(Object.fromEntries(INVITATION_OPTIONS.map(({ key }) => [key, s[key]])));
+    setConflictPolicy(s.conflictPolicy);
+    setConflictCalendarId(s.conflictCalendarId ?? "");
+    setSyncFilePath(s.syncFilePath);
+  };
 
   useEffect(() => {
-    load("settings.json").then(async (store) => {
-      const val = (await store.get("minutesBefore")) as number | undefined;
-      if (val != null) setMinutesBefore(val);
-      const notif = (await store.get("notificationMinutesBefore")) as number | undefined;
-      if (notif != null) setNotifyMinutes(notif);
-      const tray = (await store.get("trayCountdownMinutes")) as number | undefined;
-      if (tray != null) setTrayCountdown(tray);
-      const ow = (await store.get("openWith")) as Record<string, string> | undefined;
-      if (ow) setOpenWith(ow);
-    });
+    getSettings()
+      .then(applySettings)
+      .catch((e) => console.error("[galopen] getSettings error:", e));
     getVersion().then(setVersion).catch(() => {});
     getInstalledApps().then(setInstalledApps).catch(() => {});
+    getMeetingProviders().then(setProviders).catch(() => {});
+    getCalendars().then(setCalendars).catch(() => {});
+    // Imports and the sync file change settings behind our back
+    const unlisten = onSettingsChanged(applySettings);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
+  // The backend validates, saves and wakes the scheduler
+  const save = async (patch: Partial<AppSettings>) => {
+    try {
+      await updateSettings(patch);
+    } catch (e) {
+      console.error("[galopen] updateSettings error:", e);
+    }
+  };
+
   const handleChange = async (value: number) => {
     setMinutesBefore(value);
-    const store = await load("settings.json");
-    await store.set("minutesBefore", value);
-    await store.save();
+    await save({ minutesBefore: value });
+  };
+
+  const handleAutoOpen = async (value: boolean) => {
+    setAutoOpen(value);
+    await save({ autoOpen: value });
+  };
+
+  const handlePauseKeepsReminders = async (value: boolean) => {
+    setPauseKeepsReminders(value);
+    await save({ pauseKeepsReminders: value });
+  };
+
+  // Profile changes come back through onSettingsChanged
+  const handleSwitchProfile = async (name: string) => {
+    try {
+      await switchProfile(name);
+    } catch (e) {
+      console.error("[galopen] switchProfile error:", e);
+    }
+  };
+
+  const handleSaveProfile = async () => {
+    const name = newProfileName.trim();
+    if (!name) return;
+    try {
+      await saveProfile(name);
+      setNewProfileName("");
+    } catch (e) {
+      console.error("[galopen] saveProfile error:", e);
+    }
+  };
+
+  const handleDeleteProfile = async () => {
+    try {
+      await deleteProfile(activeProfile);
+    } catch (e) {
+      console.error("[galopen] deleteProfile error:", e);
+    }
   };
 
   const handleNotifyChange = async (value: number) => {
     setNotifyMinutes(value);
-    const store = await load("settings.json");
-    await store.set("notificationMinutesBefore", value);
-    await store.save();
+    await save({ notificationMinutesBefore: value });
   };
 
   const handleTrayCountdown = async (value: number) => {
     setTrayCountdown(value);
-    const store = await load("settings.json");
-    await store.set("trayCountdownMinutes", value);
-    await store.save();
+    await save({ trayCountdownMinutes: value });
+  };
+
+  const handleTrayMeetings = async (value: number) => {
+    setTrayMeetings(value);
+    await save({ trayMeetingCount: value });
+  };
+
+  const handleInvitation = async (key: InvitationKey, value: boolean) => {
+    setInvitations((prev) => ({ ...prev, [key]: value }));
+    await save({ [key]: value });
+    onEventFilterChange();
+  };
+
+  const handleConflictPolicy = async (value: ConflictPolicy) => {
+    setConflictPolicy(value);
+    await save({ conflictPolicy: value });
+    onEventFilterChange();
+  };
+
+  const handleConflictCalendar = async (value: string) => {
+    setConflictCalendarId(value);
+    await save({ conflictCalendarId: value || null });
+    onEventFilterChange();
   };
 
   const handleOpenWith = async (service: string, appId: string) => {
-    const next = { ...openWith, [service]: appId };
+    const next = { ...openWith };
+    if (appId === "default") delete next[service];
+    else next[service] = appId;
     setOpenWith(next);
-    const store = await load("settings.json");
-    await store.set("openWith", next);
-    await store.save();
+    await save({ openWith: next });
+  };
+
+  const handleExport = async () => {
+    const path = await saveDialog({
+      defaultPath: "galopen-settings.json",
+      filters: [{ name: "JSON", extensions: ["json"] }],
+    });
+    if (!path) return;
+    try {
+      await exportSettings(path);
+    } catch (e) {
+      await message(String(e), { kind: "error" });
+    }
+  };
+
+  const handleImport = async () => {
+    const path = await open({ filters: [{ name: "JSON", extensions: ["json"] }] });
+    if (!path) return;
+    try {
+      const report = await importSettings(path);
+      const skipped = report.issues.map((i) => `${i.key}: ${i.message}`);
+      await message(
+        skipped.length === 0
+          ? t.settingsImported
+          : [t.settingsImported, t.settingsSkippedKeys, ...skipped].join("\n"),
+        { kind: skipped.length === 0 ? "info" : "warning" }
+      );
+      onEventFilterChange();
+    } catch (e) {
+      await message(String(e), { kind: "error" });
+    }
+  };
+
+  const handleSyncFile = async (choose: boolean) => {
+    const path = choose
+      ? await open({ filters: [{ name: "JSON", extensions: ["json"] }] })
+      : null;
+    if (choose && !path) return;
+    setSyncFilePath(path);
+    await save({ syncFilePath: path });
   };
 
   const handleAutostart = async () => {
@@ -103,6 +292,93 @@ export function Settings({
   return (
     <div style={styles.container}>
       <div style={styles.row}>
+        <div style={styles.labelRow}>
+          <Users size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.profileLabel}</span>
+        </div>
+        <div style={styles.buttonGroup}>
+          <select
+            value={activeProfile}
+            onChange={(e) => handleSwitchProfile(e.target.value)}
+            disabled={profileNames.length === 0}
+            style={styles.select}
+          >
+            {activeProfile === "" && <option value="">{t.profileNone}</option>}
+            {profileNames.map((name) => (
+              <option key={name} value={name}>
+                {name}
+              </option>
+            ))}
+          </select>
+          {activeProfile && (
+            <button
+              type="button"
+              onClick={handleDeleteProfile}
+              style={styles.smallBtn}
+              title={t.profileDelete}
+            >
+              <Trash2 size={12} strokeWidth={1.75} />
+            </button>
+          )}
+        </div>
+      </div>
+      <div style={{ ...styles.row, marginTop: 8, paddingLeft: 20 }}>
+        <input
+          value={newProfileName}
+          onChange={(e) => setNewProfileName(e.target.value)}
+          onKeyDown={(e) => e.key === "Enter" && handleSaveProfile()}
+          placeholder={t.profileNamePlaceholder}
+          style={{ ...styles.select, flex: 1, minWidth: 0, cursor: "text" }}
+        />
+        <button
+          type="button"
+          onClick={handleSaveProfile}
+          disabled={!newProfileName.trim()}
+          style={styles.smallBtn}
+        >
+          {t.profileSave}
+        </button>
+      </div>
+      <div style={{ ...styles.row, marginTop: 12 }}>
+        <div style={styles.labelRow}>
+          <Zap size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.autoOpen}</span>
+        </div>
+        <button
+          type="button"
+          onClick={() => handleAutoOpen(!autoOpen)}
+          style={{
+            ...styles.toggle,
+            ...(autoOpen ? styles.toggleOn : styles.toggleOff),
+          }}
+        >
+          <div
+            style={{
+              ...styles.toggleKnob,
+              ...(autoOpen ? styles.knobOn : styles.knobOff),
+            }}
+          />
+        </button>
+      </div>
+      <div style={{ ...styles.row, marginTop: 8, paddingLeft: 20 }}>
+        <span style={styles.serviceLabel}>{t.pauseKeepsReminders}</span>
+        <button
+          type="button"
+          onClick={() => handlePauseKeepsReminders(!pauseKeepsReminders)}
+          style={{
+            ...styles.toggle,
+            ...(pauseKeepsReminders ? styles.toggleOn : styles.toggleOff),
+          }}
+        >
+          <div
+            style={{
+              ...styles.toggleKnob,
+              ...(pauseKeepsReminders ? styles.knobOn : styles.knobOff),
+            }}
+          />
+        </button>
+      </div>
+      <div style={{ ...styles.row, marginTop: 12 }}>
         <div style={styles.labelRow}>
           <Bell size={14} strokeWidth={1.75} color="var(--text-secondary)" />
           <span style={styles.label}>{t.openBefore}</span>
@@ -189,13 +465,90 @@ export function Settings({
           ))}
         </select>
       </div>
+      <div style={{ ...styles.row, marginTop: 12 }}>
+        <div style={styles.labelRow}>
+          <List size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.trayMeetings}</span>
+        </div>
+        <select
+          value={trayMeetings}
+          onChange={(e) => handleTrayMeetings(Number(e.target.value))}
+          style={styles.select}
+        >
+          {TRAY_MEETING_OPTIONS.map((n) => (
+            <option key={n} value={n}>
+              {n === 0 ? t.trayMeetingsOff : t.trayMeetingsCount(n)}
+            </option>
+          ))}
+        </select>
+      </div>
+      <div style={{ marginTop: 16 }}>
+        <div style={styles.labelRow}>
+          <UserX size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.invitationsLabel}</span>
+        </div>
+        {INVITATION_OPTIONS.map(({ key, label }) => (
+          <div key={key} style={{ ...styles.row, marginTop: 8, paddingLeft: 20 }}>
+            <span style={styles.serviceLabel}>{label()}</span>
+            <button
+              type="button"
+              onClick={() => handleInvitation(key, !invitations[key])}
+              style={{
+                ...styles.toggle,
+                ...(invitations[key] ? styles.toggleOn : styles.toggleOff),
+              }}
+            >
+              <div
+                style={{
+                  ...styles.toggleKnob,
+                  ...(invitations[key] ? styles.knobOn : styles.knobOff),
+                }}
+              />
+            </button>
+          </div>
+        ))}
+      </div>
+      <div style={{ ...styles.row, marginTop: 12 }}>
+        <div style={styles.labelRow}>
+          <Layers size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.conflictPolicyLabel}</span>
+        </div>
+        <select
+          value={conflictPolicy}
+          onChange={(e) => handleConflictPolicy(e.target.value as ConflictPolicy)}
+          style={styles.select}
+        >
+          {CONFLICT_POLICIES.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {label()}
+            </option>
+          ))}
+        </select>
+      </div>
+      {conflictPolicy === "preferCalendar" && (
+        <div style={{ ...styles.row, marginTop: 8, paddingLeft: 20 }}>
+          <span style={styles.serviceLabel}>{t.conflictCalendarLabel}</span>
+          <select
+            value={conflictCalendarId}
+            onChange={(e) => handleConflictCalendar(e.target.value)}
+            style={{ ...styles.select, width: 160 }}
+          >
+            <option value="">-</option>
+            {calendars.map((c) => (
+              <option key={c.id} value={c.id}>
+                {c.title}
+              </option>
+            ))}
+          </select>
+        </div>
+      )}
       {installedApps.length > 0 && (
         <div style={{ marginTop: 16 }}>
           <div style={styles.labelRow}>
             <Globe size={14} strokeWidth={1.75} color="var(--text-secondary)" />
             <span style={styles.label}>{t.openWithLabel}</span>
           </div>
-          {MEETING_SERVICES.map(({ key, label }) => {
+          {providers.map(({ id: key, name: label }) => {
             const availableApps = installedApps.filter(
               (app) => app.serviceHint === null || app.serviceHint === key
             );
@@ -219,6 +572,44 @@ export function Settings({
           })}
         </div>
       )}
+      <div style={{ ...styles.row, marginTop: 12 }}>
+        <div style={styles.labelRow}>
+          <Share2 size={14} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.label}>{t.shareSettingsLabel}</span>
+        </div>
+        <div style={styles.buttonGroup}>
+          <button type="button" onClick={handleExport} style={styles.smallBtn}>
+            {t.exportSettings}
+          </button>
+          <button type="button" onClick={handleImport} style={styles.smallBtn}>
+            {t.importSettings}
+          </button>
+        </div>
+      </div>
+      <div style={{ ...styles.row, marginTop: 8, paddingLeft: 20 }}>
+        <div style={styles.labelRow}>
+          <FileText size={12} strokeWidth={1.75} color="var(--text-secondary)" />
+          <span style={styles.serviceLabel}>{t.syncFileLabel}</span>
+        </div>
+        <span style={styles.path} title={syncFilePath ?? undefined}>
+          {syncFilePath ? syncFilePath.split("/").pop() : t.syncFileNone}
+        </span>
+        <div style={styles.buttonGroup}>
+          <button type="button" onClick={() => handleSyncFile(true)} style={styles.smallBtn}>
+            {t.syncFileChoose}
+          </button>
+          {syncFilePath && (
+            <button
+              type="button"
+              onClick={() => handleSyncFile(false)}
+              style={styles.smallBtn}
+              aria-label={t.close}
+            >
+              ×
+            </button>
+          )}
+        </div>
+      </div>
       <div style={{ ...styles.row, marginTop: 12 }}>
         <div style={styles.labelRow}>
           <Power size={14} strokeWidth={1.75} color="var(--text-secondary)" />
@@ -313,6 +704,28 @@ const styles: Record<string, React.CSSProperties> = {
     cursor: "pointer",
     outline: "none",
   },
+  buttonGroup: {
+    display: "flex",
+    gap: 6,
+  },
+  smallBtn: {
+    fontSize: 12,
+    padding: "4px 10px",
+    borderRadius: 6,
+    border: "1px solid var(--border-color)",
+    background: "var(--bg-card)",
+    color: "var(--text-primary)",
+    cursor: "pointer",
+  },
+  path: {
+    flex: 1,
+    minWidth: 0,
+    fontSize: 12,
+    color: "var(--text-tertiary)",
+    overflow: "hidden",
+    textOverflow: "ellipsis",
+    whiteSpace: "nowrap" as const,
+  },
   toggle: {
     width: 44,
     height: 24,
@@ -1,28 +1,56 @@
-import { useCallback, useEffect, useState } from "react";
-import { getCalendars } from "../lib/tauri";
+import { useCallback, useEffect, useRef, useState } from "react";
+import {
+  getCalendars,
+  getEnabledCalendars,
+  onSettingsChanged,
+  setEnabledCalendars,
+} from "../lib/tauri";
 import type { CalendarInfo } from "../types";
 
-const STORAGE_KEY = "galopen-enabled-calendars";
+// Older versions kept the selection in localStorage; it is migrated to the
+// Rust-side setting on first load
+const LEGACY_STORAGE_KEY = "galopen-enabled-calendars";
+
+function readLegacySelection(): string[] | null {
+  const saved = localStorage.getItem(LEGACY_STORAGE_KEY);
+  if (!saved) return null;
+  try {
+    return JSON.parse(saved);
+  } catch {
+    return null;
+  }
+}
 
 export function useCalendars() {
   const [calendars, setCalendars] = useState<CalendarInfo[]>([]);
   const [enabledIds, setEnabledIds] = useState<Set<string>>(new Set());
   const [loaded, setLoaded] = useState(false);
+  // Latest selection, so quick toggles build on each other instead of a
+  // stale render's copy
+  const enabledRef = useRef(enabledIds);
+  enabledRef.current = enabledIds;
 
   useEffect(() => {
-    getCalendars().then((cals) => {
+    Promise.all([getCalendars(), getEnabledCalendars()]).then(async ([cals, stored]) => {
       setCalendars(cals);
 
-      // Load saved selection from localStorage
-      const saved = localStorage.getItem(STORAGE_KEY);
-      if (saved) {
-        try {
-          const ids: string[] = JSON.parse(saved);
-          // Only keep IDs that still exist
-          const valid = new Set(ids.filter((id) => cals.some((c) => c.id === id)));
-          setEnabledIds(valid.size > 0 ? valid : new Set(cals.map((c) => c.id)));
-        } catch {
+      let ids = stored;
+      if (ids == null) {
+        ids = readLegacySelection();
+        if (ids != null) {
+          await setEnabledCalendars(ids);
+          localStorage.removeItem(LEGACY_STORAGE_KEY);
+        }
+      }
+
+      if (ids != null) {
+        // Only keep IDs that still exist
+        const valid = new Set(ids.filter((id) => cals.some((c) => c.id === id)));
+        if (valid.size > 0) {
+          setEnabledIds(valid);
+        } else {
           setEnabledIds(new Set(cals.map((c) => c.id)));
+          await setEnabledCalendars(cals.map((c) => c.id));
         }
       } else {
         // Default: all enabled
@@ -32,22 +60,30 @@ export function useCalendars() {
     });
   }, []);
 
-  const toggleCalendar = useCallback(
-    (id: string) => {
-      setEnabledIds((prev) => {
-        const next = new Set(prev);
-        if (next.has(id)) {
-          // Don't allow disabling all calendars
-          if (next.size > 1) next.delete(id);
-        } else {
-          next.add(id);
-        }
-        localStorage.setItem(STORAGE_KEY, JSON.stringify([...next]));
-        return next;
-      });
-    },
-    [],
-  );
+  // Switching profiles changes the selection from the Rust side
+  useEffect(() => {
+    const unlisten = onSettingsChanged((s) => {
+      const ids = s.enabledCalendars;
+      setEnabledIds(ids != null ? new Set(ids) : new Set(calendars.map((c) => c.id)));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [calendars]);
+
+  const toggleCalendar = useCallback(async (id: string) => {
+    const next = new Set(enabledRef.current);
+    if (next.has(id)) {
+      // Don't allow disabling all calendars
+      if (next.size > 1) next.delete(id);
+    } else {
+      next.add(id);
+    }
+    enabledRef.current = next;
+    setEnabledIds(next);
+    // The scheduler and tray read this too, so persist it on the Rust side
+    await setEnabledCalendars([...next]);
+  }, []);
 
   return { calendars, enabledIds, loaded, toggleCalendar };
 }
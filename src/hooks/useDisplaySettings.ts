@@ -1,5 +1,5 @@
 import { useEffect, useState } from "react";
-import { load } from "@tauri-apps/plugin-store";
+import { getSettings, onSettingsChanged, updateSettings } from "../lib/tauri";
 
 export type DisplayRange = 1 | 2 | 3;
 
@@ -12,28 +12,32 @@ export function useDisplaySettings() {
   const [loaded, setLoaded] = useState(false);
 
   useEffect(() => {
-    load("settings.json")
-      .then(async (store) => {
-        const r = (await store.get("displayRange")) as number | undefined;
-        if (r === 1 || r === 2 || r === 3) setRangeState(r);
-        const w = (await store.get("weekdaysOnly")) as boolean | undefined;
-        if (typeof w === "boolean") setWeekdaysOnlyState(w);
+    getSettings()
+      .then((s) => {
+        // The backend clamps displayRange to 1-3
+        setRangeState(s.displayRange);
+        setWeekdaysOnlyState(s.weekdaysOnly);
       })
+      .catch(() => {})
       .finally(() => setLoaded(true));
+    // e.g. after importing a settings file
+    const unlisten = onSettingsChanged((s) => {
+      setRangeState(s.displayRange);
+      setWeekdaysOnlyState(s.weekdaysOnly);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   const setRange = async (value: DisplayRange) => {
     setRangeState(value);
-    const store = await load("settings.json");
-    await store.set("displayRange", value);
-    await store.save();
+    await updateSettings({ displayRange: value });
   };
 
   const setWeekdaysOnly = async (value: boolean) => {
     setWeekdaysOnlyState(value);
-    const store = await load("settings.json");
-    await store.set("weekdaysOnly", value);
-    await store.save();
+    await updateSettings({ weekdaysOnly: value });
   };
 
   return { range, weekdaysOnly, loaded, setRange, setWeekdaysOnly };
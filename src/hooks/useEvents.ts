@@ -1,5 +1,5 @@
-import { useEffect, useState } from "react";
-import { forceSync, getTodaysEvents } from "../lib/tauri";
+import { useCallback, useEffect, useState } from "react";
+import { forceSync, getTodaysEvents, onEventsUpdated } from "../lib/tauri";
 import type { CalendarEvent } from "../types";
 
 export function useEvents() {
@@ -13,17 +13,28 @@ export function useEvents() {
       .catch((e) => console.error("[galopen] forceSync error:", e))
       .finally(() => setLoading(false));
 
-    // Re-read cached events every 60s
-    const interval = setInterval(async () => {
+    // The backend pushes a diff whenever a sync changes the cached events
+    const unlisten = onEventsUpdated(async () => {
       try {
-        const data = await getTodaysEvents();
-        setEvents(data);
+        setEvents(await getTodaysEvents());
       } catch {
         // ignore
       }
-    }, 60_000);
-    return () => clearInterval(interval);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Re-read cached events now, e.g. after the calendar filter changes
+  const refresh = useCallback(async () => {
+    try {
+      setEvents(await getTodaysEvents());
+    } catch {
+      // ignore
+    }
   }, []);
 
-  return { events, loading };
+  return { events, loading, refresh };
 }
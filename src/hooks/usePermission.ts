@@ -1,16 +1,13 @@
 import { useEffect, useRef, useState } from "react";
 import {
   checkCalendarPermission,
+  onPermissionChanged,
   openCalendarSettings,
   requestCalendarPermission,
 } from "../lib/tauri";
+import type { PermissionStatus as CalendarPermission } from "../types";
 
-type PermissionStatus =
-  | "loading"
-  | "granted"
-  | "denied"
-  | "not_determined"
-  | "restricted";
+type PermissionStatus = "loading" | CalendarPermission;
 
 export function usePermission() {
   const [status, setStatus] = useState<PermissionStatus>("loading");
@@ -20,9 +17,16 @@ export function usePermission() {
     checkCalendarPermission()
       .then((s) => setStatus(s as PermissionStatus))
       .catch(() => setStatus("not_determined"));
+
+    // Also catches access being revoked while the app runs
+    const unlisten = onPermissionChanged(({ status }) => setStatus(status));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
-  // Poll for permission changes when not granted
+  // Poll for permission changes when not granted; the backend only notices
+  // (and emits permission-changed) when someone checks
   useEffect(() => {
     if (status === "granted" || status === "loading") {
       if (pollingRef.current) {
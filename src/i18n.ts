@@ -29,6 +29,9 @@ interface Messages {
   now: string;
   copyUrl: string;
   copied: string;
+  copyDialIn: string;
+  meetingIdLabel: string;
+  passcodeLabel: string;
   trayMinSuffix: string;
   tomorrow: string;
   trayCountdown: string;
@@ -43,6 +46,39 @@ interface Messages {
   rangeUntilDayAfter: string;
   weekdaysOnly: string;
   weekend: string;
+  invitationsLabel: string;
+  skipDeclined: string;
+  skipTentative: string;
+  skipNeedsAction: string;
+  hideSkippedInvitations: string;
+  conflictPolicyLabel: string;
+  conflictPreferAccepted: string;
+  conflictPreferOrganizer: string;
+  conflictPreferCalendar: string;
+  conflictAsk: string;
+  conflictCalendarLabel: string;
+  overlapsOtherMeeting: string;
+  shareSettingsLabel: string;
+  profileLabel: string;
+  profileNone: string;
+  profileNamePlaceholder: string;
+  profileSave: string;
+  profileDelete: string;
+  autoOpen: string;
+  trayMeetings: string;
+  trayMeetingsOff: string;
+  trayMeetingsCount: (n: number) => string;
+  pauseKeepsReminders: string;
+  pausedUntil: (time: string) => string;
+  pausedIndefinitely: string;
+  resume: string;
+  exportSettings: string;
+  importSettings: string;
+  settingsImported: string;
+  settingsSkippedKeys: string;
+  syncFileLabel: string;
+  syncFileNone: string;
+  syncFileChoose: string;
   buyMeACoffee: string;
 }
 
@@ -90,6 +126,9 @@ const ja: Messages = {
   now: "開催中",
   copyUrl: "URLをコピー",
   copied: "コピー済み",
+  copyDialIn: "電話番号をコピー",
+  meetingIdLabel: "ミーティングID",
+  passcodeLabel: "パスコード",
   trayMinSuffix: "分",
   tomorrow: "明日の予定",
   trayCountdown: "トレイのカウントダウン",
@@ -104,6 +143,39 @@ const ja: Messages = {
   rangeUntilDayAfter: "明後日まで",
   weekdaysOnly: "土日祝をスキップ",
   weekend: "週末",
+  invitationsLabel: "招待への返答",
+  skipDeclined: "辞退した会議を開かない",
+  skipTentative: "仮承諾の会議を開かない",
+  skipNeedsAction: "未返答の会議を開かない",
+  hideSkippedInvitations: "開かない会議を一覧から隠す",
+  conflictPolicyLabel: "重なった会議",
+  conflictPreferAccepted: "承諾済みを優先",
+  conflictPreferOrganizer: "自分が主催を優先",
+  conflictPreferCalendar: "カレンダーを優先",
+  conflictAsk: "開かずに確認",
+  conflictCalendarLabel: "優先するカレンダー",
+  overlapsOtherMeeting: "他の会議と重なっています",
+  shareSettingsLabel: "設定の共有",
+  profileLabel: "プロファイル",
+  profileNone: "なし",
+  profileNamePlaceholder: "現在の設定に名前を付けて保存",
+  profileSave: "保存",
+  profileDelete: "このプロファイルを削除",
+  autoOpen: "会議を自動で開く",
+  trayMeetings: "メニューに表示する会議",
+  trayMeetingsOff: "表示しない",
+  trayMeetingsCount: (n) => `次の${n}件`,
+  pauseKeepsReminders: "一時停止中もリマインドする",
+  pausedUntil: (time) => `${time}まで自動オープンを停止中`,
+  pausedIndefinitely: "自動オープンを停止中",
+  resume: "再開",
+  exportSettings: "書き出す",
+  importSettings: "読み込む",
+  settingsImported: "設定を読み込みました。",
+  settingsSkippedKeys: "適用しなかった項目:",
+  syncFileLabel: "同期ファイル",
+  syncFileNone: "なし",
+  syncFileChoose: "選択",
   buyMeACoffee: "開発者にコーヒーを ☕",
 };
 
@@ -150,6 +222,9 @@ const en: Messages = {
   now: "Now",
   copyUrl: "Copy URL",
   copied: "Copied",
+  copyDialIn: "Copy dial-in",
+  meetingIdLabel: "Meeting ID",
+  passcodeLabel: "Passcode",
   trayMinSuffix: "m",
   tomorrow: "Tomorrow",
   trayCountdown: "Tray countdown",
@@ -164,6 +239,39 @@ const en: Messages = {
   rangeUntilDayAfter: "Through day after",
   weekdaysOnly: "Skip weekends & holidays",
   weekend: "Weekend",
+  invitationsLabel: "Invitations",
+  skipDeclined: "Skip declined",
+  skipTentative: "Skip tentative",
+  skipNeedsAction: "Skip unanswered",
+  hideSkippedInvitations: "Hide skipped from list",
+  conflictPolicyLabel: "Overlapping meetings",
+  conflictPreferAccepted: "Prefer accepted",
+  conflictPreferOrganizer: "Prefer ones I organize",
+  conflictPreferCalendar: "Prefer a calendar",
+  conflictAsk: "Open none, ask",
+  conflictCalendarLabel: "Preferred calendar",
+  overlapsOtherMeeting: "Overlaps another meeting",
+  shareSettingsLabel: "Share settings",
+  profileLabel: "Profile",
+  profileNone: "None",
+  profileNamePlaceholder: "Save current settings as…",
+  profileSave: "Save",
+  profileDelete: "Delete this profile",
+  autoOpen: "Auto-open meetings",
+  trayMeetings: "Meetings in menu",
+  trayMeetingsOff: "Off",
+  trayMeetingsCount: (n) => `Next ${n}`,
+  pauseKeepsReminders: "Remind me while paused",
+  pausedUntil: (time) => `Auto-open paused until ${time}`,
+  pausedIndefinitely: "Auto-open paused",
+  resume: "Resume",
+  exportSettings: "Export",
+  importSettings: "Import",
+  settingsImported: "Settings imported.",
+  settingsSkippedKeys: "Not applied:",
+  syncFileLabel: "Sync file",
+  syncFileNone: "None",
+  syncFileChoose: "Choose",
   buyMeACoffee: "Buy me a coffee ☕",
 };
 
@@ -1,5 +1,22 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { CalendarEvent, CalendarInfo } from "../types";
+import { listen } from "@tauri-apps/api/event";
+import type {
+  AppSettings,
+  CalendarEvent,
+  CalendarInfo,
+  Conflict,
+  EventOverrides,
+  EventsDiff,
+  ImportReport,
+  MeetingInfo,
+  MeetingOpened,
+  MeetingProvider,
+  OverrideAction,
+  Pause,
+  PauseLength,
+  PermissionChanged,
+  ReminderSent,
+} from "../types";
 
 export interface AppOption {
   id: string;
@@ -18,11 +35,91 @@ export const getCalendars = () => invoke<CalendarInfo[]>("get_calendars");
 export const getTodaysEvents = () =>
   invoke<CalendarEvent[]>("get_todays_events");
 
+// Events overlapping [start, end]; calendarIds defaults to the calendar filter
+export const getEventsInRange = (start: Date, end: Date, calendarIds?: string[]) =>
+  invoke<CalendarEvent[]>("get_events_in_range", {
+    start: start.toISOString(),
+    end: end.toISOString(),
+    calendarIds: calendarIds ?? null,
+  });
+
 export const forceSync = () => invoke<CalendarEvent[]>("force_sync");
 
+export const getEnabledCalendars = () =>
+  invoke<string[] | null>("get_enabled_calendars");
+
+export const setEnabledCalendars = (ids: string[]) =>
+  invoke("set_enabled_calendars", { ids });
+
+export const getConflicts = () => invoke<Conflict[]>("get_conflicts");
+
+export const getEventOverrides = () =>
+  invoke<EventOverrides>("get_event_overrides");
+
+export const setEventOverride = (
+  eventId: string,
+  seriesId: string | null,
+  action: OverrideAction,
+) => invoke<EventOverrides>("set_event_override", { eventId, seriesId, action });
+
+export const getMeetingProviders = () =>
+  invoke<MeetingProvider[]>("get_meeting_providers");
+
+export const getMeetingInfo = (event: CalendarEvent) =>
+  invoke<MeetingInfo | null>("get_meeting_info", { event });
+
+export const getSettings = () => invoke<AppSettings>("get_settings");
+
+/** Saves the given keys; resolves to the settings as stored (clamped to valid ranges). */
+export const updateSettings = (patch: Partial<AppSettings>) =>
+  invoke<AppSettings>("update_settings", { patch });
+
+// Each resolves to the settings after the change
+export const switchProfile = (name: string) => invoke<AppSettings>("switch_profile", { name });
+
+export const saveProfile = (name: string) => invoke<AppSettings>("save_profile", { name });
+
+export const deleteProfile = (name: string) => invoke<AppSettings>("delete_profile", { name });
+
+// Shareable settings file; machine-specific keys are left out / skipped
+export const exportSettings = (path: string) => invoke("export_settings", { path });
+
+export const importSettings = (path: string) =>
+  invoke<ImportReport>("import_settings", { path });
+
+export const getPause = () => invoke<Pause | null>("get_pause");
+
+export const pauseAutoOpen = (length: PauseLength) =>
+  invoke<Pause>("pause_auto_open", { length });
+
+export const resumeAutoOpen = () => invoke("resume_auto_open");
+
 export const openCalendarSettings = () => invoke("open_calendar_settings");
 
 export const getInstalledApps = () => invoke<AppOption[]>("get_installed_apps");
 
 export const openMeetingUrl = (url: string, account?: string | null) =>
   invoke("open_meeting_url", { url, account: account ?? null });
+
+// Backend events; each returns a promise of the unlisten function
+
+export const onEventsUpdated = (handler: (diff: EventsDiff) => void) =>
+  listen<EventsDiff>("events-updated", (e) => handler(e.payload));
+
+export const onPermissionChanged = (handler: (change: PermissionChanged) => void) =>
+  listen<PermissionChanged>("permission-changed", (e) => handler(e.payload));
+
+export const onMeetingOpened = (handler: (opened: MeetingOpened) => void) =>
+  listen<MeetingOpened>("meeting-opened", (e) => handler(e.payload));
+
+export const onConflictDetected = (handler: (conflict: Conflict) => void) =>
+  listen<Conflict>("conflict-detected", (e) => handler(e.payload));
+
+export const onSettingsChanged = (handler: (settings: AppSettings) => void) =>
+  listen<AppSettings>("settings-changed", (e) => handler(e.payload));
+
+export const onPauseChanged = (handler: (pause: Pause | null) => void) =>
+  listen<Pause | null>("pause-changed", (e) => handler(e.payload));
+
+export const onReminderSent = (handler: (reminder: ReminderSent) => void) =>
+  listen<ReminderSent>("reminder-sent", (e) => handler(e.payload));
@@ -1,4 +1,4 @@
-import { Calendar, Settings as SettingsIcon, X } from "lucide-react";
+import { Calendar, Pause as PauseIcon, Settings as SettingsIcon, X } from "lucide-react";
 import { useEffect, useMemo, useState } from "react";
 import { isEnabled } from "@tauri-apps/plugin-autostart";
 import { CalendarFilter } from "../components/CalendarFilter";
@@ -8,6 +8,8 @@ import { useCalendars } from "../hooks/useCalendars";
 import { useDisplaySettings } from "../hooks/useDisplaySettings";
 import { useEvents } from "../hooks/useEvents";
 import { t } from "../i18n";
+import { getConflicts, getPause, onPauseChanged, resumeAutoOpen } from "../lib/tauri";
+import type { Pause } from "../types";
 import { computeVisibleDates } from "../lib/dateRange";
 
 function IconButton({
@@ -53,7 +55,7 @@ function IconButton({
 }
 
 export function Home() {
-  const { events, loading } = useEvents();
+  const { events, loading, refresh } = useEvents();
   const { calendars, enabledIds, loaded, toggleCalendar } = useCalendars();
   const { range, weekdaysOnly, setRange, setWeekdaysOnly } = useDisplaySettings();
   const [showFilter, setShowFilter] = useState(false);
@@ -67,6 +69,24 @@ export function Home() {
     return () => clearInterval(id);
   }, []);
 
+  // Overlaps are computed in Rust with the same filters the scheduler uses
+  const [conflictIds, setConflictIds] = useState<Set<string>>(() => new Set());
+  useEffect(() => {
+    getConflicts()
+      .then((conflicts) => setConflictIds(new Set(conflicts.flatMap((c) => c.eventIds))))
+      .catch(() => setConflictIds(new Set()));
+  }, [events, enabledIds]);
+
+  // Paused from the tray menu; shown so a quiet morning isn't a surprise
+  const [pause, setPause] = useState<Pause | null>(null);
+  useEffect(() => {
+    getPause().then(setPause).catch(() => {});
+    const unlisten = onPauseChanged(setPause);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   useEffect(() => {
     isEnabled().then(setAutostart).catch(() => setAutostart(false));
   }, []);
@@ -119,11 +139,34 @@ export function Home() {
           />
         </div>
       </div>
+      {pause && (
+        <div style={styles.pauseBar}>
+          <PauseIcon size={12} strokeWidth={1.75} />
+          <span style={{ flex: 1 }}>
+            {pause.type === "until"
+              ? t.pausedUntil(
+                  new Date(pause.until).toLocaleString(undefined, {
+                    weekday: "short",
+                    hour: "2-digit",
+                    minute: "2-digit",
+                  })
+                )
+              : t.pausedIndefinitely}
+          </span>
+          <button
+            type="button"
+            onClick={() => resumeAutoOpen().catch(() => {})}
+            style={styles.resumeBtn}
+          >
+            {t.resume}
+          </button>
+        </div>
+      )}
       {showFilter && (
         <CalendarFilter
           calendars={calendars}
           enabledIds={enabledIds}
-          onToggle={toggleCalendar}
+          onToggle={(id) => toggleCalendar(id).then(refresh)}
         />
       )}
       {showSettings && (
@@ -134,9 +177,10 @@ export function Home() {
           onDisplayRangeChange={setRange}
           weekdaysOnly={weekdaysOnly}
           onWeekdaysOnlyChange={setWeekdaysOnly}
+          onEventFilterChange={refresh}
         />
       )}
-      <EventList events={filteredEvents} loading={loading} />
+      <EventList events={filteredEvents} loading={loading} conflictIds={conflictIds} />
     </div>
   );
 }
@@ -158,6 +202,24 @@ const styles: Record<string, React.CSSProperties> = {
     display: "flex",
     gap: 4,
   },
+  pauseBar: {
+    display: "flex",
+    alignItems: "center",
+    gap: 6,
+    padding: "6px 16px",
+    fontSize: 12,
+    color: "var(--text-secondary)",
+    borderBottom: "1px solid var(--divider)",
+  },
+  resumeBtn: {
+    fontSize: 12,
+    padding: "2px 8px",
+    borderRadius: 6,
+    border: "1px solid var(--border-color)",
+    background: "var(--bg-card)",
+    color: "var(--text-primary)",
+    cursor: "pointer",
+  },
   appName: {
     fontSize: 15,
     fontWeight: 600,
@@ -17,6 +17,75 @@ export interface CalendarEvent {
   calendarName: string | null;
   calendarAccountName: string | null;
   externalUrl: string | null;
+  meeting: MeetingLink | null;
+  attendees: Participant[];
+  organizer: Participant | null;
+  selfStatus: ParticipationStatus | null;
+  isRecurring: boolean;
+  seriesId: string | null;
+  alarms: Alarm[];
+  availability: Availability | null;
+  timeZone: string | null;
+}
+
+export type ParticipationStatus =
+  | "unknown"
+  | "needsAction"
+  | "accepted"
+  | "declined"
+  | "tentative"
+  | "delegated"
+  | "completed"
+  | "inProcess";
+
+export type ParticipantRole =
+  | "unknown"
+  | "required"
+  | "optional"
+  | "chair"
+  | "nonParticipant";
+
+export interface Participant {
+  name: string | null;
+  email: string | null;
+  role: ParticipantRole;
+  status: ParticipationStatus;
+  isSelf: boolean;
+}
+
+export type Availability = "busy" | "free" | "tentative" | "unavailable";
+
+export interface Alarm {
+  minutesBefore: number | null;
+  at: string | null;
+}
+
+export interface MeetingLink {
+  service: string;
+  name: string;
+  url: string;
+}
+
+export interface DialIn {
+  number: string;
+  pin: string | null;
+  passcode: string | null;
+  label: string | null;
+  telUri: string;
+}
+
+export interface MeetingInfo {
+  service: string | null;
+  name: string | null;
+  url: string | null;
+  meetingId: string | null;
+  passcode: string | null;
+  dialIns: DialIn[];
+}
+
+export interface MeetingProvider {
+  id: string;
+  name: string;
 }
 
 export interface CalendarInfo {
@@ -24,3 +93,174 @@ export interface CalendarInfo {
   title: string;
   sourceName: string;
 }
+
+export interface EventOverrides {
+  skipAutoOpen: string[];
+  snoozedUntil: Record<string, string>;
+  skippedSeries: string[];
+  /** Skipped series → when a synced event last belonged to it */
+  seriesSeen: Record<string, string>;
+}
+
+export type OverrideAction =
+  | { type: "skip" }
+  | { type: "snooze"; minutes: number }
+  | { type: "skipSeries" }
+  | { type: "clear" };
+
+export type RuleAction = "open" | "notifyOnly" | "ignore";
+
+export interface RuleMatch {
+  calendarIds?: string[];
+  accountName?: string;
+  titleRegex?: string;
+  descriptionRegex?: string;
+  services?: string[];
+  tentative?: boolean;
+  startTimeFrom?: string;
+  startTimeTo?: string;
+  minAttendees?: number;
+  maxAttendees?: number;
+}
+
+export interface Rule {
+  name?: string;
+  match?: RuleMatch;
+  action: RuleAction;
+  minutesBefore?: number;
+  openWith?: string;
+}
+
+export interface CustomProvider {
+  id: string;
+  name: string | null;
+  hosts: string[];
+  pathPattern: string | null;
+}
+
+export interface CalDavAccount {
+  url: string;
+  username: string;
+  /** Write-only: stored in the Keychain and never sent back */
+  password?: string;
+  name: string | null;
+}
+
+/** Profile values; the active profile's are the top-level settings */
+export interface Profile {
+  name: string;
+  enabledCalendars: string[] | null;
+  minutesBefore: number;
+  notificationMinutesBefore: number;
+  autoOpen: boolean;
+  openWith: Record<string, string>;
+}
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+export interface ProfileSchedule {
+  profile: string;
+  days?: Weekday[];
+  /** Local "HH:MM"; `to` is exclusive and may wrap past midnight */
+  from?: string | null;
+  to?: string | null;
+}
+
+/** Mirrors `Settings` in settings.rs; read and written via get/update_settings. */
+export interface AppSettings {
+  schemaVersion: number;
+  minutesBefore: number;
+  notificationMinutesBefore: number;
+  autoOpen: boolean;
+  pauseKeepsReminders: boolean;
+  trayCountdownMinutes: number;
+  trayMeetingCount: number;
+  schedulerWindowDays: number;
+  openWith: Record<string, string>;
+  customProviders: CustomProvider[];
+  rules: Rule[];
+  icsPaths: string[];
+  caldavAccounts: CalDavAccount[];
+  enabledCalendars: string[] | null;
+  skipDeclined: boolean;
+  skipTentative: boolean;
+  skipNeedsAction: boolean;
+  hideSkippedInvitations: boolean;
+  conflictPolicy: ConflictPolicy;
+  conflictCalendarId: string | null;
+  displayRange: 1 | 2 | 3;
+  weekdaysOnly: boolean;
+  /** Shared settings file re-imported whenever it changes */
+  syncFilePath: string | null;
+  profiles: Profile[];
+  activeProfile: string | null;
+  profileSchedule: ProfileSchedule[];
+}
+
+export type IssueKind = "unknown" | "invalid" | "local";
+
+/** A settings key that was not applied */
+export interface SettingsIssue {
+  key: string;
+  kind: IssueKind;
+  message: string;
+}
+
+export interface ImportReport {
+  settings: AppSettings;
+  issues: SettingsIssue[];
+}
+
+// Payloads of the Tauri events pushed by the backend
+
+export interface EventsDiff {
+  added: string[];
+  removed: string[];
+  changed: string[];
+}
+
+export type PermissionStatus =
+  | "granted"
+  | "denied"
+  | "not_determined"
+  | "restricted";
+
+export interface PermissionChanged {
+  status: PermissionStatus;
+}
+
+export interface MeetingOpened {
+  eventId: string;
+  summary: string;
+  url: string;
+  service: string | null;
+}
+
+export type ConflictPolicy =
+  | "preferOrganizer"
+  | "preferAccepted"
+  | "preferCalendar"
+  | "ask";
+
+/** Overlapping meetings; also the payload of `conflict-detected`. */
+export interface Conflict {
+  eventIds: string[];
+  start: string;
+  end: string;
+  /** Meeting the conflict policy would join; null means the user is asked */
+  preferred: string | null;
+}
+
+/** Auto-open paused from the tray (pause.rs) */
+export type Pause = { type: "until"; until: string } | { type: "indefinite" };
+
+export type PauseLength = "oneHour" | "untilTomorrow" | "untilResumed";
+
+export type ReminderKind = "upcoming" | "snoozed" | "joinNow";
+
+export interface ReminderSent {
+  eventId: string;
+  summary: string;
+  kind: ReminderKind;
+  minutesUntil: number;
+}